@@ -0,0 +1,150 @@
+//! A screen reader friendly way to play. Nothing is drawn: every change to the game is
+//! announced as a new line of text, and the board can be read out on request.
+
+use crate::analysis::Analysis;
+use crate::cursor::Cursor;
+use crate::describe;
+use crate::game::{Game, GameStatus, Rules};
+use crate::input::{read_action, Action};
+use crate::players::Players;
+use crate::review;
+use crate::solver::Solver;
+
+use crossterm::Result;
+use crossterm::terminal::{enable_raw_mode, disable_raw_mode};
+use std::io::Write;
+
+const INSTRUCTIONS: &str = "Tic Tac Toe. Use the arrow keys or h, j, k and l to move, \
+    Enter to mark a space, r to read the board, question mark for a hint, a to hear \
+    the outcome of every move and Escape to quit. In wild games, m switches between \
+    placing X and O. In numerical games, the number keys 1 to 9 choose the number to \
+    place, and m steps through the ones you have left. In morris games, once your \
+    marks are placed, Enter on one of them picks it up and Enter on a neighboring \
+    space slides it there.";
+
+/// Print a line of text. Raw mode is on, so the carriage return has to be explicit.
+fn say(line: &str) -> Result<()> {
+    let mut stdout = std::io::stdout();
+    write!(stdout, "{}\r\n", line)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Read out every row of the board
+fn say_board(game: &Game) -> Result<()> {
+    for line in describe::board(game) { say(&line)?; }
+    Ok(())
+}
+
+/// Read out every mistake in a finished game, with the board as it was before each
+fn say_review(game: &Game) -> Result<()> {
+    let blunders = review::blunders(game, &mut Solver::new());
+    say(&describe::review(blunders.len()))?;
+    for blunder in blunders.iter() {
+        say(&describe::blunder(blunder))?;
+        say(&format!("The board before move {}:", blunder.number))?;
+        say_board(&blunder.position)?;
+    }
+    Ok(())
+}
+
+/// Play a game announcing each cursor movement, move and change in status. Returns the
+/// game as it stood when it ended or the players quit. Lines
+/// wrap around the edges if `wrap` is set.
+pub(crate) fn play(players: &mut Players, rules: Rules, wrap: bool) -> Result<Game> {
+    let mut game = Game::with_rules(rules).with_wrap(wrap);
+    enable_raw_mode()?;
+    let result = run(&mut game, players);
+
+    // Read out the final board, and any mistakes, before handing the terminal back
+    say_board(&game)?;
+    if let Some(line) = describe::wrapped_line(&game) { say(&line)?; }
+    if let (Ok(()), GameStatus::Winner(_) | GameStatus::Draw) = (&result, game.status()) {
+        say_review(&game)?;
+    }
+    disable_raw_mode()?;
+    result.map(|_| game)
+}
+
+/// The game loop, from the first move until the game ends or a player quits
+fn run(game: &mut Game, players: &mut Players) -> Result<()> {
+    let mut solver = Solver::new();
+    players.new_game(game)?;
+    say(INSTRUCTIONS)?;
+    if let Some(line) = describe::rules(game.rules()) { say(line)?; }
+    if let Some(line) = describe::wrap(game) { say(line)?; }
+    say(&describe::status(&game.status()))?;
+    'game: while let GameStatus::Pending(player) = game.status() {
+
+        // Programs move on their own
+        if players.is_computer(player) {
+            let mv = players.play_computer_move(game)?;
+            say(&describe::mark(player, mv))?;
+            say(&describe::status(&game.status()))?;
+            continue 'game;
+        }
+
+        // While the game status is pending, there must be at least one available space
+        let mut gc = Cursor::first_available(game).unwrap();
+        let mut hint = None;
+        if let Some(line) = describe::vanishing(game) { say(&line)?; }
+        if let Some(line) = describe::numbers(game) { say(&line)?; }
+        say(&describe::cursor(game, gc.get_coordinate()))?;
+        loop {
+            match read_action()? {
+                Some(Action::Quit) => break 'game,
+                Some(Action::Shift(direction)) => {
+                    gc.shift(direction);
+                    say(&describe::cursor(game, gc.get_coordinate()))?;
+                },
+                Some(Action::ReadBoard) => say_board(game)?,
+                Some(Action::Analyze) => {
+                    let analysis = Analysis::new(game, &mut solver);
+                    for line in describe::analysis(player, analysis.outcomes()) { say(&line)?; }
+                },
+                Some(Action::Hint) => {
+                    // Asking again moves on to the next equally good move
+                    hint = solver.hint(game, hint.map(|(mv, _)| mv));
+                    if let Some((mv, outcome)) = hint {
+                        gc.move_to(mv.coord);
+                        gc.set_mark(mv.mark);
+                        gc.select(mv.from);
+                        say(&describe::hint(player, mv, outcome))?;
+                    }
+                },
+                Some(Action::ToggleMark) if game.marks().len() > 1 => {
+                    gc.toggle_mark(game);
+                    say(&format!("Placing {}.", gc.get_mark()))?;
+                },
+                Some(Action::ChooseMark(mark)) if game.marks().contains(&mark) => {
+                    gc.set_mark(mark);
+                    say(&format!("Placing {}.", mark))?;
+                },
+                Some(Action::ChooseMark(mark)) if game.marks().len() > 1 => {
+                    say(&format!("{} isn't yours to place.", mark))?;
+                },
+                Some(Action::Place) => {
+                    let mv = match (gc.choose(game), gc.get_selected()) {
+                        (Some(mv), _) => mv,
+                        (None, Some(from)) => {
+                            say(&format!("Picked up {}, choose where to slide it.", describe::coordinate(from)))?;
+                            continue;
+                        },
+                        (None, None) => { say("Put back down.")?; continue; },
+                    };
+                    match game.play(mv) {
+                        Ok(()) => {
+                            say(&describe::mark(player, mv))?;
+                            say(&describe::status(&game.status()))?;
+                            continue 'game;
+                        },
+                        Err(e) => say(&format!("{}, please try again!", e))?,
+                    }
+                },
+                Some(Action::ToggleMark) | Some(Action::ChooseMark(_)) | Some(Action::NextBoard)
+                    | Some(Action::PreviousBoard) | Some(Action::Leaderboard) | None => {},
+            }
+        }
+    }
+    Ok(())
+}
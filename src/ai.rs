@@ -0,0 +1,373 @@
+//! The built-in computer player, at a few levels of skill
+
+use crate::cube::{Cube, CubeCoordinate};
+use crate::game::{Coordinate, Game, GameStatus, Move, Player, Rules};
+use crate::gravity::{self, Gravity};
+use crate::notakto::{self, BoardMove, Notakto, Search};
+use crate::order::{self, OrderChaos, Role};
+use crate::quantum::{Quantum, Step};
+use crate::solver::{Outcome, Solver};
+use crate::three::{self, Three};
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+
+//--------------------------------------------------------------------------------------
+//-- Random numbers
+//--------------------------------------------------------------------------------------
+
+/// A small xorshift random number generator. Good enough to pick moves, and seedable
+/// so that tournaments can be repeated.
+#[derive(Debug, Clone)]
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        // The state must never be zero
+        Rng((seed ^ 0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    /// Seed from the clock, for when nobody asked for a particular seed
+    pub(crate) fn from_time() -> Self {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+        Rng::new(nanos as u64)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Pick one item from a slice at random
+    pub(crate) fn choose<T: Copy>(&mut self, items: &[T]) -> Option<T> {
+        if items.is_empty() { return None; }
+        let idx = (self.next_u64() % items.len() as u64) as usize;
+        Some(items[idx])
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Levels
+//--------------------------------------------------------------------------------------
+
+/// How well the computer plays
+/// - Random: Marks any empty space
+/// - Easy: Wins when it can do so right away, otherwise plays at random, though never
+///   completing a line that loses under misere rules
+/// - Medium: Like easy, but also blocks the opponent from winning right away, or under
+///   misere rules, avoids leaving itself with only losing moves
+/// - Hard: The strongest play there is for games too big to solve, which looks for
+///   more than medium does, and sometimes a few moves ahead, but isn't perfect
+/// - Perfect: Always plays one of the best moves, for games the solver or the notakto
+///   search can play out to the end
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum Level {
+    Random,
+    Easy,
+    Medium,
+    Hard,
+    Perfect,
+}
+
+impl Level {
+    pub(crate) const ALL: [Level; 5] = [Level::Random, Level::Easy, Level::Medium, Level::Hard, Level::Perfect];
+
+    /// Can this level play by these rules? Only games on the 3x3 board, and notakto on
+    /// more than one, are played perfectly. Hard is the best there is for the others.
+    pub(crate) fn plays(&self, rules: Rules) -> bool {
+        match self {
+            Level::Hard => !rules.is_classical(),
+            Level::Perfect => rules.is_classical(),
+            _ => true,
+        }
+    }
+
+    /// The strongest level that plays by these rules
+    pub(crate) fn strongest(rules: Rules) -> Self {
+        if rules.is_classical() { Level::Perfect } else { Level::Hard }
+    }
+
+    /// The level to play by these rules when this one is asked for: itself if it can,
+    /// or else the strongest that can, so perfect plays hard on the variants too big
+    /// to solve and hard plays perfectly on the 3x3 board
+    pub(crate) fn for_rules(&self, rules: Rules) -> Self {
+        if self.plays(rules) { *self } else { Level::strongest(rules) }
+    }
+
+    /// Look up a level by the name used on the command line
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "random" => Some(Level::Random),
+            "easy" => Some(Level::Easy),
+            "medium" => Some(Level::Medium),
+            "hard" => Some(Level::Hard),
+            "perfect" => Some(Level::Perfect),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            Level::Random => "random",
+            Level::Easy => "easy",
+            Level::Medium => "medium",
+            Level::Hard => "hard",
+            Level::Perfect => "perfect",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Ai
+//--------------------------------------------------------------------------------------
+
+/// A computer player
+pub(crate) struct Ai {
+    level: Level,
+    solver: Solver,
+    search: Search,  // For notakto on more than one board
+    rng: Rng,
+}
+
+impl Ai {
+    pub(crate) fn new(level: Level, rng: Rng) -> Self {
+        Ai { level, solver: Solver::new(), search: Search::new(), rng }
+    }
+
+    pub(crate) fn level(&self) -> Level {
+        self.level
+    }
+
+    /// Choose a move for the player to move. The game must still be pending.
+    pub(crate) fn choose_move(&mut self, game: &Game) -> Move {
+        let outcomes = self.solver.move_outcomes(game);
+        // Wins within two moves cover winning outright, and under misere rules, leaving
+        // the opponent nothing but moves that complete a line
+        let wins_now: Vec<_> = outcomes.iter()
+            .filter(|(_, outcome)| matches!(outcome, Outcome::Win(n) if *n <= 2))
+            .map(|(mv, _)| *mv)
+            .collect();
+        let not_losing = |within: u8| -> Vec<_> {
+            outcomes.iter()
+                .filter(|(_, outcome)| !matches!(outcome, Outcome::Loss(n) if *n <= within))
+                .map(|(mv, _)| *mv)
+                .collect()
+        };
+        let safe_now = not_losing(1);
+        let safe = not_losing(3);
+        let all: Vec<_> = outcomes.iter().map(|(mv, _)| *mv).collect();
+
+        let choice = match self.level {
+            Level::Random => self.rng.choose(&all),
+            Level::Easy => self.rng.choose(&wins_now)
+                .or_else(|| self.rng.choose(&safe_now))
+                .or_else(|| self.rng.choose(&all)),
+            Level::Medium => self.rng.choose(&wins_now)
+                .or_else(|| self.rng.choose(&safe))
+                .or_else(|| self.rng.choose(&all)),
+            Level::Hard | Level::Perfect => {
+                let best = self.solver.best_moves(game);
+                self.rng.choose(&best)
+            },
+        };
+        choice.expect("A pending game has at least one move")
+    }
+
+    /// Choose a move in a game of notakto on more than one board. The levels play as
+    /// they do on one board: killing the last board is losing right away, and leaving
+    /// the opponent nothing but that is winning.
+    pub(crate) fn choose_board_move(&mut self, notakto: &Notakto) -> BoardMove {
+        // Leaves the opponent nothing but moves that kill the last board
+        let forces_loss = |next: &Notakto| {
+            next.live_boards() > 0 && next.legal_moves().iter().all(|mv| next.after(*mv).live_boards() == 0)
+        };
+        let all = notakto.legal_moves();
+        let safe_now = notakto::safe_moves(notakto);
+        let wins_now: Vec<_> = all.iter().copied().filter(|mv| forces_loss(&notakto.after(*mv))).collect();
+        // Moves that don't lose right away, or let the opponent force a loss
+        let safe: Vec<_> = safe_now.iter()
+            .copied()
+            .filter(|mv| {
+                let next = notakto.after(*mv);
+                !next.legal_moves().iter().any(|reply| forces_loss(&next.after(*reply)))
+            })
+            .collect();
+
+        let choice = match self.level {
+            Level::Random => self.rng.choose(&all),
+            Level::Easy => self.rng.choose(&wins_now)
+                .or_else(|| self.rng.choose(&safe_now)),
+            Level::Medium => self.rng.choose(&wins_now)
+                .or_else(|| self.rng.choose(&safe))
+                .or_else(|| self.rng.choose(&safe_now)),
+            Level::Hard | Level::Perfect => {
+                let winning = self.search.winning_moves(notakto);
+                self.rng.choose(&winning).or_else(|| self.rng.choose(&safe_now))
+            },
+        };
+        choice.expect("A pending game has at least one move")
+    }
+
+    /// Choose a step in quantum tic-tac-toe. There's no search for perfect play, so
+    /// the medium and hard levels play alike: they collapse cycles in their own favor,
+    /// and never close a cycle the opponent can collapse into a win. Easy only collapses
+    /// well.
+    pub(crate) fn choose_quantum_step(&mut self, quantum: &Quantum) -> Step {
+        let me = quantum.current_player();
+        let score = |next: &Quantum| match next.status() {
+            GameStatus::Winner(winner) if winner == me => 1,
+            GameStatus::Winner(_) => -1,
+            _ => 0,
+        };
+        // After closing a cycle, the opponent collapses it however suits them best
+        let outcome = |step: Step| {
+            let next = quantum.after(step);
+            match next.pending_collapse() {
+                Some(_) => next.legal_steps().into_iter().map(|reply| score(&next.after(reply))).min().unwrap_or(0),
+                None => score(&next),
+            }
+        };
+        let all = quantum.legal_steps();
+        let outcomes: Vec<_> = all.iter().map(|step| outcome(*step)).collect();
+        let best = outcomes.iter().max().copied().unwrap_or(0);
+        let best_steps: Vec<_> = all.iter()
+            .zip(outcomes.iter())
+            .filter(|(_, outcome)| **outcome == best)
+            .map(|(step, _)| *step)
+            .collect();
+
+        let choice = match self.level {
+            Level::Random => self.rng.choose(&all),
+            Level::Easy if quantum.pending_collapse().is_some() => self.rng.choose(&best_steps),
+            Level::Easy => self.rng.choose(&all),
+            Level::Medium | Level::Hard | Level::Perfect => self.rng.choose(&best_steps),
+        };
+        choice.expect("A pending game has at least one move")
+    }
+
+    /// Choose a space in a 3D game. The cube is too big to solve, so every level but
+    /// random takes a win when there is one, medium also blocks the opponent's, and
+    /// hard then makes two threats at once if it can, or takes the space on the most
+    /// promising lines.
+    pub(crate) fn choose_cube_move(&mut self, cube: &Cube) -> CubeCoordinate {
+        let me = cube.current_player();
+        let mut opponent = me;
+        opponent.toggle();
+        let all = cube.available_moves();
+        let wins_now = cube.threats(me);
+        let blocks = cube.threats(opponent);
+        let forks: Vec<_> = all.iter().copied().filter(|point| cube.after(*point).threats(me).len() > 1).collect();
+        let best = all.iter().map(|point| cube.promise(*point)).max().unwrap_or(0);
+        let promising: Vec<_> = all.iter().copied().filter(|point| cube.promise(*point) == best).collect();
+
+        let choice = match self.level {
+            Level::Random => self.rng.choose(&all),
+            Level::Easy => self.rng.choose(&wins_now)
+                .or_else(|| self.rng.choose(&all)),
+            Level::Medium => self.rng.choose(&wins_now)
+                .or_else(|| self.rng.choose(&blocks))
+                .or_else(|| self.rng.choose(&all)),
+            Level::Hard | Level::Perfect => self.rng.choose(&wins_now)
+                .or_else(|| self.rng.choose(&blocks))
+                .or_else(|| self.rng.choose(&forks))
+                .or_else(|| self.rng.choose(&promising)),
+        };
+        choice.expect("A pending game has at least one move")
+    }
+
+    /// Choose a column to drop a mark into under gravity rules. The board is too big to
+    /// solve, so hard play searches a few moves ahead. Easy takes a win when there
+    /// is one, and medium also blocks the opponent's and never drops a mark the
+    /// opponent can win on top of.
+    pub(crate) fn choose_gravity_move(&mut self, gravity: &Gravity) -> usize {
+        let me = gravity.current_player();
+        let wins = |next: &Gravity| next.status() == GameStatus::Winner(me);
+        // Blocking means dropping into the column the opponent would win with
+        let loses = |next: &Gravity| {
+            next.open_columns().iter().any(|reply| matches!(next.after(*reply).status(), GameStatus::Winner(winner) if winner != me))
+        };
+        let all = gravity.open_columns();
+        let wins_now: Vec<_> = all.iter().copied().filter(|col| wins(&gravity.after(*col))).collect();
+        let safe: Vec<_> = all.iter().copied().filter(|col| !loses(&gravity.after(*col))).collect();
+
+        let choice = match self.level {
+            Level::Random => self.rng.choose(&all),
+            Level::Easy => self.rng.choose(&wins_now)
+                .or_else(|| self.rng.choose(&all)),
+            Level::Medium => self.rng.choose(&wins_now)
+                .or_else(|| self.rng.choose(&safe))
+                .or_else(|| self.rng.choose(&all)),
+            Level::Hard | Level::Perfect => self.rng.choose(&gravity::best_columns(gravity)),
+        };
+        choice.expect("A pending game has at least one move")
+    }
+
+    /// Choose a move in Order and Chaos. The board is too big to search, so every level
+    /// but random takes a win when there is one. Medium also never hands the opponent
+    /// a win on their next move, and hard then picks the move that leaves the most
+    /// open lines for Order, or the fewest for Chaos.
+    pub(crate) fn choose_order_move(&mut self, order: &OrderChaos) -> Move {
+        let me = order.current_player();
+        let wins = |next: &OrderChaos| next.status() == GameStatus::Winner(me);
+        let loses = |next: &OrderChaos| match Role::of(me) {
+            // Order fills any four in a row in kind
+            Role::Chaos => !next.threats().is_empty(),
+            // Order can only lose to the last move, if it doesn't line up five
+            Role::Order => next.legal_moves().iter().any(|reply| next.after(*reply).status() == GameStatus::Winner(Player::O)),
+        };
+        let all = order.legal_moves();
+        let wins_now: Vec<_> = all.iter().copied().filter(|mv| wins(&order.after(*mv))).collect();
+        let safe: Vec<_> = all.iter().copied().filter(|mv| !loses(&order.after(*mv))).collect();
+        let best = |moves: &[Move]| {
+            let sign = if Role::of(me) == Role::Order { 1 } else { -1 };
+            let scores: Vec<_> = moves.iter().map(|mv| sign * order::openness(&order.after(*mv))).collect();
+            let top = scores.iter().max().copied().unwrap_or(0);
+            moves.iter().zip(scores.iter()).filter(|(_, score)| **score == top).map(|(mv, _)| *mv).collect::<Vec<_>>()
+        };
+
+        let choice = match self.level {
+            Level::Random => self.rng.choose(&all),
+            Level::Easy => self.rng.choose(&wins_now)
+                .or_else(|| self.rng.choose(&all)),
+            Level::Medium => self.rng.choose(&wins_now)
+                .or_else(|| self.rng.choose(&safe))
+                .or_else(|| self.rng.choose(&all)),
+            Level::Hard | Level::Perfect => self.rng.choose(&wins_now)
+                .or_else(|| self.rng.choose(&best(&safe)))
+                .or_else(|| self.rng.choose(&best(&all))),
+        };
+        choice.expect("A pending game has at least one move")
+    }
+
+    /// Choose a space in a three-player game. Every level but random takes a win when
+    /// there is one. Medium also blocks the player who moves next, since the one after
+    /// them can still block their own threats. Hard searches a few moves ahead,
+    /// taking both opponents to be against it.
+    pub(crate) fn choose_three_move(&mut self, three: &Three) -> Coordinate {
+        let me = three.current_side();
+        let mut next = me;
+        next.next();
+        let all = three.available_moves();
+        let wins_now = three.threats(me);
+        let blocks = three.threats(next);
+
+        let choice = match self.level {
+            Level::Random => self.rng.choose(&all),
+            Level::Easy => self.rng.choose(&wins_now)
+                .or_else(|| self.rng.choose(&all)),
+            Level::Medium => self.rng.choose(&wins_now)
+                .or_else(|| self.rng.choose(&blocks))
+                .or_else(|| self.rng.choose(&all)),
+            Level::Hard | Level::Perfect => self.rng.choose(&wins_now)
+                .or_else(|| self.rng.choose(&three::best_moves(three))),
+        };
+        choice.expect("A pending game has at least one move")
+    }
+}
@@ -0,0 +1,64 @@
+//! An overlay that labels every empty space with the outcome of marking it, under
+//! perfect play from then on, for the player to move. When either mark may be placed,
+//! each space gets a label for each mark, or under numerical rules for each number
+//! left, and when marks slide, for each mark that can slide there.
+
+use crate::display::{CharMatrix, Draw, DrawWithColor, Glyphs, ROW_HEIGHT, COL_WIDTH};
+use crate::game::{Game, Move};
+use crate::solver::{Outcome, Solver};
+use crate::theme::Theme;
+
+use crossterm::Result;
+
+/// The row inside a space where the label goes, roughly the middle
+const LABEL_ROW: u16 = 6;
+
+/// The width of the inside of a space, not counting the grid
+const SPACE_WIDTH: u16 = 30;
+
+/// The outcome of each legal move, for the player to move
+pub(crate) struct Analysis {
+    outcomes: Vec<(Move, Outcome)>,  // Each legal move and its outcome
+    marks: usize,                    // How many marks the player may choose from
+}
+
+impl Analysis {
+    pub(crate) fn new(game: &Game, solver: &mut Solver) -> Self {
+        Analysis { outcomes: solver.move_outcomes(game), marks: game.marks().len() }
+    }
+
+    /// Each legal move and the outcome of making it
+    pub(crate) fn outcomes(&self) -> &[(Move, Outcome)] {
+        &self.outcomes
+    }
+}
+
+impl Draw for Analysis {
+    fn draw(&self, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> Result<()> {
+        for (idx, (mv, outcome)) in self.outcomes().iter().enumerate() {
+            let (row, col) = mv.coord.get_tuple();
+            // Moves into the same space get a line each, one above the other
+            let same_space = |(other, _): &&(Move, Outcome)| other.coord == mv.coord;
+            let above = self.outcomes[..idx].iter().filter(same_space).count() as u16;
+            let count = self.outcomes.iter().filter(same_space).count() as u16;
+            let line = LABEL_ROW + 2 * above + 1 - count;
+            let label = match (mv.from, self.marks) {
+                (Some(from), _) => {
+                    let (from_row, from_col) = from.get_tuple();
+                    format!("from {},{}: {}", from_row + 1, from_col + 1, outcome)
+                },
+                (None, 1) => outcome.to_string(),
+                (None, _) => format!("{}: {}", mv.mark, outcome),
+            };
+            let color = match outcome {
+                Outcome::Win(_) => theme.win,
+                Outcome::Draw => theme.label,
+                Outcome::Loss(_) => theme.draw,
+            };
+            let out_row = term_row + (row as u16 * ROW_HEIGHT) + line;
+            let out_col = term_col + (col as u16 * COL_WIDTH) + (SPACE_WIDTH - label.len() as u16) / 2;
+            CharMatrix::from(label.as_str()).draw_with_color(out_row, out_col, color, theme, glyphs)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,276 @@
+//! External programs that play the game, talking to it over their stdin and stdout.
+//!
+//! The protocol is line based, in the spirit of UCI for chess engines. Every line the
+//! game sends is one of:
+//!
+//! - `ttt` - Sent once, right after the bot is started
+//! - `newgame` - A new game is starting
+//! - `rules <rules>` - Right after `newgame`, if the game isn't played by standard
+//!   rules. `<rules>` is `misere` when completing three in a row loses, `wild` when
+//!   either player may place either mark, `notakto` when both players place X and
+//!   completing three in a row loses, `morris` when each player has three marks and
+//!   then slides them, `infinite` when placing a fourth mark removes the player's
+//!   oldest, or `numerical` when X places odd numbers and O even ones, and a line
+//!   summing to 15 wins.
+//! - `position <board> <player>` - The current position. `<board>` is nine characters,
+//!   row by row from the top left, each `x`, `o` or `.` for an empty space, or under
+//!   numerical rules the digit placed there. `<player>`
+//!   is `x` or `o`, whichever is to move. Under infinite rules, a third word lists the
+//!   marked spaces from the oldest mark to the newest, as digits from 0 to 8 row by
+//!   row, e.g. `40` when the center was marked before the top left.
+//! - `go` - Reply with a move for the last position sent
+//! - `quit` - The bot should exit
+//!
+//! And the bot replies with:
+//!
+//! - `id name <name>` - Optional, before `tttok`. The name the game shows for the bot.
+//! - `tttok` - In reply to `ttt`, once the bot is ready to play
+//! - `move <row> <col> [x|o|<number>]` - In reply to `go`, the 0-based row and column
+//!   to mark. The mark is only needed under wild rules, and defaults to the player's
+//!   own. Under numerical rules it's the number to place, and defaults to their
+//!   smallest left.
+//! - `move <from row> <from col> <row> <col>` - In reply to `go`, under morris rules
+//!   once the player's three marks are placed, the mark to slide and where to.
+//!
+//! Any other line from the bot is ignored, so bots are free to print debugging output.
+//! A bot that takes longer than `REPLY_TIMEOUT` to reply is given up on, however many
+//! other lines it prints in the meantime.
+
+use crate::error::BotError;
+use crate::game::{Coordinate, Game, Mark, Move, Rules};
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+/// How long a bot may take to answer `ttt` or `go`
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a bot is given to exit after `quit` before it's killed
+const QUIT_TIMEOUT: Duration = Duration::from_millis(200);
+
+
+//--------------------------------------------------------------------------------------
+//-- Protocol messages
+//--------------------------------------------------------------------------------------
+
+/// Describe a game position as sent in the `position` message, e.g. "x...o.... x",
+/// with the order the marks were placed in under infinite rules, e.g. "x...o.... x 40"
+pub(crate) fn position(game: &Game) -> String {
+    let board: String = game.iter()
+        .map(|space| match space.get_mark() {
+            Some(Mark::X) => 'x',
+            Some(Mark::O) => 'o',
+            Some(Mark::Number(n)) => char::from(b'0' + n),
+            None => '.',
+        })
+        .collect();
+    let player = game.current_player().to_string().to_ascii_lowercase();
+    if game.rules() != Rules::Infinite { return format!("{} {}", board, player); }
+
+    // Each mark was placed by the last move to its space
+    let history = game.history();
+    let mut order: Vec<_> = game.iter()
+        .filter(|space| space.get_mark().is_some())
+        .map(|space| space.get_coordinate())
+        .collect();
+    order.sort_by_key(|coord| history.iter().rposition(|mv| mv.coord == *coord));
+    let order: String = order.iter().map(|coord| {
+        let (row, col) = coord.get_tuple();
+        char::from(b'0' + (row * 3 + col) as u8)
+    }).collect();
+    format!("{} {} {}", board, player, order)
+}
+
+/// Parse the coordinate, and the mark if there is one, from a `move <row> <col>
+/// [x|o|<number>]` reply, if that's what the line is
+fn parse_move(line: &str) -> Option<(Coordinate, Option<Mark>)> {
+    let mut words = line.split_whitespace();
+    if words.next() != Some("move") { return None; }
+    let row = words.next()?.parse().ok()?;
+    let col = words.next()?.parse().ok()?;
+    let mark = match words.next() {
+        None => None,
+        Some("x") => Some(Mark::X),
+        Some("o") => Some(Mark::O),
+        Some(word) => match word.parse() {
+            Ok(n @ 1..=9) => Some(Mark::Number(n)),
+            _ => return None,
+        },
+    };
+    if words.next().is_some() || row > 2 || col > 2 { return None; }
+    Some((Coordinate::new(row, col), mark))
+}
+
+/// Parse the spaces from a `move <from row> <from col> <row> <col>` reply, if that's
+/// what the line is
+fn parse_slide(line: &str) -> Option<(Coordinate, Coordinate)> {
+    let mut words = line.split_whitespace();
+    if words.next() != Some("move") { return None; }
+    let numbers: Vec<usize> = words.map(|word| word.parse().ok()).collect::<Option<_>>()?;
+    match numbers.as_slice() {
+        [from_row, from_col, row, col] if numbers.iter().all(|n| *n <= 2) => {
+            Some((Coordinate::new(*from_row, *from_col), Coordinate::new(*row, *col)))
+        },
+        _ => None,
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Bot
+//--------------------------------------------------------------------------------------
+
+/// A running bot program
+pub(crate) struct Bot {
+    name: String,                             // From `id name`, or the program name
+    command: String,                          // The program and its arguments, as given
+    child: Child,                             // The bot process
+    stdin: ChildStdin,                        // Lines sent to the bot
+    lines: Receiver<std::io::Result<String>>, // Lines read from the bot, by a thread
+}
+
+impl Bot {
+    /// Start a bot from a program and its arguments and wait for it to be ready
+    pub(crate) fn spawn(command: &[String]) -> Result<Self, BotError> {
+        let program = command.first().ok_or_else(|| BotError::Spawn(String::new(), "no command given".into()))?;
+        let mut child = Command::new(program)
+            .args(&command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| BotError::Spawn(command.join(" "), e.to_string()))?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+
+        // Read the bot's output on a separate thread, so a stuck bot can time out
+        let (sender, lines) = channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                if sender.send(line).is_err() { break; }
+            }
+        });
+
+        let name = std::path::Path::new(program)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| program.clone());
+        let mut bot = Bot { name, command: command.join(" "), child, stdin, lines };
+        bot.send("ttt")?;
+        let deadline = Instant::now() + REPLY_TIMEOUT;
+        loop {
+            let line = bot.receive(deadline)?;
+            if line == "tttok" { break; }
+            if let Some(name) = line.strip_prefix("id name ") {
+                bot.name = name.trim().to_string();
+            }
+        }
+        Ok(bot)
+    }
+
+    /// The name of the bot
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The command the bot was started with, its program and arguments
+    pub(crate) fn command(&self) -> &str {
+        &self.command
+    }
+
+    /// Tell the bot a new game is starting, and what rules it's played by
+    pub(crate) fn new_game(&mut self, rules: Rules) -> Result<(), BotError> {
+        self.send("newgame")?;
+        match rules {
+            Rules::Standard => Ok(()),
+            rules => self.send(&format!("rules {}", rules)),
+        }
+    }
+
+    /// Send the bot the current position and wait for its move. The move is checked
+    /// against the protocol, but not against the game.
+    pub(crate) fn choose_move(&mut self, game: &Game) -> Result<Move, BotError> {
+        self.send(&format!("position {}", position(game)))?;
+        self.send("go")?;
+        let deadline = Instant::now() + REPLY_TIMEOUT;
+        loop {
+            let line = self.receive(deadline)?;
+            if let Some((from, coord)) = parse_slide(&line) {
+                return Ok(Move::slide(from, coord, game.usual_mark()));
+            }
+            if line.starts_with("move") {
+                let (coord, mark) = parse_move(&line).ok_or_else(|| BotError::Protocol(self.name.clone(), line))?;
+                return Ok(Move::new(coord, mark.unwrap_or_else(|| game.usual_mark())));
+            }
+        }
+    }
+
+    fn send(&mut self, line: &str) -> Result<(), BotError> {
+        writeln!(self.stdin, "{}", line)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|_| BotError::Exited(self.name.clone()))
+    }
+
+    /// The next line from the bot, if it comes before the deadline for its reply
+    fn receive(&mut self, deadline: Instant) -> Result<String, BotError> {
+        match self.lines.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(Ok(line)) => Ok(line.trim().to_string()),
+            Ok(Err(_)) | Err(RecvTimeoutError::Disconnected) => Err(BotError::Exited(self.name.clone())),
+            Err(RecvTimeoutError::Timeout) => Err(BotError::Timeout(self.name.clone())),
+        }
+    }
+}
+
+impl Drop for Bot {
+    fn drop(&mut self) {
+        // Ask nicely, then insist
+        let _ = self.send("quit");
+        let deadline = Instant::now() + QUIT_TIMEOUT;
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.child.try_wait() { return; }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Tests
+//--------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position() {
+        let moves: Vec<Coordinate> = vec![(0, 0).into(), (1, 1).into(), (2, 2).into()];
+        let game = Game::from(&moves).expect("Failed to create game.");
+        assert_eq!(position(&game), "x...o...x o");
+
+        // Under infinite rules, X's oldest mark is gone and the order is sent too
+        let mut game = Game::with_rules(Rules::Infinite);
+        for coord in [(1, 1), (0, 0), (0, 2), (2, 0), (2, 1), (1, 2), (1, 0)] {
+            game.add_move(coord.into()).expect("Failed to add move.");
+        }
+        assert_eq!(position(&game), "o.xx.oox. o 026753");
+    }
+
+    #[test]
+    fn test_parse_move() {
+        assert_eq!(parse_move("move 2 0"), Some((Coordinate::new(2, 0), None)));
+        assert_eq!(parse_move("move 0 1 o"), Some((Coordinate::new(0, 1), Some(Mark::O))));
+        assert_eq!(parse_move("move 3 0"), None);
+        assert_eq!(parse_move("move 1"), None);
+        assert_eq!(parse_move("move 1 1 7"), Some((Coordinate::new(1, 1), Some(Mark::Number(7)))));
+        assert_eq!(parse_move("move 1 1 0"), None);
+        assert_eq!(parse_move("info thinking"), None);
+        assert_eq!(parse_slide("move 0 0 1 0"), Some((Coordinate::new(0, 0), Coordinate::new(1, 0))));
+        assert_eq!(parse_slide("move 0 0 1 3"), None);
+        assert_eq!(parse_slide("move 1 1"), None);
+    }
+}
@@ -0,0 +1,405 @@
+//! Tic-tac-toe in three dimensions: a 3x3x3 cube with 49 winning lines, or Qubic, a
+//! 4x4x4 cube with 76. Lines run along rows, columns and diagonals of each layer, down
+//! through the layers, and across them diagonally, corner to corner included.
+
+use crate::ai::Ai;
+use crate::cursor::Cursor;
+use crate::describe;
+use crate::display::{tiny_grid, CharMatrix, TINY_CURSOR, TINY_ROW_HEIGHT, TINY_COL_WIDTH, Draw, DrawWithColor, Glyphs};
+use crate::error::{GameError, Result};
+use crate::game::{Coordinate, GameStatus, Mark, Player, Rules};
+use crate::input::Action;
+use crate::plain;
+use crate::theme::Theme;
+use crate::variant::Variant;
+
+/// The columns between one layer and the next, when they're drawn side by side
+const LAYER_GAP: u16 = 4;
+
+
+//--------------------------------------------------------------------------------------
+//-- Coordinates
+//--------------------------------------------------------------------------------------
+
+/// A space in the cube: a layer, and a row and column on it
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) struct CubeCoordinate {
+    pub(crate) layer: usize,       // The layer, counting from the top
+    pub(crate) coord: Coordinate,  // The row and column on the layer
+}
+
+impl CubeCoordinate {
+    pub(crate) fn new(layer: usize, row: usize, col: usize) -> Self {
+        CubeCoordinate { layer, coord: Coordinate::new(row, col) }
+    }
+
+    /// Return the layer, row and column
+    pub(crate) fn get_tuple(&self) -> (usize, usize, usize) {
+        let (row, col) = self.coord.into();
+        (self.layer, row, col)
+    }
+}
+
+impl From<(usize, usize, usize)> for CubeCoordinate {
+    fn from((layer, row, col): (usize, usize, usize)) -> Self {
+        CubeCoordinate::new(layer, row, col)
+    }
+}
+
+/// Every winning line in a cube `size` spaces along each edge. Each of the 13
+/// directions through the cube, counting a direction and its reverse once, gives a
+/// line from every space on the faces it starts from.
+pub(crate) fn lines(size: usize) -> Vec<Vec<CubeCoordinate>> {
+    let steps: [isize; 3] = [-1, 0, 1];
+    // A line along an axis may start anywhere on it, forward from the first space or
+    // back from the last
+    let starts = |step: isize| match step {
+        0 => (0..size).collect(),
+        1 => vec![0],
+        _ => vec![size - 1],
+    };
+    let mut lines = Vec::new();
+    for down in steps {
+        for across in steps {
+            for deep in steps {
+                // Each line once: the first step that isn't zero goes forward
+                match [deep, down, across].iter().find(|step| **step != 0) {
+                    Some(1) => {},
+                    _ => continue,
+                }
+                for layer in starts(deep) {
+                    for row in starts(down) {
+                        for col in starts(across) {
+                            let at = |start: usize, step: isize, idx: usize| (start as isize + step * idx as isize) as usize;
+                            lines.push((0..size)
+                                .map(|idx| CubeCoordinate::new(at(layer, deep, idx), at(row, down, idx), at(col, across, idx)))
+                                .collect());
+                        }
+                    }
+                }
+            }
+        }
+    }
+    lines
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Cube
+//--------------------------------------------------------------------------------------
+
+/// A game of 3D tic-tac-toe
+#[derive(Debug, Clone)]
+pub(crate) struct Cube {
+    size: usize,                       // The spaces along each edge, and the layers
+    board: Vec<Option<Player>>,        // Whose mark is in each space, layer by layer, row by row
+    lines: Vec<Vec<CubeCoordinate>>,   // Every winning line, looked up rather than searched for
+    moves: Vec<CubeCoordinate>,        // Every move so far, in order
+    player: Player,                    // The current player
+}
+
+impl Cube {
+    /// A new game by the given rules: 3D for a 3x3x3 cube, or Qubic for 4x4x4
+    pub(crate) fn with_rules(rules: Rules) -> Self {
+        let size = if rules == Rules::Qubic { 4 } else { 3 };
+        Cube { size, board: vec![None; size * size * size], lines: lines(size), moves: Vec::new(), player: Player::X }
+    }
+
+    /// Return the rules the game is played by
+    pub(crate) fn rules(&self) -> Rules {
+        if self.size == 4 { Rules::Qubic } else { Rules::Cube }
+    }
+
+    /// The spaces along each edge of the cube, and the number of layers
+    pub(crate) fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Return the current player
+    pub(crate) fn current_player(&self) -> Player {
+        self.player
+    }
+
+    /// Return every move made so far, in order
+    pub(crate) fn history(&self) -> &[CubeCoordinate] {
+        &self.moves
+    }
+
+    /// Whose mark is in a space, if anyone's
+    pub(crate) fn get_mark(&self, point: CubeCoordinate) -> Option<Player> {
+        self.board[self.index(point)]
+    }
+
+    fn index(&self, point: CubeCoordinate) -> usize {
+        let (layer, row, col) = point.get_tuple();
+        (layer * self.size + row) * self.size + col
+    }
+
+    /// Every empty space, layer by layer, left to right, top to bottom
+    pub(crate) fn available_moves(&self) -> Vec<CubeCoordinate> {
+        let size = self.size;
+        (0..size * size * size)
+            .map(|idx| CubeCoordinate::new(idx / (size * size), idx / size % size, idx % size))
+            .filter(|point| self.get_mark(*point).is_none())
+            .collect()
+    }
+
+    /// Place the current player's mark in an empty space. Returns an error if the
+    /// space is occupied or off the board.
+    pub(crate) fn play(&mut self, point: CubeCoordinate) -> Result<()> {
+        let (layer, row, col) = point.get_tuple();
+        if layer >= self.size || row >= self.size || col >= self.size { return Err(GameError::OffBoard); }
+        if self.get_mark(point).is_some() { return Err(GameError::SpaceOccupied); }
+        let idx = self.index(point);
+        self.board[idx] = Some(self.player);
+        self.moves.push(point);
+        self.player.toggle();
+        Ok(())
+    }
+
+    /// The game after a move, which must be to an empty space
+    pub(crate) fn after(&self, point: CubeCoordinate) -> Cube {
+        let mut next = self.clone();
+        next.play(point).expect("Empty spaces can be played");
+        next
+    }
+
+    /// The first line filled with one player's marks, if there is one
+    pub(crate) fn line(&self) -> Option<&[CubeCoordinate]> {
+        self.lines.iter()
+            .find(|line| {
+                let first = self.get_mark(line[0]);
+                first.is_some() && line.iter().all(|point| self.get_mark(*point) == first)
+            })
+            .map(|line| line.as_slice())
+    }
+
+    /// The empty spaces where `player` completes a line with one more mark
+    pub(crate) fn threats(&self, player: Player) -> Vec<CubeCoordinate> {
+        let mut threats = Vec::new();
+        for line in self.lines.iter() {
+            let mine = line.iter().filter(|point| self.get_mark(**point) == Some(player)).count();
+            let gap = line.iter().find(|point| self.get_mark(**point).is_none());
+            if let (true, Some(gap)) = (mine == self.size - 1, gap) {
+                if !threats.contains(gap) { threats.push(*gap); }
+            }
+        }
+        threats
+    }
+
+    /// How promising an empty space looks for the player to move: every line through
+    /// it that the opponent hasn't blocked counts for more the more of the player's
+    /// marks it holds, and every line only the opponent holds counts too, for blocking
+    /// it
+    pub(crate) fn promise(&self, point: CubeCoordinate) -> i32 {
+        self.lines.iter()
+            .filter(|line| line.contains(&point))
+            .map(|line| {
+                let count = |player| line.iter().filter(|point| self.get_mark(**point) == Some(player)).count() as u32;
+                let mut opponent = self.player;
+                opponent.toggle();
+                match (count(self.player), count(opponent)) {
+                    (mine, 0) => 2 * 4_i32.pow(mine),
+                    (0, theirs) => 4_i32.pow(theirs),
+                    _ => 0,
+                }
+            })
+            .sum()
+    }
+
+    /// Determine and return the current status of the game
+    pub(crate) fn status(&self) -> GameStatus {
+        if let Some(line) = self.line() {
+            return GameStatus::Winner(self.get_mark(line[0]).expect("A line is made of marks"));
+        }
+        if self.moves.len() == self.board.len() { return GameStatus::Draw; }
+        GameStatus::Pending(self.player)
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Drawing
+//--------------------------------------------------------------------------------------
+
+/// The cube as the players see it, each layer a small grid of letters, side by side
+/// from the top layer on the left, with the winning line picked out
+pub(crate) struct View<'a> {
+    cube: &'a Cube,
+    cursor: Option<CubeCoordinate>,  // The space the cursor is on, if it's a person's turn
+}
+
+impl<'a> View<'a> {
+    pub(crate) fn new(cube: &'a Cube, cursor: Option<CubeCoordinate>) -> Self {
+        View { cube, cursor }
+    }
+}
+
+impl Draw for View<'_> {
+    fn draw(&self, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> crossterm::Result<()> {
+        let size = self.cube.size();
+        let layer_width = size as u16 * TINY_COL_WIDTH + 1 + LAYER_GAP;
+        // The layers go under their labels
+        let origin = |point: CubeCoordinate| {
+            let (layer, row, col) = point.get_tuple();
+            (term_row + 1 + row as u16 * TINY_ROW_HEIGHT, term_col + layer as u16 * layer_width + col as u16 * TINY_COL_WIDTH)
+        };
+
+        for layer in 0..size {
+            let color = match self.cursor {
+                Some(cursor) if cursor.layer == layer => theme.cursor,
+                _ => theme.label,
+            };
+            let label = format!("Layer {}", layer + 1);
+            let out_col = term_col + layer as u16 * layer_width;
+            CharMatrix::from(label.as_str()).draw_with_color(term_row, out_col, color, theme, glyphs)?;
+            tiny_grid(size, size).draw_with_color(term_row + 1, out_col, theme.grid, theme, glyphs)?;
+        }
+
+        let line = self.cube.line().unwrap_or_default();
+        for point in self.cube.history() {
+            let player = self.cube.get_mark(*point).expect("Marks stay where they're played");
+            let color = if line.contains(point) { theme.win } else { theme.player(player) };
+            let (out_row, out_col) = origin(*point);
+            CharMatrix::from(player.to_string().as_str()).draw_with_color(out_row + 1, out_col + 2, color, theme, glyphs)?;
+        }
+
+        if let Some(point) = self.cursor {
+            let (out_row, out_col) = origin(point);
+            if self.cube.get_mark(point).is_none() {
+                let mark = self.cube.current_player().to_string();
+                CharMatrix::from(mark.as_str()).draw_with_color(out_row + 1, out_col + 2, theme.cursor, theme, glyphs)?;
+            }
+            TINY_CURSOR.draw_with_color(out_row, out_col, theme.cursor, theme, glyphs)?;
+        }
+        self.cube.status().draw(term_row, term_col + 104, theme, glyphs)
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Variant
+//--------------------------------------------------------------------------------------
+
+impl Variant for Cube {
+    type Side = Player;
+    type Move = CubeCoordinate;
+    type Search = ();
+
+    fn rules(&self) -> Rules {
+        Cube::rules(self)
+    }
+
+    fn status(&self) -> GameStatus {
+        Cube::status(self)
+    }
+
+    fn turn(&self) -> Player {
+        self.player
+    }
+
+    fn moves(&self) -> usize {
+        self.moves.len()
+    }
+
+    fn play(&mut self, point: CubeCoordinate) -> Result<()> {
+        Cube::play(self, point)
+    }
+
+    fn choose(&self, ai: &mut Ai) -> CubeCoordinate {
+        ai.choose_cube_move(self)
+    }
+
+    fn describe_move(&self, player: Player, point: CubeCoordinate) -> String {
+        describe::cube_mark(player, point)
+    }
+
+    fn instructions(&self) -> &'static str {
+        plain::CUBE_INSTRUCTIONS
+    }
+
+    fn grid(&self) -> Vec<String> {
+        plain::cube_grid(self)
+    }
+
+    fn parse(&self, line: &str) -> Option<CubeCoordinate> {
+        plain::parse_cube_move(line, self.size)
+    }
+
+    fn draw(&self, cursor: Option<&Cursor>, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> crossterm::Result<()> {
+        View::new(self, cursor.map(point)).draw(term_row, term_col, theme, glyphs)
+    }
+
+    /// The cursor's board is the layer, and it stays where it was from turn to turn
+    fn cursor(&self, previous: Option<Cursor>) -> Cursor {
+        previous.unwrap_or_else(|| Cursor::new(Mark::X, 0, 0))
+    }
+
+    fn act(&self, cursor: &mut Cursor, action: Action) -> Option<CubeCoordinate> {
+        match action {
+            Action::Shift(direction) => cursor.shift_on(direction, self.size),
+            Action::NextBoard => cursor.shift_board(true, self.size),
+            Action::PreviousBoard => cursor.shift_board(false, self.size),
+            Action::Place => return Some(point(cursor)),
+            _ => {},
+        }
+        None
+    }
+
+    fn prompt(&self, cursor: &Cursor) -> Option<String> {
+        Some(describe::cube_coordinate(point(cursor)))
+    }
+
+    fn help(&self) -> Option<&'static str> {
+        Some("[ and ] move between layers, the arrows within one.")
+    }
+}
+
+/// The space under the cursor, on the layer that is the cursor's board
+fn point(cursor: &Cursor) -> CubeCoordinate {
+    CubeCoordinate { layer: cursor.get_board(), coord: cursor.get_coordinate() }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Tests
+//--------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variant::play;
+
+    #[test]
+    fn test_lines() {
+        assert_eq!(lines(3).len(), 49);
+        assert_eq!(lines(4).len(), 76);
+        // A corner to corner diagonal through the middle of the cube
+        let diagonal: Vec<CubeCoordinate> = vec![(0, 0, 2).into(), (1, 1, 1).into(), (2, 2, 0).into()];
+        assert!(lines(3).contains(&diagonal));
+    }
+
+    #[test]
+    fn test_win_across_layers() {
+        // X makes a diagonal down through the layers of Qubic, while O fills a row
+        let mut cube = Cube::with_rules(Rules::Qubic);
+        play(&mut cube, &[(0, 0, 0), (3, 3, 0), (1, 1, 1), (3, 3, 1), (2, 2, 2), (3, 3, 2)].map(CubeCoordinate::from));
+        assert_eq!(cube.threats(Player::X), vec![CubeCoordinate::new(3, 3, 3)]);
+        assert_eq!(cube.threats(Player::O), vec![CubeCoordinate::new(3, 3, 3)]);
+        assert_eq!(cube.play((1, 1, 1).into()), Err(GameError::SpaceOccupied));
+        assert_eq!(cube.play((0, 0, 4).into()), Err(GameError::OffBoard));
+        assert_eq!(cube.status(), GameStatus::Pending(Player::X));
+        play(&mut cube, &[(3, 3, 3)].map(CubeCoordinate::from));
+        assert_eq!(cube.status(), GameStatus::Winner(Player::X));
+        assert_eq!(cube.line().map(|line| line.len()), Some(4));
+    }
+
+    #[test]
+    fn test_promise() {
+        // The middle of the 3x3x3 cube is on 13 lines, more than any other space
+        let cube = Cube::with_rules(Rules::Cube);
+        let best = cube.available_moves().into_iter().max_by_key(|point| cube.promise(*point));
+        assert_eq!(best, Some(CubeCoordinate::new(1, 1, 1)));
+        assert_eq!(cube.promise((1, 1, 1).into()), 13 * 2);
+    }
+}
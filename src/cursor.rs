@@ -1,9 +1,11 @@
-use crate::display::{BIG_CURSOR, BIG_X, BIG_O, ROW_HEIGHT, COL_WIDTH, Draw, DrawWithColor};
-use crate::game::{Coordinate, Game, Player};
+use crate::display::{BIG_CURSOR, ROW_HEIGHT, COL_WIDTH, Draw, DrawWithColor, Glyphs};
+use crate::game::{Coordinate, Game, Mark, Move};
+use crate::notakto::{BoardMove, Notakto};
+use crate::theme::Theme;
 
 use crossterm::Result;
-use crossterm::style::Color;
 
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub(crate) enum Direction {
     Up,
     Down,
@@ -12,50 +14,141 @@ pub(crate) enum Direction {
 }
 
 pub(crate) struct Cursor {
-    player: Player,
+    mark: Mark,
     coordinate: Coordinate,
+    board: usize,                    // The board the cursor is on, when there's more than one
+    selected: Option<Coordinate>,    // The mark picked up to slide, under morris rules
 }
 
 impl Cursor {
-    pub(crate) fn new(player: Player, row: usize, col: usize) -> Self {
+    pub(crate) fn new(mark: Mark, row: usize, col: usize) -> Self {
         let coordinate = Coordinate::new(row, col);
-        Cursor { player, coordinate }
+        Cursor { mark, coordinate, board: 0, selected: None }
     }
 
     pub(crate) fn first_available(game: &Game) -> Option<Self> {
-        let player = game.current_player();
+        let mark = game.usual_mark();
         for space in game.iter() {
             if space.get_mark().is_none() { 
                 let (row, col) = space.get_coordinate().into();
-                return Some(Cursor::new(player, row, col));
+                return Some(Cursor::new(mark, row, col));
             }
         }
         None
     }
 
+    /// The first empty space on the first board still in play, for notakto
+    pub(crate) fn first_available_board(notakto: &Notakto) -> Option<Self> {
+        let board = (0..notakto.boards().len()).find(|board| !notakto.is_dead(*board))?;
+        let mut cursor = Cursor::first_available(&notakto.boards()[board])?;
+        cursor.board = board;
+        Some(cursor)
+    }
+
     pub(crate) fn shift(&mut self, direction: Direction) {
+        self.shift_on(direction, 3);
+    }
+
+    /// Move one space on a board `size` spaces across, wrapping around the edges
+    pub(crate) fn shift_on(&mut self, direction: Direction, size: usize) {
         let (mut row, mut col) = self.coordinate.into(); 
         match direction {
-            Direction::Up    => if row == 0 { row = 2 } else { row -= 1 },
-            Direction::Left  => if col == 0 { col = 2 } else { col -= 1 },
-            Direction::Down  => if row == 2 { row = 0 } else { row += 1 },
-            Direction::Right => if col == 2 { col = 0 } else { col += 1 },
+            Direction::Up    => if row == 0 { row = size - 1 } else { row -= 1 },
+            Direction::Left  => if col == 0 { col = size - 1 } else { col -= 1 },
+            Direction::Down  => if row == size - 1 { row = 0 } else { row += 1 },
+            Direction::Right => if col == size - 1 { col = 0 } else { col += 1 },
         };
         self.coordinate = Coordinate::new(row, col);
     }
 
+    pub(crate) fn move_to(&mut self, coordinate: Coordinate) {
+        self.coordinate = coordinate;
+    }
+
     pub(crate) fn get_coordinate(&self) -> Coordinate {
         self.coordinate
     }
+
+    /// Switch to the next mark the current player may place, if they have a choice
+    pub(crate) fn toggle_mark(&mut self, game: &Game) {
+        let marks = game.marks();
+        let idx = marks.iter().position(|mark| *mark == self.mark).map_or(0, |idx| idx + 1);
+        self.mark = marks[idx % marks.len()];
+    }
+
+    /// Place the mark shown in the cursor
+    pub(crate) fn set_mark(&mut self, mark: Mark) {
+        self.mark = mark;
+    }
+
+    pub(crate) fn get_mark(&self) -> Mark {
+        self.mark
+    }
+
+    /// The move the cursor is pointing at: its space, with its mark, slid from the
+    /// selected space if there is one
+    pub(crate) fn get_move(&self) -> Move {
+        Move { from: self.selected, ..Move::new(self.coordinate, self.mark) }
+    }
+
+    /// Pick up the mark on a space to slide it, or put it back down with `None`
+    pub(crate) fn select(&mut self, from: Option<Coordinate>) {
+        self.selected = from;
+    }
+
+    pub(crate) fn get_selected(&self) -> Option<Coordinate> {
+        self.selected
+    }
+
+    /// Act on the space under the cursor, returning the move to play if there is one
+    /// yet. Once marks slide, choosing one of your own marks picks it up, or puts it
+    /// back down if it was already picked up, and choosing another space then slides
+    /// it there.
+    pub(crate) fn choose(&mut self, game: &Game) -> Option<Move> {
+        if game.must_slide() && game.get_space(self.coordinate).get_mark() == Some(self.mark) {
+            let picked_up = self.selected == Some(self.coordinate);
+            self.selected = if picked_up { None } else { Some(self.coordinate) };
+            return None;
+        }
+        Some(self.get_move())
+    }
+
+    /// Move to the next board, or the previous one, out of `count`. Dead boards aren't
+    /// skipped, so they can still be looked at.
+    pub(crate) fn shift_board(&mut self, forward: bool, count: usize) {
+        self.board = if forward { (self.board + 1) % count } else { (self.board + count - 1) % count };
+    }
+
+    /// Move to a space on one of the boards
+    pub(crate) fn move_to_board(&mut self, mv: BoardMove) {
+        self.board = mv.board;
+        self.coordinate = mv.coord;
+    }
+
+    pub(crate) fn get_board(&self) -> usize {
+        self.board
+    }
+
+    /// The notakto move the cursor is pointing at: its board and space
+    pub(crate) fn get_board_move(&self) -> BoardMove {
+        BoardMove::new(self.board, self.coordinate)
+    }
 }
 
 impl Draw for Cursor {
-    fn draw(&self, term_row: u16, term_col: u16) -> Result<()> {
-        let img = match self.player { Player::X => BIG_X, Player::O => BIG_O, };
+    fn draw(&self, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> Result<()> {
+        let img = self.mark.glyph();
+        // The mark picked up to slide is framed too, in the plain label color
+        if let Some(from) = self.selected {
+            let (row, col) = from.into();
+            let (out_row, out_col) = (term_row + (row as u16 * ROW_HEIGHT), term_col + (col as u16 * COL_WIDTH));
+            BIG_CURSOR.draw_with_color(out_row, out_col, theme.label, theme, glyphs)?;
+        }
+
         let (row, col) = self.coordinate.into();
         let out_row = term_row + (row as u16 * ROW_HEIGHT);
         let out_col = term_col + (col as u16 * COL_WIDTH);
-        img.draw_with_color(out_row, out_col, Color::DarkYellow)?;
-        BIG_CURSOR.draw_with_color(out_row, out_col, Color::DarkYellow)
+        img.draw_with_color(out_row, out_col, theme.cursor, theme, glyphs)?;
+        BIG_CURSOR.draw_with_color(out_row, out_col, theme.cursor, theme, glyphs)
     }
 }
@@ -0,0 +1,345 @@
+//! Plain text descriptions of the game, for modes that print words instead of
+//! drawing pictures.
+
+use crate::game::{Coordinate, Game, GameStatus, Mark, Move, Player, Rules};
+use crate::cube::CubeCoordinate;
+use crate::gravity::Shape;
+use crate::notakto::BoardMove;
+use crate::order::Role;
+use crate::quantum::{Quantum, Step};
+use crate::review::Blunder;
+use crate::three::Side;
+use crate::solver::Outcome;
+
+/// Describe a coordinate using 1-based row and column numbers, e.g. "row 1, column 3"
+pub(crate) fn coordinate(coord: Coordinate) -> String {
+    let (row, col) = coord.into();
+    format!("row {}, column {}", row + 1, col + 1)
+}
+
+/// Describe the contents of a single space, e.g. "X" or "empty"
+pub(crate) fn space(game: &Game, coord: Coordinate) -> String {
+    match game.get_space(coord).get_mark() {
+        Some(player) => player.to_string(),
+        None => String::from("empty"),
+    }
+}
+
+/// Describe the board one row per line, e.g. "Row 1: X, empty, O"
+pub(crate) fn board(game: &Game) -> Vec<String> {
+    (0..3).map(|row| {
+        let spaces: Vec<_> = (0..3).map(|col| space(game, (row, col).into())).collect();
+        format!("Row {}: {}", row + 1, spaces.join(", "))
+    }).collect()
+}
+
+/// Describe the space under the cursor, e.g. "Row 2, column 2: empty"
+pub(crate) fn cursor(game: &Game, coord: Coordinate) -> String {
+    let mut out = coordinate(coord);
+    out[..1].make_ascii_uppercase();
+    format!("{}: {}", out, space(game, coord))
+}
+
+/// Describe where a move goes, e.g. "row 1, column 1", naming the mark if it isn't
+/// the player's own, e.g. "row 1, column 1 with an O", and where it slid from if it
+/// did, e.g. "row 1, column 1 to row 2, column 2". Numbers come first, e.g. "7 in row
+/// 1, column 1".
+pub(crate) fn placement(player: Player, mv: Move) -> String {
+    if let Some(from) = mv.from { return format!("{} to {}", coordinate(from), coordinate(mv.coord)); }
+    if let Mark::Number(n) = mv.mark { return format!("{} in {}", n, coordinate(mv.coord)); }
+    if mv.mark == player.mark() { return coordinate(mv.coord); }
+    format!("{} with an {}", coordinate(mv.coord), mv.mark)
+}
+
+/// Describe a move that was just made, e.g. "X marks row 1, column 1.", "X slides
+/// row 1, column 1 to row 2, column 1." or "X places 7 in row 1, column 1."
+pub(crate) fn mark(player: Player, mv: Move) -> String {
+    let verb = match mv.mark {
+        _ if mv.from.is_some() => "slides",
+        Mark::Number(_) => "places",
+        _ => "marks",
+    };
+    format!("{} {} {}.", player, verb, placement(player, mv))
+}
+
+/// Describe the outcome of a position under perfect play, for `player` to move, e.g.
+/// "With perfect play, X wins in 3 moves."
+pub(crate) fn outcome(player: Player, outcome: Outcome) -> String {
+    let mut opponent = player;
+    opponent.toggle();
+    let plural = |n: u8| if n == 1 { "move" } else { "moves" };
+    match outcome {
+        Outcome::Win(n) => format!("With perfect play, {} wins in {} {}.", player, n, plural(n)),
+        Outcome::Draw => String::from("With perfect play, it's a draw."),
+        Outcome::Loss(n) => format!("With perfect play, {} wins in {} {}.", opponent, n, plural(n)),
+    }
+}
+
+/// Describe a suggested move, e.g. "Hint: row 2, column 2. With perfect play, it's a draw."
+pub(crate) fn hint(player: Player, mv: Move, result: Outcome) -> String {
+    format!("Hint: {}. {}", placement(player, mv), outcome(player, result))
+}
+
+/// Describe a move on one of several boards, e.g. "board 2, row 1, column 3"
+pub(crate) fn board_move(mv: BoardMove) -> String {
+    format!("board {}, {}", mv.board + 1, coordinate(mv.coord))
+}
+
+/// Describe a notakto move that was just made, e.g. "X marks board 2, row 1, column 3."
+pub(crate) fn board_mark(player: Player, mv: BoardMove) -> String {
+    format!("{} marks {}.", player, board_move(mv))
+}
+
+/// Describe a suggested notakto move, e.g. "Hint: board 1, row 2, column 2. With
+/// perfect play, X wins." There are no draws, so whoever doesn't win loses.
+pub(crate) fn board_hint(player: Player, mv: BoardMove, wins: bool) -> String {
+    let mut winner = player;
+    if !wins { winner.toggle(); }
+    format!("Hint: {}. With perfect play, {} wins.", board_move(mv), winner)
+}
+
+/// Describe a move in Order and Chaos that was just made, e.g. "Chaos places an X in
+/// row 2, column 5."
+pub(crate) fn order_mark(player: Player, mv: Move) -> String {
+    format!("{} places an {} in {}.", Role::of(player), mv.mark, coordinate(mv.coord))
+}
+
+/// Describe the status of a game of Order and Chaos by the roles, e.g. "Chaos to
+/// play." or "Order wins with five in a row!"
+pub(crate) fn order_status(status: &GameStatus) -> String {
+    match status {
+        GameStatus::Winner(Player::X) => String::from("Order wins with five in a row!"),
+        GameStatus::Winner(Player::O) => String::from("Chaos wins, the board is full!"),
+        GameStatus::Pending(player) => format!("{} to play.", Role::of(*player)),
+        GameStatus::Draw => String::from("It's a draw, no more moves possible."),
+    }
+}
+
+/// Describe a space in a 3D game, e.g. "layer 2, row 1, column 3"
+pub(crate) fn cube_coordinate(point: CubeCoordinate) -> String {
+    format!("layer {}, {}", point.layer + 1, coordinate(point.coord))
+}
+
+/// Describe a 3D move that was just made, e.g. "X marks layer 2, row 1, column 3."
+pub(crate) fn cube_mark(player: Player, point: CubeCoordinate) -> String {
+    format!("{} marks {}.", player, cube_coordinate(point))
+}
+
+/// Describe the board and run to win under gravity rules, e.g. "Four in a row wins on
+/// a 7x6 board."
+pub(crate) fn shape(shape: Shape) -> String {
+    const NUMBERS: [&str; 10] = ["Zero", "One", "Two", "Three", "Four", "Five", "Six", "Seven", "Eight", "Nine"];
+    let run = NUMBERS.get(shape.run).map(|name| name.to_string()).unwrap_or_else(|| shape.run.to_string());
+    format!("{} in a row wins on a {} board.", run, shape)
+}
+
+/// Describe a mark that was just dropped under gravity rules, e.g. "X drops into
+/// column 4, landing in row 6."
+pub(crate) fn drop(player: Player, coord: Coordinate) -> String {
+    let (row, col) = coord.into();
+    format!("{} drops into column {}, landing in row {}.", player, col + 1, row + 1)
+}
+
+/// Describe a mark a player of a three-player game just placed, e.g. "Y marks row 1,
+/// column 3."
+pub(crate) fn side_mark(side: Side, coord: Coordinate) -> String {
+    format!("{} marks {}.", side, coordinate(coord))
+}
+
+/// Name a quantum mark by the move that made it, e.g. "X3"
+pub(crate) fn quantum_mark(idx: usize) -> String {
+    format!("{}{}", Quantum::mover(idx), idx + 1)
+}
+
+/// Describe a quantum step that was just taken, e.g. "X places a spooky X1 in row 1,
+/// column 1 and row 2, column 2." or "O collapses the cycle, settling X3 in row 1,
+/// column 1."
+pub(crate) fn quantum_step(quantum: &Quantum, player: Player, step: Step) -> String {
+    match step {
+        Step::Collapse(coord) => {
+            let idx = quantum.classical(coord).expect("A collapse settles a mark in the space");
+            format!("{} collapses the cycle, settling {} in {}.", player, quantum_mark(idx), coordinate(coord))
+        },
+        Step::Place(mv) => {
+            let mark = quantum_mark(quantum.history().len() - 1);
+            match mv.first == mv.second {
+                true => format!("{} places {} in {}.", player, mark, coordinate(mv.first)),
+                false => format!("{} places a spooky {} in {} and {}.", player, mark, coordinate(mv.first), coordinate(mv.second)),
+            }
+        },
+    }
+}
+
+/// Describe the cycle waiting to collapse, e.g. "O3 closed a cycle. X chooses where it
+/// settles: row 1, column 1 or row 2, column 2."
+pub(crate) fn quantum_collapse(quantum: &Quantum) -> Option<String> {
+    let mv = quantum.pending_collapse()?;
+    let mark = quantum_mark(quantum.history().len() - 1);
+    Some(format!(
+        "{} closed a cycle. {} chooses where it settles: {} or {}.",
+        mark, quantum.current_player(), coordinate(mv.first), coordinate(mv.second),
+    ))
+}
+
+/// Describe the score when both players completed a line in the same collapse, e.g.
+/// "Both players completed a line, X's by move 5 and O's by move 6, so X scores 1
+/// point and O half a point."
+pub(crate) fn quantum_score(quantum: &Quantum) -> Option<String> {
+    let (winner, loser) = match quantum.half_points()? {
+        (2, 1) => (Player::X, Player::O),
+        (1, 2) => (Player::O, Player::X),
+        _ => return None,
+    };
+    let lines = quantum.lines();
+    let first = |player| lines.iter().filter(|(owner, _)| *owner == player).map(|(_, number)| *number).min();
+    let (x, o) = (first(Player::X)?, first(Player::O)?);
+    Some(format!(
+        "Both players completed a line, X's by move {} and O's by move {}, so {} scores 1 point and {} half a point.",
+        x, o, winner, loser,
+    ))
+}
+
+/// Describe the outcome of each move for `player`, one per line, e.g.
+/// "Row 1, column 1: draw"
+pub(crate) fn analysis(player: Player, outcomes: &[(Move, Outcome)]) -> Vec<String> {
+    outcomes.iter()
+        .map(|(mv, result)| {
+            let mut line = placement(player, *mv);
+            line[..1].make_ascii_uppercase();
+            format!("{}: {}", line, result)
+        })
+        .collect()
+}
+
+/// Introduce the review of a finished game, e.g. "Review: 2 mistakes."
+pub(crate) fn review(count: usize) -> String {
+    match count {
+        0 => String::from("Review: no mistakes, well played!"),
+        1 => String::from("Review: 1 mistake."),
+        n => format!("Review: {} mistakes.", n),
+    }
+}
+
+/// Describe a blunder, e.g. "Move 4 by O (row 1, column 2): draw -> loss in 3.
+/// Better was row 2, column 2."
+pub(crate) fn blunder(blunder: &Blunder) -> String {
+    let better: Vec<_> = blunder.best.iter().map(|mv| placement(blunder.player, *mv)).collect();
+    format!(
+        "Move {} by {} ({}): {} -> {}. Better was {}.",
+        blunder.number, blunder.player, placement(blunder.player, blunder.mv),
+        blunder.before, blunder.after, better.join(" or "),
+    )
+}
+
+/// Describe the rules, if they aren't the standard ones everyone knows
+pub(crate) fn rules(rules: Rules) -> Option<&'static str> {
+    match rules {
+        Rules::Standard => None,
+        Rules::Misere => Some("Misere rules: whoever completes three in a row loses."),
+        Rules::Wild => Some("Wild rules: place X or O on any turn, whoever completes three of a kind in a row wins."),
+        Rules::Notakto => Some("Notakto rules: both players place X, a board with three in a row is dead, whoever kills the last board loses."),
+        Rules::Morris => Some("Morris rules: three marks each, then slide one to a neighboring space each turn. The third repetition is a draw."),
+        Rules::Infinite => Some("Infinite rules: placing a fourth mark removes your oldest, dimmed. The third repetition is a draw."),
+        Rules::Quantum => Some("Quantum rules: each mark goes in two spaces, and settles in one when a cycle forms. The other player chooses which."),
+        Rules::Numerical => Some("Numerical rules: X places odd numbers and O even ones, each once. Whoever completes a line summing to 15 wins."),
+        Rules::Order => Some("Order and Chaos: both place X or O on a 6x6 board. Order (X) wants five in a row, Chaos (O) a full board without."),
+        Rules::Gravity => Some("Gravity rules: each mark drops to the lowest empty space in its column, as in Connect Four."),
+        Rules::Cube => Some("3D rules: three layers of 3x3. Three in a row wins along any of 49 lines, through the layers too."),
+        Rules::Qubic => Some("Qubic rules: four layers of 4x4. Four in a row wins along any of 76 lines, through the layers too."),
+        Rules::Three => Some("Three players: X, O and Y take turns in that order. Whoever completes three in a row first wins."),
+    }
+}
+
+/// Describe lines wrapping around the edges of the board, if they do
+pub(crate) fn wrap(game: &Game) -> Option<&'static str> {
+    game.wraps().then_some("Wraparound: lines wrap around the edges, so row 1, column 3, row 2, column 1 and row 3, column 2 is a line too.")
+}
+
+/// Describe a line of three that wraps around the edges of the board, if there is one,
+/// e.g. "The line wraps around the edges: row 1, column 3, row 2, column 1 and row 3,
+/// column 2."
+pub(crate) fn wrapped_line(game: &Game) -> Option<String> {
+    let [a, b, c] = game.wrapped_line()?;
+    Some(format!("The line wraps around the edges: {}, {} and {}.", coordinate(a), coordinate(b), coordinate(c)))
+}
+
+/// Describe the mark the current player's next move removes, under infinite rules,
+/// e.g. "X's mark at row 1, column 1 vanishes with their next move."
+pub(crate) fn vanishing(game: &Game) -> Option<String> {
+    let coord = game.vanishing()?;
+    Some(format!("{}'s mark at {} vanishes with their next move.", game.current_player(), coordinate(coord)))
+}
+
+/// Describe the numbers the current player has left to place, under numerical rules,
+/// e.g. "X has 1, 3 and 7 left."
+pub(crate) fn numbers(game: &Game) -> Option<String> {
+    if game.rules() != Rules::Numerical { return None; }
+    let numbers: Vec<_> = game.marks().iter().map(|mark| mark.to_string()).collect();
+    let list = match numbers.split_last()? {
+        (last, []) => last.clone(),
+        (last, rest) => format!("{} and {}", rest.join(", "), last),
+    };
+    Some(format!("{} has {} left.", game.current_player(), list))
+}
+
+/// Describe the game status, e.g. "O to play.", whoever the players are
+pub(crate) fn status<P: std::fmt::Display>(status: &GameStatus<P>) -> String {
+    match status {
+        GameStatus::Winner(player) => format!("{} wins!", player),
+        GameStatus::Pending(player) => format!("{} to play.", player),
+        GameStatus::Draw => String::from("It's a draw, no more moves possible."),
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Tests
+//--------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_board() {
+        let moves: Vec<Coordinate> = vec![(0, 0).into(), (0, 2).into(), (1, 1).into()];
+        let game = Game::from(&moves).expect("Failed to create game.");
+        assert_eq!(board(&game), vec![
+            "Row 1: X, empty, O",
+            "Row 2: empty, X, empty",
+            "Row 3: empty, empty, empty",
+        ]);
+        assert_eq!(cursor(&game, (0, 2).into()), "Row 1, column 3: O");
+        assert_eq!(status(&game.status()), "O to play.");
+        assert_eq!(numbers(&game), None);
+        assert_eq!(wrap(&game), None);
+    }
+
+    #[test]
+    fn test_describe_wrapped_line() {
+        let moves: Vec<Coordinate> = vec![(0, 2).into(), (0, 0).into(), (1, 0).into(), (1, 1).into(), (2, 1).into()];
+        let game = Game::from(&moves).expect("Failed to create game.");
+        assert_eq!(wrapped_line(&game), None);
+        let game = game.with_wrap(true);
+        assert_eq!(
+            wrapped_line(&game).as_deref(),
+            Some("The line wraps around the edges: row 1, column 3, row 2, column 1 and row 3, column 2.")
+        );
+    }
+
+    #[test]
+    fn test_describe_numbers() {
+        let mut game = Game::with_rules(Rules::Numerical);
+        let mv = Move::new((0, 0).into(), Mark::Number(5));
+        game.play(mv).expect("Failed to play.");
+        assert_eq!(mark(Player::X, mv), "X places 5 in row 1, column 1.");
+        assert_eq!(space(&game, (0, 0).into()), "5");
+        assert_eq!(numbers(&game).as_deref(), Some("O has 2, 4, 6 and 8 left."));
+    }
+
+    #[test]
+    fn test_describe_gravity() {
+        assert_eq!(shape(Shape::default()), "Four in a row wins on a 7x6 board.");
+        assert_eq!(drop(Player::O, (5, 3).into()), "O drops into column 4, landing in row 6.");
+    }
+}
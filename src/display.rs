@@ -1,3 +1,5 @@
+use crate::theme::Theme;
+
 use crossterm::{execute, Result};
 use crossterm::cursor::MoveTo;
 use crossterm::style::{Color, Print, SetForegroundColor, ResetColor};
@@ -10,12 +12,16 @@ use std::ops::{Deref, DerefMut};
 
 pub(crate) const ROW_HEIGHT: u16 = 14;  // The height of a game board row, including grid space
 pub(crate) const COL_WIDTH: u16 = 33;   // The width of a game board column, including grid space
+pub(crate) const SMALL_ROW_HEIGHT: u16 = 4;  // The height of a small board row, including grid space
+pub(crate) const SMALL_COL_WIDTH: u16 = 9;   // The width of a small board column, including grid space
+pub(crate) const TINY_ROW_HEIGHT: u16 = 2;   // The height of a tiny board row, including grid space
+pub(crate) const TINY_COL_WIDTH: u16 = 4;    // The width of a tiny board column, including grid space
 
 //--------------------------------------------------------------------------------------
 //-- Character constants for various display items
 //--------------------------------------------------------------------------------------
 
-pub(crate) const BIG_X: [&'static str; 12] = [
+pub(crate) const BIG_X: [&str; 12] = [
     "                              ",
     "      ●●●●●●●●●      ●●●      ",
     "       ●●●●●●●●●    ●●●       ",
@@ -30,7 +36,7 @@ pub(crate) const BIG_X: [&'static str; 12] = [
     "                              "
 ];
 
-pub(crate) const BIG_O: [&'static str; 12] = [
+pub(crate) const BIG_O: [&str; 12] = [
     "                              ",
     "          ●●●●●●●●●●          ",
     "       ●●●●●●      ●●●        ",
@@ -45,7 +51,153 @@ pub(crate) const BIG_O: [&'static str; 12] = [
     "                              ",
 ];
 
-pub(crate) const BIG_CURSOR: [&'static str; 12] = [
+/// The third player's mark, in three-player games
+pub(crate) const BIG_Y: [&str; 12] = [
+    "                              ",
+    "     ●●●●           ●●●●      ",
+    "      ●●●●         ●●●●       ",
+    "       ●●●●       ●●●●        ",
+    "        ●●●●     ●●●●         ",
+    "         ●●●●   ●●●●          ",
+    "          ●●●●●●●●●●          ",
+    "            ●●●●●●            ",
+    "            ●●●●●●            ",
+    "            ●●●●●●            ",
+    "            ●●●●●●            ",
+    "                              ",
+];
+
+/// The numbers 1 to 9, as placed under numerical rules, at the size of `BIG_X`
+pub(crate) const BIG_DIGITS: [[&str; 12]; 9] = [
+    [
+        "                              ",
+        "             ●●●              ",
+        "             ●●●              ",
+        "          ●●●●●●              ",
+        "          ●●●●●●              ",
+        "             ●●●              ",
+        "             ●●●              ",
+        "             ●●●              ",
+        "             ●●●              ",
+        "          ●●●●●●●●●           ",
+        "          ●●●●●●●●●           ",
+        "                              ",
+    ],
+    [
+        "                              ",
+        "       ●●●●●●●●●●●●           ",
+        "       ●●●●●●●●●●●●           ",
+        "                   ●●●        ",
+        "                   ●●●        ",
+        "          ●●●●●●●●●           ",
+        "          ●●●●●●●●●           ",
+        "       ●●●                    ",
+        "       ●●●                    ",
+        "       ●●●●●●●●●●●●●●●        ",
+        "       ●●●●●●●●●●●●●●●        ",
+        "                              ",
+    ],
+    [
+        "                              ",
+        "       ●●●●●●●●●●●●           ",
+        "       ●●●●●●●●●●●●           ",
+        "                   ●●●        ",
+        "                   ●●●        ",
+        "          ●●●●●●●●●           ",
+        "          ●●●●●●●●●           ",
+        "                   ●●●        ",
+        "                   ●●●        ",
+        "       ●●●●●●●●●●●●           ",
+        "       ●●●●●●●●●●●●           ",
+        "                              ",
+    ],
+    [
+        "                              ",
+        "       ●●●         ●●●        ",
+        "       ●●●         ●●●        ",
+        "       ●●●         ●●●        ",
+        "       ●●●         ●●●        ",
+        "       ●●●●●●●●●●●●●●●        ",
+        "       ●●●●●●●●●●●●●●●        ",
+        "                   ●●●        ",
+        "                   ●●●        ",
+        "                   ●●●        ",
+        "                   ●●●        ",
+        "                              ",
+    ],
+    [
+        "                              ",
+        "       ●●●●●●●●●●●●●●●        ",
+        "       ●●●●●●●●●●●●●●●        ",
+        "       ●●●                    ",
+        "       ●●●                    ",
+        "       ●●●●●●●●●●●●           ",
+        "       ●●●●●●●●●●●●           ",
+        "                   ●●●        ",
+        "                   ●●●        ",
+        "       ●●●●●●●●●●●●           ",
+        "       ●●●●●●●●●●●●           ",
+        "                              ",
+    ],
+    [
+        "                              ",
+        "          ●●●●●●●●●           ",
+        "          ●●●●●●●●●           ",
+        "       ●●●                    ",
+        "       ●●●                    ",
+        "       ●●●●●●●●●●●●           ",
+        "       ●●●●●●●●●●●●           ",
+        "       ●●●         ●●●        ",
+        "       ●●●         ●●●        ",
+        "          ●●●●●●●●●           ",
+        "          ●●●●●●●●●           ",
+        "                              ",
+    ],
+    [
+        "                              ",
+        "       ●●●●●●●●●●●●●●●        ",
+        "       ●●●●●●●●●●●●●●●        ",
+        "                   ●●●        ",
+        "                   ●●●        ",
+        "                ●●●           ",
+        "                ●●●           ",
+        "             ●●●              ",
+        "             ●●●              ",
+        "             ●●●              ",
+        "             ●●●              ",
+        "                              ",
+    ],
+    [
+        "                              ",
+        "          ●●●●●●●●●           ",
+        "          ●●●●●●●●●           ",
+        "       ●●●         ●●●        ",
+        "       ●●●         ●●●        ",
+        "          ●●●●●●●●●           ",
+        "          ●●●●●●●●●           ",
+        "       ●●●         ●●●        ",
+        "       ●●●         ●●●        ",
+        "          ●●●●●●●●●           ",
+        "          ●●●●●●●●●           ",
+        "                              ",
+    ],
+    [
+        "                              ",
+        "          ●●●●●●●●●           ",
+        "          ●●●●●●●●●           ",
+        "       ●●●         ●●●        ",
+        "       ●●●         ●●●        ",
+        "          ●●●●●●●●●●●●        ",
+        "          ●●●●●●●●●●●●        ",
+        "                   ●●●        ",
+        "                   ●●●        ",
+        "          ●●●●●●●●●           ",
+        "          ●●●●●●●●●           ",
+        "                              ",
+    ],
+];
+
+pub(crate) const BIG_CURSOR: [&str; 12] = [
     " ╔══════════════════════════╗ ",
     " ║                          ║ ",
     " ║                          ║ ",
@@ -60,7 +212,7 @@ pub(crate) const BIG_CURSOR: [&'static str; 12] = [
     " ╚══════════════════════════╝ ",
 ];
 
-pub(crate) const BIG_GRID: [&'static str; 40] = [
+pub(crate) const BIG_GRID: [&str; 40] = [
     "                              ┃ ┃                              ┃ ┃                               ",
     "                              ┃ ┃                              ┃ ┃                               ",
     "                              ┃ ┃                              ┃ ┃                               ",
@@ -103,7 +255,77 @@ pub(crate) const BIG_GRID: [&'static str; 40] = [
     "                              ┃ ┃                              ┃ ┃                               ",
 ];
 
-pub(crate) const BIG_WINS: [&'static str; 11] = [
+/// X, O, Y and the cursor for boards too big for the big glyphs, drawn on `small_grid`
+pub(crate) const SMALL_X: [&str; 3] = [
+    "  ●  ●  ",
+    "   ●●   ",
+    "  ●  ●  ",
+];
+
+pub(crate) const SMALL_O: [&str; 3] = [
+    "  ●●●●  ",
+    " ●    ● ",
+    "  ●●●●  ",
+];
+
+pub(crate) const SMALL_Y: [&str; 3] = [
+    "  ●  ●  ",
+    "   ●●   ",
+    "   ●●   ",
+];
+
+pub(crate) const SMALL_CURSOR: [&str; 5] = [
+    "╔════════╗",
+    "║        ║",
+    "║        ║",
+    "║        ║",
+    "╚════════╝",
+];
+
+
+/// The cursor for boards of letters, three characters wide and one high, drawn on
+/// `tiny_grid`
+pub(crate) const TINY_CURSOR: [&str; 3] = [
+    "╔═══╗",
+    "║   ║",
+    "╚═══╝",
+];
+
+/// A grid of `rows` by `cols` spaces for the small glyphs, each eight characters wide
+/// and three high inside its lines
+pub(crate) fn small_grid(rows: usize, cols: usize) -> CharMatrix {
+    grid(rows, cols, SMALL_ROW_HEIGHT, SMALL_COL_WIDTH)
+}
+
+/// A grid of `rows` by `cols` spaces with room for a letter in each
+pub(crate) fn tiny_grid(rows: usize, cols: usize) -> CharMatrix {
+    grid(rows, cols, TINY_ROW_HEIGHT, TINY_COL_WIDTH)
+}
+
+/// A grid of `rows` by `cols` spaces, each taking up `height` rows and `width` columns
+/// of the terminal including the lines on one side
+fn grid(rows: usize, cols: usize, height: u16, width: u16) -> CharMatrix {
+    let line = |left: char, middle: char, right: char, fill: char| {
+        let space = vec![fill; width as usize - 1];
+        let mut out = vec![left];
+        out.extend(&space);
+        for _ in 1..cols {
+            out.push(middle);
+            out.extend(&space);
+        }
+        out.push(right);
+        out
+    };
+    let mut out = vec![line('┏', '┳', '┓', '━')];
+    for row in 0..rows {
+        if row > 0 { out.push(line('┣', '╋', '┫', '━')); }
+        for _ in 1..height { out.push(line('┃', '┃', '┃', ' ')); }
+    }
+    out.push(line('┗', '┻', '┛', '━'));
+    CharMatrix(out)
+}
+
+pub(crate) const BIG_WINS: [&str; 11] = [
     "                                          ",
     "                                          ",
     "  ●●●       ●●● ●●●                   ●●● ",
@@ -117,7 +339,7 @@ pub(crate) const BIG_WINS: [&'static str; 11] = [
     "  ●●●       ●●● ●●● ●●●  ●●●  ●●●●●●● ●●● ",
 ];
 
-pub(crate) const BIG_PLAYER: [&'static str; 14] = [
+pub(crate) const BIG_PLAYER: [&str; 14] = [
     "                                                         ",
     "                                                         ",
     "                                                         ",
@@ -134,7 +356,7 @@ pub(crate) const BIG_PLAYER: [&'static str; 14] = [
     "                           ●●●●●●                        ",
 ];
 
-pub(crate) const BIG_TRY_AGAIN: [&'static str; 10] = [
+pub(crate) const BIG_TRY_AGAIN: [&str; 10] = [
     "  ●●●●●●●●●●●                           ●●●●●                   ●●●          ●●●", 
     "      ●●●                              ●●●●●●                   ●●●          ●●●", 
     "      ●●●                             ●●●●●●●                                ●●●", 
@@ -148,6 +370,72 @@ pub(crate) const BIG_TRY_AGAIN: [&'static str; 10] = [
 ];
 
 
+//--------------------------------------------------------------------------------------
+//-- Glyph sets
+//--------------------------------------------------------------------------------------
+
+/// The characters used to draw the game. The constants above are written with the
+/// `Unicode` glyphs and translated one character at a time when drawing with `Ascii`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Glyphs {
+    Unicode,
+    Ascii,
+}
+
+impl Glyphs {
+    /// Choose a glyph set from the locale environment variables, falling back to ASCII
+    /// when the locale names a character set other than UTF-8
+    pub(crate) fn from_locale() -> Self {
+        let var = |name| std::env::var(name).ok();
+        Glyphs::from_locale_vars(var("LC_ALL"), var("LC_CTYPE"), var("LANG"))
+    }
+
+    /// The first non-empty variable of LC_ALL, LC_CTYPE and LANG decides the character
+    /// set, as it does for the C library. With none of them set, assume Unicode.
+    fn from_locale_vars(lc_all: Option<String>, lc_ctype: Option<String>, lang: Option<String>) -> Self {
+        let locale = [lc_all, lc_ctype, lang].iter()
+            .flatten()
+            .find(|value| !value.is_empty())
+            .map(|value| value.to_ascii_lowercase());
+        match locale {
+            Some(locale) if !locale.contains("utf-8") && !locale.contains("utf8") => Glyphs::Ascii,
+            _ => Glyphs::Unicode,
+        }
+    }
+
+    /// Translate a character from the Unicode glyph set into this glyph set
+    pub(crate) fn translate(&self, c: char) -> char {
+        match self {
+            Glyphs::Unicode => c,
+            Glyphs::Ascii => match c {
+                '━' | '═' => '-',
+                '┃' | '║' => '|',
+                '┏' | '┓' | '┗' | '┛' | '╔' | '╗' | '╚' | '╝' => '+',
+                '┳' | '┻' | '┣' | '┫' | '╋' => '+',
+                '₀'..='₉' => char::from_u32(c as u32 - '₀' as u32 + '0' as u32).unwrap_or('#'),
+                c if c.is_ascii() => c,
+                _ => '#',
+            },
+        }
+    }
+
+    /// Translate a line of text into this glyph set, for text printed as it is, like
+    /// names and hints
+    pub(crate) fn text(&self, text: &str) -> String {
+        text.chars().map(|c| self.translate(c)).collect()
+    }
+
+    /// Look up a glyph set by the name used on the command line
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "unicode" => Some(Glyphs::Unicode),
+            "ascii" => Some(Glyphs::Ascii),
+            _ => None,
+        }
+    }
+}
+
+
 //--------------------------------------------------------------------------------------
 //-- Structs and traits for displaying the game
 //--------------------------------------------------------------------------------------
@@ -160,6 +448,12 @@ impl Default for CharMatrix {
     }
 }
 
+impl From<&str> for CharMatrix {
+    fn from(text: &str) -> Self {
+        CharMatrix(text.lines().map(|line| line.chars().collect()).collect())
+    }
+}
+
 impl Deref for CharMatrix {
     type Target = Vec<Vec<char>>;
 
@@ -192,7 +486,7 @@ pub(crate) trait ToCharMatrix {
 }
 
 // const generics to the rescue!
-impl<const N: usize> ToCharMatrix for [&'static str; N] {
+impl<const N: usize> ToCharMatrix for [&str; N] {
     fn to_char_matrix(&self) -> CharMatrix {
         let mut out = Vec::new();
         for line in self {
@@ -204,8 +498,8 @@ impl<const N: usize> ToCharMatrix for [&'static str; N] {
 }
 
 impl<T> Draw for T where T: ToCharMatrix {
-    fn draw(&self, term_row: u16, term_col: u16) -> Result<()> {
-        self.to_char_matrix().draw(term_row, term_col)
+    fn draw(&self, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> Result<()> {
+        self.to_char_matrix().draw(term_row, term_col, theme, glyphs)
     }
 }
 
@@ -213,19 +507,20 @@ impl<T> Draw for T where T: ToCharMatrix {
 //-- Drawing traits
 //--------------------------------------------------------------------------------------
 
-// For printing game elements to the console
+// For printing game elements to the console, using the colors from `theme`
 pub(crate) trait Draw {
-    fn draw(&self, term_row: u16, term_col: u16) -> Result<()>;
+    fn draw(&self, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> Result<()>;
 }
 
 impl Draw for CharMatrix {
-    fn draw(&self, term_row: u16, term_col: u16) -> Result<()> {
+    fn draw(&self, term_row: u16, term_col: u16, _theme: &Theme, glyphs: Glyphs) -> Result<()> {
         for (row_idx, row) in self.iter().enumerate() {
             let print_row = (row_idx as u16) + term_row;
             for (col_idx, col) in row.iter().enumerate() {
                 if col.is_ascii_whitespace() { continue; }
                 let print_col = (col_idx as u16) + term_col;
-                execute!(std::io::stdout(), MoveTo(print_col, print_row), Print(col))?;
+                let glyph = glyphs.translate(*col);
+                execute!(std::io::stdout(), MoveTo(print_col, print_row), Print(glyph))?;
             }
         }
         Ok(())
@@ -234,16 +529,64 @@ impl Draw for CharMatrix {
 
 
 pub(crate) trait DrawWithColor {
-    fn draw_with_color(&self, term_row: u16, term_col: u16, color: Color) -> Result<()>;
+    fn draw_with_color(&self, term_row: u16, term_col: u16, color: Color, theme: &Theme, glyphs: Glyphs) -> Result<()>;
 }
 
 impl<T: Draw> DrawWithColor for T {
-    fn draw_with_color(&self, term_row: u16, term_col: u16, color: Color) -> Result<()> {
+    fn draw_with_color(&self, term_row: u16, term_col: u16, color: Color, theme: &Theme, glyphs: Glyphs) -> Result<()> {
         let mut stdout = std::io::stdout();
         execute!(stdout, SetForegroundColor(color))?;
-        self.draw(term_row, term_col)?;
+        self.draw(term_row, term_col, theme, glyphs)?;
         execute!(stdout, ResetColor)
     }
 }
 
 
+
+
+//--------------------------------------------------------------------------------------
+//-- Tests
+//--------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ascii_glyphs() {
+        // Every glyph in the big images has an ASCII stand-in
+        let lines = BIG_GRID.iter()
+            .chain(BIG_CURSOR.iter())
+            .chain(BIG_X.iter())
+            .chain(BIG_Y.iter())
+            .chain(BIG_WINS.iter())
+            .chain(SMALL_CURSOR.iter())
+            .chain(TINY_CURSOR.iter());
+        for line in lines {
+            assert!(line.chars().map(|c| Glyphs::Ascii.translate(c)).all(|c| c.is_ascii()));
+        }
+        assert!(small_grid(6, 7).iter().flatten().all(|c| Glyphs::Ascii.translate(*c).is_ascii()));
+        assert_eq!(tiny_grid(3, 3).to_string(), "\
+┏━━━┳━━━┳━━━┓
+┃   ┃   ┃   ┃
+┣━━━╋━━━╋━━━┫
+┃   ┃   ┃   ┃
+┣━━━╋━━━╋━━━┫
+┃   ┃   ┃   ┃
+┗━━━┻━━━┻━━━┛
+");
+        assert_eq!(Glyphs::Ascii.translate('┛'), '+');
+        assert_eq!(Glyphs::Ascii.translate('●'), '#');
+        assert_eq!(Glyphs::Ascii.translate('₇'), '7');
+    }
+
+    #[test]
+    fn test_glyphs_from_locale() {
+        let some = |s: &str| Some(s.to_string());
+        assert_eq!(Glyphs::from_locale_vars(None, None, some("en_US.UTF-8")), Glyphs::Unicode);
+        assert_eq!(Glyphs::from_locale_vars(some("C"), None, some("en_US.UTF-8")), Glyphs::Ascii);
+        assert_eq!(Glyphs::from_locale_vars(some(""), some("de_DE.utf8"), None), Glyphs::Unicode);
+        assert_eq!(Glyphs::from_locale_vars(None, None, some("en_US.ISO-8859-1")), Glyphs::Ascii);
+        assert_eq!(Glyphs::from_locale_vars(None, None, None), Glyphs::Unicode);
+    }
+}
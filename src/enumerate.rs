@@ -0,0 +1,139 @@
+//! Exhaustive enumeration of the game tree: every legal game and every reachable
+//! position, counted by depth (the number of marks on the board).
+
+use crate::game::{Game, GameStatus, Player};
+use crate::symmetry::{canonicalize, position_key};
+
+use std::collections::HashSet;
+use std::io::Write;
+
+
+//--------------------------------------------------------------------------------------
+//-- Counts
+//--------------------------------------------------------------------------------------
+
+/// What was found at one depth of the game tree
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct Level {
+    pub(crate) positions: usize,  // Distinct positions
+    pub(crate) unique: usize,     // Distinct positions, up to symmetry
+    pub(crate) x_wins: u64,       // Games that end here with X winning
+    pub(crate) o_wins: u64,       // Games that end here with O winning
+    pub(crate) draws: u64,        // Games that end here in a draw
+}
+
+impl Level {
+    pub(crate) fn games(&self) -> u64 {
+        self.x_wins + self.o_wins + self.draws
+    }
+}
+
+/// The counts for every depth from the starting position to the end of the longest game
+#[derive(Debug, Default)]
+pub(crate) struct Tree {
+    pub(crate) start: usize,        // The depth of the starting position
+    pub(crate) levels: Vec<Level>,  // One per depth, starting with `start`
+}
+
+impl Tree {
+    /// Enumerate every game that can be played from `game`
+    pub(crate) fn enumerate(game: &Game) -> Self {
+        let start = game.history().len();
+        let mut seen = vec![(HashSet::new(), HashSet::new()); 10 - start];
+        let mut levels = vec![Level::default(); 10 - start];
+        visit(game, start, &mut seen, &mut levels);
+        while levels.last().is_some_and(|level| level.positions == 0) { levels.pop(); }
+        Tree { start, levels }
+    }
+
+    /// The totals over every depth
+    pub(crate) fn total(&self) -> Level {
+        self.levels.iter().fold(Level::default(), |total, level| Level {
+            positions: total.positions + level.positions,
+            unique: total.unique + level.unique,
+            x_wins: total.x_wins + level.x_wins,
+            o_wins: total.o_wins + level.o_wins,
+            draws: total.draws + level.draws,
+        })
+    }
+
+    /// Print the counts as a table, one row per depth and a row of totals
+    pub(crate) fn print(&self, output: &mut impl Write) -> std::io::Result<()> {
+        writeln!(
+            output, "{:>5}  {:>9}  {:>9}  {:>9}  {:>9}  {:>9}  {:>9}",
+            "Depth", "Positions", "Unique*", "Games", "X wins", "O wins", "Draws",
+        )?;
+        let rows = self.levels.iter()
+            .enumerate()
+            .map(|(idx, level)| ((self.start + idx).to_string(), level.clone()))
+            .chain(Some((String::from("Total"), self.total())));
+        for (depth, level) in rows {
+            writeln!(
+                output, "{:>5}  {:>9}  {:>9}  {:>9}  {:>9}  {:>9}  {:>9}",
+                depth, level.positions, level.unique, level.games(), level.x_wins, level.o_wins, level.draws,
+            )?;
+        }
+        writeln!(output, "\n* Distinct positions up to rotating and flipping the board.")?;
+        writeln!(output, "  Games are counted at the depth they end.")
+    }
+}
+
+/// Count a position and every game that continues from it. Positions are counted the
+/// first time they're seen, but games through them are counted every time.
+fn visit(game: &Game, depth: usize, seen: &mut [(HashSet<u32>, HashSet<u32>)], levels: &mut [Level]) {
+    let idx = depth - (10 - levels.len());
+    let (positions, unique) = &mut seen[idx];
+    if positions.insert(position_key(game)) {
+        levels[idx].positions += 1;
+        if unique.insert(canonicalize(game).key) { levels[idx].unique += 1; }
+    }
+
+    match game.status() {
+        GameStatus::Winner(Player::X) => levels[idx].x_wins += 1,
+        GameStatus::Winner(Player::O) => levels[idx].o_wins += 1,
+        GameStatus::Draw => levels[idx].draws += 1,
+        GameStatus::Pending(_) => {
+            for mv in game.legal_moves() {
+                let mut next = game.clone();
+                next.play(mv).expect("Legal moves can be played");
+                visit(&next, depth + 1, seen, levels);
+            }
+        },
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Tests
+//--------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Coordinate;
+
+    #[test]
+    fn test_whole_game_tree() {
+        // The well known counts for tic-tac-toe
+        let tree = Tree::enumerate(&Game::new());
+        let total = tree.total();
+        assert_eq!(total.games(), 255_168);
+        assert_eq!(total.x_wins, 131_184);
+        assert_eq!(total.o_wins, 77_904);
+        assert_eq!(total.draws, 46_080);
+        assert_eq!(total.positions, 5_478);
+        assert_eq!(total.unique, 765);
+        assert_eq!(tree.levels.len(), 10);
+        assert_eq!(tree.levels[5].x_wins, 1_440);
+    }
+
+    #[test]
+    fn test_from_a_position() {
+        let moves: Vec<_> = [(1, 1), (0, 0)].iter().map(|x| Coordinate::from(*x)).collect();
+        let tree = Tree::enumerate(&Game::from(&moves).unwrap());
+        assert_eq!(tree.start, 2);
+        assert_eq!(tree.levels[0].positions, 1);
+        assert_eq!(tree.levels[1].positions, 7);
+        assert_eq!(tree.levels[1].unique, 4);
+    }
+}
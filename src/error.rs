@@ -1,5 +1,30 @@
 //! Custom error types to represent the sorts of errors that may occur during a game
 //! `SpaceOccupied` - Tried to place a mark in a space already marked
+//! `WrongMark` - Tried to place the other player's mark, when the rules don't allow it
+//! `DeadBoard` - Tried to mark a notakto board that already has three in a row
+//! `MustSlide` - Tried to place a fourth mark under morris rules
+//! `IllegalSlide` - Tried to slide something other than one of the player's own marks
+//!   to a neighboring space
+//! `MustCollapse` - Tried to move in quantum tic-tac-toe before collapsing a cycle
+//! `IllegalCollapse` - Tried to collapse a cycle into a space it can't settle in
+//! `SameSpace` - Tried to put both halves of a spooky mark in the same space
+//! `NumberUsed` - Tried to place a number that's already on the board
+//! `ColumnFull` - Tried to drop a mark into a full column under gravity rules
+//!
+//! ...and while reading the command line
+//! `UnknownArgument` - An argument that isn't a recognized option
+//! `MissingValue` - An option that takes a value was given without one
+//! `InvalidValue` - An option was given a value it doesn't accept
+//! `Usage` - The arguments don't make sense together
+//!
+//! ...and while talking to a bot program
+//! `Spawn` - The bot program couldn't be started
+//! `Exited` - The bot program stopped responding or exited
+//! `Timeout` - The bot program took too long to reply
+//! `Protocol` - The bot program sent a reply that doesn't follow the protocol
+//! `IllegalMove` - The bot program chose a space that's already occupied
+//! `Unsupported` - The bot program was asked to play a variant the protocol can't
+//!   describe
 
 /// A list specifying the categories of Game errors
 #[derive(Debug, PartialEq)]
@@ -7,6 +32,29 @@
 pub(crate) enum GameError {
     /// Tried to place a mark in a space already marked
     SpaceOccupied,
+    /// Tried to place the other player's mark, when the rules don't allow it
+    WrongMark,
+    /// Tried to mark a notakto board that already has three in a row
+    DeadBoard,
+    /// Tried to place a fourth mark under morris rules
+    MustSlide,
+    /// Tried to slide something other than one of the player's own marks to a
+    /// neighboring space
+    IllegalSlide,
+    /// Tried to move in quantum tic-tac-toe before collapsing a cycle
+    MustCollapse,
+    /// Tried to collapse a cycle into a space it can't settle in, or when there's no
+    /// cycle to collapse
+    IllegalCollapse,
+    /// Tried to put both halves of a spooky mark in the same space
+    SameSpace,
+    /// Tried to place a number that's already on the board, under numerical rules
+    NumberUsed,
+    /// Tried to drop a mark into a column that's full, or off the board, under
+    /// gravity rules
+    ColumnFull,
+    /// Tried to place a mark in a space that isn't on the board
+    OffBoard,
 }
 
 pub(crate) type Result<T> = std::result::Result<T, GameError>;
@@ -15,8 +63,82 @@ impl std::fmt::Display for GameError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let msg = match self {
             GameError::SpaceOccupied => "Cannot add a move to an already occupied space",
+            GameError::WrongMark => "Cannot place the other player's mark",
+            GameError::DeadBoard => "Cannot mark a board that already has three in a row",
+            GameError::MustSlide => "All three marks are placed, slide one of them instead",
+            GameError::IllegalSlide => "Can only slide your own mark to a neighboring space",
+            GameError::MustCollapse => "The last move closed a cycle, choose where it collapses first",
+            GameError::IllegalCollapse => "Can only collapse a cycle into one of the last move's two spaces",
+            GameError::SameSpace => "A spooky mark goes in two different spaces",
+            GameError::NumberUsed => "Each number can only be placed once",
+            GameError::ColumnFull => "Cannot drop a mark into a full column",
+            GameError::OffBoard => "Cannot place a mark off the board",
         };
         write!(f, "{}", msg)
     }
 }
 
+/// A list specifying the categories of command line errors
+#[derive(Debug, PartialEq)]
+#[non_exhaustive]
+pub(crate) enum OptionsError {
+    /// An argument that isn't a recognized option
+    UnknownArgument(String),
+    /// An option that takes a value was given without one
+    MissingValue(String),
+    /// An option was given a value it doesn't accept, as (option, value)
+    InvalidValue(String, String),
+    /// The arguments don't make sense together
+    Usage(String),
+}
+
+impl std::fmt::Display for OptionsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            OptionsError::UnknownArgument(arg) => write!(f, "Unknown argument '{}'", arg),
+            OptionsError::MissingValue(opt) => write!(f, "Missing value for '{}'", opt),
+            OptionsError::InvalidValue(opt, value) => {
+                write!(f, "Invalid value '{}' for '{}'", value, opt)
+            },
+            OptionsError::Usage(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+/// A list specifying the categories of errors from bot programs. Each carries the name
+/// of the bot, or the command used to start it.
+#[derive(Debug, PartialEq)]
+#[non_exhaustive]
+pub(crate) enum BotError {
+    /// The bot program couldn't be started, as (command, reason)
+    Spawn(String, String),
+    /// The bot program stopped responding or exited
+    Exited(String),
+    /// The bot program took too long to reply
+    Timeout(String),
+    /// The bot program sent a reply that doesn't follow the protocol, as (name, reply)
+    Protocol(String, String),
+    /// The bot program chose a space that's already occupied
+    IllegalMove(String),
+    /// The bot program was asked to play a game the protocol can't describe
+    Unsupported(String),
+}
+
+impl std::fmt::Display for BotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BotError::Spawn(cmd, reason) => write!(f, "Couldn't start bot '{}': {}", cmd, reason),
+            BotError::Exited(name) => write!(f, "Bot '{}' exited unexpectedly", name),
+            BotError::Timeout(name) => write!(f, "Bot '{}' took too long to reply", name),
+            BotError::Protocol(name, line) => write!(f, "Bot '{}' sent an invalid reply: '{}'", name, line),
+            BotError::IllegalMove(name) => write!(f, "Bot '{}' tried to mark an occupied space", name),
+            BotError::Unsupported(name) => write!(f, "Bot '{}' can't play this variant, the protocol can't describe it", name),
+        }
+    }
+}
+
+impl From<BotError> for std::io::Error {
+    fn from(e: BotError) -> Self {
+        std::io::Error::other(e.to_string())
+    }
+}
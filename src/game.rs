@@ -4,6 +4,7 @@
 use crate::display::{
     BIG_X,
     BIG_O,
+    BIG_DIGITS,
     BIG_GRID,
     BIG_PLAYER,
     BIG_TRY_AGAIN,
@@ -12,12 +13,15 @@ use crate::display::{
     COL_WIDTH,
     Draw,
     DrawWithColor,
+    Glyphs,
 };
 use crate::error::{GameError, Result};
+use crate::symmetry::position_key;
+use crate::theme::Theme;
 
 use crossterm::execute;
 use crossterm::cursor::MoveTo;
-use crossterm::style::{Color, SetForegroundColor, ResetColor};
+use crossterm::style::{SetForegroundColor, ResetColor};
 use itertools::Itertools; 
 use std::collections::HashMap;
 
@@ -30,26 +34,29 @@ use std::collections::HashMap;
 /// - Winner: A player has won. Encapsulates the identity of the winner.
 /// - Pending: The game has not yet concluded.
 /// - Draw: The game has concluded in a draw. No more moves possible.
+///
+/// The players are X and O, but three-player games have a third, so the banner draws
+/// whoever it names.
 #[derive(Debug, PartialEq)]
-pub(crate) enum GameStatus {
-    Winner(Player),
-    Pending(Player),
+pub(crate) enum GameStatus<P = Player> {
+    Winner(P),
+    Pending(P),
     Draw,
 }
 
-impl Draw for GameStatus {
-    fn draw(&self, term_row: u16, term_col: u16) -> crossterm::Result<()> {
+impl<P: Draw> Draw for GameStatus<P> {
+    fn draw(&self, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> crossterm::Result<()> {
         match self {
             GameStatus::Winner(player) => {
-                player.draw(term_row, term_col)?;
-                BIG_WINS.draw_with_color(term_row, term_col + 30, Color::DarkGreen)
+                player.draw(term_row, term_col, theme, glyphs)?;
+                BIG_WINS.draw_with_color(term_row, term_col + 30, theme.win, theme, glyphs)
             },
             GameStatus::Pending(player) => {
-                BIG_PLAYER.draw(term_row, term_col)?;
-                player.draw(term_row, term_col + 55)
+                BIG_PLAYER.draw_with_color(term_row, term_col, theme.label, theme, glyphs)?;
+                player.draw(term_row, term_col + 55, theme, glyphs)
             },
             GameStatus::Draw => {
-                BIG_TRY_AGAIN.draw_with_color(term_row, term_col, Color::DarkRed)
+                BIG_TRY_AGAIN.draw_with_color(term_row, term_col, theme.draw, theme, glyphs)
             },
         }
     }
@@ -70,6 +77,14 @@ impl Player {
             Player::O => Player::X
         };
     }
+
+    /// The mark this player places, unless the rules let them choose
+    pub(crate) fn mark(&self) -> Mark {
+        match self {
+            Player::X => Mark::X,
+            Player::O => Mark::O,
+        }
+    }
 }
 
 impl std::fmt::Display for Player {
@@ -82,10 +97,164 @@ impl std::fmt::Display for Player {
 }
 
 impl Draw for Player {
-    fn draw(&self, term_row: u16, term_col: u16) -> crossterm::Result<()> {
-        match self { 
-            Player::X => BIG_X.draw_with_color(term_row, term_col, Color::DarkCyan),
-            Player::O => BIG_O.draw_with_color(term_row, term_col, Color::DarkMagenta),
+    fn draw(&self, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> crossterm::Result<()> {
+        let img = match self { Player::X => BIG_X, Player::O => BIG_O };
+        img.draw_with_color(term_row, term_col, theme.player(*self), theme, glyphs)
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Mark
+//--------------------------------------------------------------------------------------
+
+/// A mark placed in a space. Usually each player has their own, but some rules let
+/// either player place either mark. Under numerical rules the marks are the numbers
+/// from 1 to 9, odd for X and even for O.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub(crate) enum Mark { X, O, Number(u8) }
+
+impl Mark {
+    /// The big picture of the mark drawn in a space
+    pub(crate) fn glyph(&self) -> [&'static str; 12] {
+        match self {
+            Mark::X => BIG_X,
+            Mark::O => BIG_O,
+            Mark::Number(n) => BIG_DIGITS[*n as usize - 1],
+        }
+    }
+}
+
+impl std::fmt::Display for Mark {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Mark::X => write!(f, "X"),
+            Mark::O => write!(f, "O"),
+            Mark::Number(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+impl Draw for Mark {
+    fn draw(&self, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> crossterm::Result<()> {
+        self.glyph().draw_with_color(term_row, term_col, theme.mark(*self), theme, glyphs)
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Rules
+//--------------------------------------------------------------------------------------
+
+/// The rule variants a game can be played under
+/// - Standard: Completing three in a row wins.
+/// - Misere: Completing three in a row loses.
+/// - Wild: Either player may place either mark, and completing three of the same mark
+///   in a row wins.
+/// - Notakto: Both players place X, and completing three in a row loses. Played with
+///   more than one board, see `Notakto`.
+/// - Morris: Three men's morris. Each player has three marks, and once they're all
+///   placed, slides one of them to a neighboring empty space each turn. Completing
+///   three in a row wins, being unable to slide loses, and a position coming up for
+///   the third time is a draw.
+/// - Infinite: Each player keeps at most three marks on the board, and placing a
+///   fourth removes their oldest. Completing three in a row wins, and a position
+///   coming up for the third time is a draw.
+/// - Quantum: Each move puts a spooky mark in two spaces, which settles into one of
+///   them when a cycle forms. Played by its own rules engine, see `Quantum`.
+/// - Numerical: X places the odd numbers and O the even ones, each number once.
+///   Completing a line of three numbers that sum to 15 wins, whoever's they are.
+/// - Order: Order and Chaos on a 6x6 board, where either player may place either
+///   mark. Played by its own rules engine, see `OrderChaos`.
+/// - Gravity: Marks drop to the bottom of a column, as in Connect Four, on a board
+///   of any size. Played by its own rules engine, see `Gravity`.
+/// - Cube: Three in a row in a 3x3x3 cube, along any of its 49 lines. Played by its
+///   own rules engine, see `Cube`.
+/// - Qubic: Four in a row in a 4x4x4 cube, along any of its 76 lines. Played by the
+///   same engine as `Cube`.
+/// - Three: Three players, X, O and Y, take turns on a 4x4 or 5x5 board, and
+///   completing three in a row wins. Played by its own rules engine, see `Three`.
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
+pub(crate) enum Rules {
+    #[default]
+    Standard,
+    Misere,
+    Wild,
+    Notakto,
+    Morris,
+    Infinite,
+    Quantum,
+    Numerical,
+    Order,
+    Gravity,
+    Cube,
+    Qubic,
+    Three,
+}
+
+impl Rules {
+    /// Every variant, in the order they're offered on the setup screen
+    pub(crate) const ALL: [Rules; 13] = [
+        Rules::Standard, Rules::Misere, Rules::Wild, Rules::Notakto, Rules::Morris, Rules::Infinite, Rules::Quantum,
+        Rules::Numerical, Rules::Order, Rules::Gravity, Rules::Cube, Rules::Qubic, Rules::Three,
+    ];
+
+    /// Look up a variant by the name used on the command line
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "standard" => Some(Rules::Standard),
+            "misere" => Some(Rules::Misere),
+            "wild" => Some(Rules::Wild),
+            "notakto" => Some(Rules::Notakto),
+            "morris" => Some(Rules::Morris),
+            "infinite" => Some(Rules::Infinite),
+            "quantum" => Some(Rules::Quantum),
+            "numerical" => Some(Rules::Numerical),
+            "order" => Some(Rules::Order),
+            "gravity" => Some(Rules::Gravity),
+            "3d" => Some(Rules::Cube),
+            "qubic" => Some(Rules::Qubic),
+            "three" => Some(Rules::Three),
+            _ => None,
+        }
+    }
+
+    /// Is a game by these rules played on a `Game`? Quantum, Order and Chaos, gravity,
+    /// 3D and three-player games have their own engines, and so can't be solved,
+    /// enumerated or played by bots.
+    pub(crate) fn is_classical(&self) -> bool {
+        !matches!(self, Rules::Quantum | Rules::Order | Rules::Gravity | Rules::Cube | Rules::Qubic | Rules::Three)
+    }
+
+    /// Can a position come up again? Only when marks move or vanish, so the board
+    /// doesn't just fill up. Under these rules the third repetition is a draw.
+    pub(crate) fn can_repeat(&self) -> bool {
+        matches!(self, Rules::Morris | Rules::Infinite)
+    }
+
+    /// Can lines wrap around the edges of the board? Only for rules played with
+    /// marks on a `Game`, except numerical, whose lines are sums of numbers.
+    pub(crate) fn can_wrap(&self) -> bool {
+        self.is_classical() && *self != Rules::Numerical
+    }
+}
+
+impl std::fmt::Display for Rules {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Rules::Standard => write!(f, "standard"),
+            Rules::Misere => write!(f, "misere"),
+            Rules::Wild => write!(f, "wild"),
+            Rules::Notakto => write!(f, "notakto"),
+            Rules::Morris => write!(f, "morris"),
+            Rules::Infinite => write!(f, "infinite"),
+            Rules::Quantum => write!(f, "quantum"),
+            Rules::Numerical => write!(f, "numerical"),
+            Rules::Order => write!(f, "order"),
+            Rules::Gravity => write!(f, "gravity"),
+            Rules::Cube => write!(f, "3d"),
+            Rules::Qubic => write!(f, "qubic"),
+            Rules::Three => write!(f, "three"),
         }
     }
 }
@@ -95,7 +264,7 @@ impl Draw for Player {
 //-- Game Space Coordinate
 //--------------------------------------------------------------------------------------
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub(crate) struct Coordinate {
     row: usize,
     col: usize,
@@ -123,15 +292,50 @@ impl From<(usize, usize)> for Coordinate {
     }
 }
 
+impl Coordinate {
+    /// Are two spaces joined by a line on a morris board? Every space is joined to the
+    /// spaces beside, above and below it, and the center is joined to every space.
+    pub(crate) fn is_adjacent(&self, other: Coordinate) -> bool {
+        let center = Coordinate::new(1, 1);
+        let (rows, cols) = (self.row.abs_diff(other.row), self.col.abs_diff(other.col));
+        match (rows, cols) {
+            (0, 1) | (1, 0) => true,
+            (1, 1) => *self == center || other == center,
+            _ => false,
+        }
+    }
+}
+
+/// A single move: the space marked and the mark placed there. Under morris rules, a
+/// move can slide a mark from another space instead of placing a new one.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub(crate) struct Move {
+    pub(crate) coord: Coordinate,
+    pub(crate) mark: Mark,
+    pub(crate) from: Option<Coordinate>,  // The space the mark slid from, if it did
+}
+
+impl Move {
+    pub(crate) fn new(coord: Coordinate, mark: Mark) -> Self {
+        Move { coord, mark, from: None }
+    }
+
+    /// A move that slides the mark in `from` to `coord`
+    pub(crate) fn slide(from: Coordinate, coord: Coordinate, mark: Mark) -> Self {
+        Move { coord, mark, from: Some(from) }
+    }
+}
+
 
 //--------------------------------------------------------------------------------------
 //-- Game Space
 //--------------------------------------------------------------------------------------
 
 /// Represents a single space on the game board
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct GameSpace {
-    mark: Option<Player>,     // Corresponds to which player marked that space
+    mark: Option<Mark>,       // The mark placed in that space, if any
+    placed: usize,            // The number of moves made before the mark was placed
     value: u8,                // The 'magic square' value for this space
     coordinate: Coordinate,   // The coordinate in the game board containing this space
 }
@@ -139,10 +343,10 @@ pub(crate) struct GameSpace {
 impl GameSpace {
     pub(crate) fn new(value: u8, row: usize, col: usize) -> Self { 
         let coordinate = Coordinate::new(row, col);
-        GameSpace { mark: None, value, coordinate } 
+        GameSpace { mark: None, placed: 0, value, coordinate } 
     }
 
-    pub(crate) fn get_mark(&self) -> Option<Player> {
+    pub(crate) fn get_mark(&self) -> Option<Mark> {
         self.mark
     }
 
@@ -152,11 +356,11 @@ impl GameSpace {
 }
 
 impl Draw for GameSpace {
-    fn draw(&self, term_row: u16, term_col: u16) -> crossterm::Result<()> {
+    fn draw(&self, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> crossterm::Result<()> {
         let (row, col) = self.coordinate.into();
         let out_row = term_row + (row as u16 * ROW_HEIGHT);
         let out_col = term_col + (col as u16 * COL_WIDTH);
-        if let Some(player) = self.mark { return player.draw(out_row, out_col); }
+        if let Some(mark) = self.mark { return mark.draw(out_row, out_col, theme, glyphs); }
         Ok(())
     }
 }
@@ -166,94 +370,375 @@ impl Draw for GameSpace {
 //-- Game
 //--------------------------------------------------------------------------------------
 
+/// Every line of three spaces: the rows, the columns and the two diagonals, then the
+/// diagonals that only count when lines wrap around the edges of the board
+const LINES: [[(usize, usize); 3]; 12] = [
+    [(0, 0), (0, 1), (0, 2)], [(1, 0), (1, 1), (1, 2)], [(2, 0), (2, 1), (2, 2)],
+    [(0, 0), (1, 0), (2, 0)], [(0, 1), (1, 1), (2, 1)], [(0, 2), (1, 2), (2, 2)],
+    [(0, 0), (1, 1), (2, 2)], [(0, 2), (1, 1), (2, 0)],
+    [(0, 1), (1, 2), (2, 0)], [(0, 2), (1, 0), (2, 1)],
+    [(0, 0), (1, 2), (2, 1)], [(0, 1), (1, 0), (2, 2)],
+];
+
+/// How many of `LINES` wrap around the edges
+const WRAPPED_LINES: usize = 4;
+
 /// Represents a Tic Tac Toe game
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct Game {
     board: [[GameSpace; 3]; 3],  // The game board represented by game spaces
-    player: Player               // The current player, Player::X or Player::O
+    player: Player,              // The current player, Player::X or Player::O
+    moves: Vec<Move>,            // Every move made so far, in order
+    rules: Rules,                // What wins, and which marks each player may place
+    positions: Vec<u32>,         // Every position so far with the player to move, for repetition
+    wrap: bool,                  // Do lines wrap around the edges, as if the board were a torus?
 }
 
 impl<'a> Game {
     /// Create a new, empty game board
-    pub(crate) fn new() -> Self { 
+    #[cfg(test)]
+    pub(crate) fn new() -> Self {
+        Self::with_rules(Rules::Standard)
+    }
+
+    /// Create a new, empty game board played under the given rules
+    pub(crate) fn with_rules(rules: Rules) -> Self {
         let board = [
             [GameSpace::new(2, 0, 0), GameSpace::new(7, 0, 1), GameSpace::new(6, 0, 2)],
             [GameSpace::new(9, 1, 0), GameSpace::new(5, 1, 1), GameSpace::new(1, 1, 2)],
             [GameSpace::new(4, 2, 0), GameSpace::new(3, 2, 1), GameSpace::new(8, 2, 2)]
         ];
-        Game { board, player: Player::X } 
+        let mut game = Game { board, player: Player::X, moves: Vec::with_capacity(9), rules, positions: Vec::new(), wrap: false };
+        game.positions.push(game.repetition_key());
+        game
     }
 
-    /// Add a 'move' to the game board, marking a space according to the current player.
-    /// Returns an error if the space indicated by 'move' is currently occupied.
+    /// The same game, with lines wrapping around the edges of the board if `wrap` is
+    /// set. Rows and columns are no different, but there are six diagonals instead of
+    /// two, like (0, 2), (1, 0), (2, 1).
+    pub(crate) fn with_wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Add a 'move' to the game board, marking a space with the current player's usual
+    /// mark. Returns an error if the space indicated by 'move' is currently occupied.
     pub(crate) fn add_move(&mut self, coord: Coordinate) -> Result<()> {
-        let (row, col) = coord.into();
-        if let Some(_) = self.board[row][col].mark {
-            Err(GameError::SpaceOccupied)
-        } else {
-            self.board[row][col].mark = Some(self.player);
-            self.player.toggle();
-            Ok(())
+        self.play(Move::new(coord, self.usual_mark()))
+    }
+
+    /// Add a 'move' to the game board, placing the mark it names, or sliding it from
+    /// another space. Returns an error if the space is currently occupied, the rules
+    /// don't let the current player place that mark, or the player must slide a mark
+    /// but didn't, or can't slide it from where they tried to, or the number is
+    /// already on the board. Under infinite rules, a player's fourth mark removes
+    /// their oldest.
+    pub(crate) fn play(&mut self, mv: Move) -> Result<()> {
+        let (row, col) = mv.coord.into();
+        if self.board[row][col].mark.is_some() { return Err(GameError::SpaceOccupied); }
+        if matches!(mv.mark, Mark::Number(_)) && self.iter().any(|space| space.mark == Some(mv.mark)) {
+            return Err(GameError::NumberUsed);
+        }
+        if !self.marks().contains(&mv.mark) { return Err(GameError::WrongMark); }
+        match mv.from {
+            None if self.must_slide() => return Err(GameError::MustSlide),
+            None => {},
+            Some(from) => {
+                let legal = self.must_slide() && from.is_adjacent(mv.coord) && self.get_space(from).mark == Some(mv.mark);
+                if !legal { return Err(GameError::IllegalSlide); }
+                let (from_row, from_col) = from.into();
+                self.board[from_row][from_col].mark = None;
+            },
+        }
+        if let Some(oldest) = self.vanishing() {
+            let (old_row, old_col) = oldest.into();
+            self.board[old_row][old_col].mark = None;
+        }
+        self.board[row][col].mark = Some(mv.mark);
+        self.board[row][col].placed = self.moves.len();
+        self.moves.push(mv);
+        self.player.toggle();
+        self.positions.push(self.repetition_key());
+        Ok(())
+    }
+
+    /// Must the current player slide one of their marks, instead of placing a new one?
+    /// Only under morris rules, once all three of their marks are placed.
+    pub(crate) fn must_slide(&self) -> bool {
+        let own = self.player.mark();
+        self.rules == Rules::Morris && self.iter().filter(|space| space.mark == Some(own)).count() == 3
+    }
+
+    /// The space holding the mark that the current player's next move removes, under
+    /// infinite rules once they have three marks on the board
+    pub(crate) fn vanishing(&self) -> Option<Coordinate> {
+        if self.rules != Rules::Infinite { return None; }
+        match self.aged(self.player.mark()).as_slice() {
+            [oldest, _, _] => Some(*oldest),
+            _ => None,
         }
     }
 
+    /// The spaces holding a mark, from the oldest mark to the newest
+    pub(crate) fn aged(&self, mark: Mark) -> Vec<Coordinate> {
+        let mut spaces: Vec<_> = self.iter().filter(|space| space.mark == Some(mark)).collect();
+        spaces.sort_by_key(|space| space.placed);
+        spaces.into_iter().map(|space| space.coordinate).collect()
+    }
+
+    /// A number identifying the marks on the board and the player to move, and under
+    /// infinite rules, the order the marks will vanish in
+    pub(crate) fn repetition_key(&self) -> u32 {
+        let key = position_key(self) * 2 + if self.player == Player::X { 0 } else { 1 };
+        if self.rules != Rules::Infinite { return key; }
+
+        // The two oldest marks of each player fix the order of all three, with 9 for
+        // none. There are 3^9 * 2 keys for the marks alone.
+        let order = [Mark::X, Mark::O].iter()
+            .flat_map(|mark| {
+                let aged = self.aged(*mark);
+                (0..2).map(move |idx| aged.get(idx).map_or(9, |coord| coord.row * 3 + coord.col) as u32)
+            })
+            .fold(0, |order, idx| order * 10 + idx);
+        key + order * 3u32.pow(9) * 2
+    }
+
+    /// How many times the current position has come up, counting this time
+    pub(crate) fn repetitions(&self) -> usize {
+        let key = self.repetition_key();
+        self.positions.iter().filter(|seen| **seen == key).count()
+    }
+
     /// Create a new Game from a series of 'moves'
     /// If any of the moves is invalid (space already occupied), return an error.
-    /// Used primarily for testing
-    #[allow(dead_code)]
+    /// Used only for testing
+    #[cfg(test)]
     pub(crate) fn from(moves: &[Coordinate]) -> Result<Self> {
         let mut game = Self::new();
         for mv in moves { game.add_move(*mv)?; }
         Ok(game)
     }
 
+    /// Return the rules the game is played under
+    pub(crate) fn rules(&self) -> Rules {
+        self.rules
+    }
+
+    /// Do lines wrap around the edges of the board?
+    pub(crate) fn wraps(&self) -> bool {
+        self.wrap
+    }
+
     /// Return the current player
     pub(crate) fn current_player(&self) -> Player {
         self.player
     }
 
+    /// Return every move made so far, in order. X made the moves at even indices.
+    pub(crate) fn history(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// Return the marks the current player may place. Under numerical rules, the
+    /// numbers they haven't placed yet, smallest first.
+    pub(crate) fn marks(&self) -> Vec<Mark> {
+        match self.rules {
+            Rules::Wild => vec![Mark::X, Mark::O],
+            Rules::Notakto => vec![Mark::X],
+            Rules::Numerical => {
+                let first = if self.player == Player::X { 1 } else { 2 };
+                (first..=9).step_by(2)
+                    .map(Mark::Number)
+                    .filter(|mark| self.iter().all(|space| space.mark != Some(*mark)))
+                    .collect()
+            },
+            _ => vec![self.player.mark()],
+        }
+    }
+
+    /// Return the mark the current player places when they don't choose one: their
+    /// own, if the rules allow it
+    pub(crate) fn usual_mark(&self) -> Mark {
+        let marks = self.marks();
+        let own = self.player.mark();
+        if marks.contains(&own) { own } else { marks[0] }
+    }
+
     /// Return an iterator that yields references to the individual game spaces, in
     /// order from left to right, top to bottom.
     pub(crate) fn iter(&'a self) -> GameIterator<'a> {
-        GameIterator { game: &self, row: 0, col: 0 }
+        GameIterator { game: self, row: 0, col: 0 }
     }
     
+    /// Return the coordinates of every unoccupied space, left to right, top to bottom
+    pub(crate) fn available_moves(&self) -> Vec<Coordinate> {
+        self.iter()
+            .filter(|space| space.mark.is_none())
+            .map(|space| space.coordinate)
+            .collect()
+    }
+
+    /// Return every move the current player may make: each mark they may place in
+    /// each unoccupied space, left to right, top to bottom. When they must slide a
+    /// mark instead, each slide, in order by the space slid from.
+    pub(crate) fn legal_moves(&self) -> Vec<Move> {
+        if self.must_slide() {
+            let own = self.player.mark();
+            let empty = self.available_moves();
+            return self.iter()
+                .filter(|space| space.mark == Some(own))
+                .flat_map(|space| {
+                    let from = space.coordinate;
+                    empty.iter()
+                        .filter(move |to| from.is_adjacent(**to))
+                        .map(move |to| Move::slide(from, *to, own))
+                })
+                .collect();
+        }
+        let marks = self.marks();
+        self.available_moves()
+            .into_iter()
+            .flat_map(|coord| marks.iter().map(move |mark| Move::new(coord, *mark)))
+            .collect()
+    }
+
     /// Return a reference to a game space given by its row/col index
     pub(crate) fn get_space(&self, coord: Coordinate) -> &GameSpace {
         let (row, col) = coord.into();
         &self.board[row][col]
     }
 
-    /// Return a mapping of player to the values of the spaces occupied by that player.
-    /// The values are derived from a 3x3 magic square where each horizontal, vertical,
-    /// and diagonal line sums to 15.
-    pub(crate) fn get_player_scores(&self) -> HashMap<Player, Vec<u8>> {
-        let mut player_scores = HashMap::new();
+    /// Return a mapping of mark to the values of the spaces holding that mark. The
+    /// values are derived from a 3x3 magic square where each horizontal, vertical, and
+    /// diagonal line sums to 15.
+    pub(crate) fn get_mark_scores(&self) -> HashMap<Mark, Vec<u8>> {
+        let mut mark_scores = HashMap::new();
         for space in self.iter() {
             let (row, col) = space.coordinate.into();
-            if let Some(p) = self.board[row][col].mark {
-                let scores = player_scores.entry(p).or_insert(Vec::with_capacity(5));
+            if let Some(mark) = self.board[row][col].mark {
+                let scores = mark_scores.entry(mark).or_insert(Vec::with_capacity(5));
                 scores.push(space.value);
             }
         }
-        player_scores
+        mark_scores
     }
 
-    /// Determines the winner of the game, as it stands, if there is one. Returns None
-    /// if there is no winner. A winner is declared if any three of the values of the
-    /// spaces occupied by that player sum to 15.
-    pub(crate) fn get_winner(&self) -> Option<Player> {
-        let player_scores = self.get_player_scores();
-        for (player, scores) in player_scores.iter() {
+    /// Determines which mark is three in a row, if either is. Returns None if neither
+    /// is. A mark is three in a row if any three of the values of the spaces holding
+    /// that mark sum to 15. Wrapped lines don't, so they're looked for one by one.
+    pub(crate) fn get_line_mark(&self) -> Option<Mark> {
+        if self.wrap {
+            return self.line().and_then(|line| self.get_space(line[0]).mark);
+        }
+        let mark_scores = self.get_mark_scores();
+        for (mark, scores) in mark_scores.iter() {
             // Do any unique three-space combinations sum to 15?
-            let player_wins = scores.iter()
+            let has_line = scores.iter()
                 .combinations(3)
-                .any(|scores| scores.iter().map(|i| *i).sum::<u8>() == 15);
-            if player_wins { return Some(*player) }
+                .any(|scores| scores.iter().copied().sum::<u8>() == 15);
+            if has_line { return Some(*mark) }
         }
         None
     }
 
+    /// Every line of three spaces, the four diagonals that wrap around the edges last
+    /// when lines wrap
+    fn lines(&self) -> impl Iterator<Item = [Coordinate; 3]> {
+        let count = if self.wrap { LINES.len() } else { LINES.len() - WRAPPED_LINES };
+        LINES[..count].iter().map(|line| [line[0].into(), line[1].into(), line[2].into()])
+    }
+
+    /// The spaces of a line of three of the same mark, if there is one
+    pub(crate) fn line(&self) -> Option<[Coordinate; 3]> {
+        self.lines().find(|line| {
+            let mark = self.get_space(line[0]).mark;
+            mark.is_some() && line.iter().all(|coord| self.get_space(*coord).mark == mark)
+        })
+    }
+
+    /// The spaces of a line of three of the same mark that wraps around the edges of
+    /// the board, if there is one
+    pub(crate) fn wrapped_line(&self) -> Option<[Coordinate; 3]> {
+        let line = self.line()?;
+        let wrapped = LINES[LINES.len() - WRAPPED_LINES..].iter()
+            .any(|wrapped| wrapped.iter().zip(line.iter()).all(|(a, b)| Coordinate::from(*a) == *b));
+        wrapped.then_some(line)
+    }
+
+    /// The number in each space, indexed by the space's magic square value, with 0 for
+    /// none. Three spaces are in a line when their indices sum to 15.
+    fn numbers_by_value(&self) -> [u8; 10] {
+        let mut numbers = [0; 10];
+        for space in self.iter() {
+            if let Some(Mark::Number(n)) = space.mark { numbers[space.value as usize] = n; }
+        }
+        numbers
+    }
+
+    /// Is there a line of three numbers summing to 15? Like the spaces of a line, whose
+    /// magic square values sum to 15 too.
+    pub(crate) fn has_fifteen(&self) -> bool {
+        let numbers = self.numbers_by_value();
+        (1..=9).tuple_combinations()
+            .filter(|(a, b, c)| a + b + c == 15)
+            .any(|(a, b, c)| {
+                let line = [numbers[a], numbers[b], numbers[c]];
+                !line.contains(&0) && line.iter().sum::<u8>() == 15
+            })
+    }
+
+    /// Under numerical rules, the empty spaces where a player could complete a line
+    /// summing to 15 with one of the numbers they have left
+    pub(crate) fn fifteen_threats(&self, player: Player) -> Vec<Coordinate> {
+        let numbers = self.numbers_by_value();
+        let parity = if player == Player::X { 1 } else { 0 };
+        let mut threats = Vec::new();
+        for line in (1..=9usize).tuple_combinations().filter(|(a, b, c)| a + b + c == 15) {
+            let (a, b, c) = line;
+            let (empty, sum) = match (numbers[a], numbers[b], numbers[c]) {
+                (0, x, y) if x > 0 && y > 0 => (a, x + y),
+                (x, 0, y) if x > 0 && y > 0 => (b, x + y),
+                (x, y, 0) if x > 0 && y > 0 => (c, x + y),
+                _ => continue,
+            };
+            let needed = 15 - sum.min(15);
+            if needed == 0 || needed > 9 || needed % 2 != parity || numbers.contains(&needed) { continue; }
+            let coord = self.iter().find(|space| space.value as usize == empty).map(|space| space.coordinate);
+            if let Some(coord) = coord {
+                if !threats.contains(&coord) { threats.push(coord); }
+            }
+        }
+        threats
+    }
+
+    /// Determines the winner of the game, as it stands, if there is one. Returns None
+    /// if there is no winner. Under standard rules the player whose mark is three in a
+    /// row wins, under misere rules their opponent does, under wild rules whoever made
+    /// the line wins, whichever mark it is, and under notakto rules whoever made it
+    /// loses. A morris player with nowhere to slide loses too. Under numerical rules
+    /// whoever completed a line summing to 15 wins.
+    pub(crate) fn get_winner(&self) -> Option<Player> {
+        let mut last_player = self.player;
+        last_player.toggle();
+        if self.rules == Rules::Numerical {
+            return if self.has_fifteen() { Some(last_player) } else { None };
+        }
+        let mark = match self.get_line_mark() {
+            Some(mark) => mark,
+            None if self.must_slide() && self.legal_moves().is_empty() => return Some(last_player),
+            None => return None,
+        };
+        let mut owner = if mark == Mark::X { Player::X } else { Player::O };
+        match self.rules {
+            Rules::Standard | Rules::Morris | Rules::Infinite => Some(owner),
+            // Played by their own engines, but three in a row still belongs to its owner
+            Rules::Quantum | Rules::Order | Rules::Gravity | Rules::Cube | Rules::Qubic | Rules::Three => Some(owner),
+            Rules::Misere => { owner.toggle(); Some(owner) },
+            Rules::Wild | Rules::Numerical => Some(last_player),
+            Rules::Notakto => Some(self.player),
+        }
+    }
+
     /// Count the number of occupied spaces on the game board
     pub(crate) fn count_occupied_spaces(&self) -> u8 {
         let mut occupied_spaces = 0;
@@ -267,23 +752,44 @@ impl<'a> Game {
     pub(crate) fn status(&self) -> GameStatus {
         if let Some(player) = self.get_winner() { return GameStatus::Winner(player) }
         if self.count_occupied_spaces() == 9 { return GameStatus::Draw }
+        if self.rules.can_repeat() && self.repetitions() >= 3 { return GameStatus::Draw }
         GameStatus::Pending(self.player)
     }
 }
 
-impl Draw for Game {
-    fn draw(&self, term_row: u16, term_col: u16) -> crossterm::Result<()> {
-        let mut stdout = std::io::stdout();
-
+impl Game {
+    /// Draw the grid and the marks in it, without the status message
+    pub(crate) fn draw_board(&self, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> crossterm::Result<()> {
         // Print the game grid (#)
-        execute!(stdout, SetForegroundColor(Color::Grey))?;
-        BIG_GRID.draw(term_row, term_col)?;
+        execute!(std::io::stdout(), SetForegroundColor(theme.grid))?;
+        BIG_GRID.draw(term_row, term_col, theme, glyphs)?;
+
+        // Print out the game spaces. Under infinite rules, the mark the next move
+        // removes is dimmed to the 'Try Again' color. When lines wrap around the edges,
+        // they can be hard to spot, so a finished line is lit in the 'Wins!' color.
+        let vanishing = self.vanishing();
+        let dimmed = Theme { x: theme.draw, o: theme.draw, ..*theme };
+        let line = if self.wrap { self.line() } else { None };
+        let lit = Theme { x: theme.win, o: theme.win, ..*theme };
+        for space in self.iter() {
+            let theme = match line {
+                Some(line) if line.contains(&space.coordinate) => &lit,
+                _ if Some(space.coordinate) == vanishing => &dimmed,
+                _ => theme,
+            };
+            space.draw(term_row, term_col, theme, glyphs)?;
+        }
+        Ok(())
+    }
+}
 
-        // Print out the game spaces
-        for space in self.iter() { space.draw(term_row, term_col)?; }
+impl Draw for Game {
+    fn draw(&self, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> crossterm::Result<()> {
+        let mut stdout = std::io::stdout();
+        self.draw_board(term_row, term_col, theme, glyphs)?;
 
         // Print a status message to the right of the game grid
-        self.status().draw(term_row, term_col + 104)?;
+        self.status().draw(term_row, term_col + 104, theme, glyphs)?;
 
         // Reset the color and move the cursor underneath the message
         execute!(stdout, ResetColor, MoveTo(term_col + 104, term_row + 15))?;
@@ -395,6 +901,116 @@ mod tests {
         assert_eq!(game.status(), GameStatus::Winner(Player::O));
     }
 
+    #[test]
+    fn test_misere() {
+        // Completing a line loses
+        let mut game = Game::with_rules(Rules::Misere);
+        for coord in to_coord_vec(&[(0, 0), (2, 0), (1, 1), (2, 1), (2, 2)]) {
+            game.add_move(coord).expect("Failed to add move.");
+        }
+        assert_eq!(game.get_line_mark(), Some(Mark::X));
+        assert_eq!(game.status(), GameStatus::Winner(Player::O));
+    }
+
+    #[test]
+    fn test_wild() {
+        // O completes a line of X's, and wins with it
+        let mut game = Game::with_rules(Rules::Wild);
+        let moves = [((0, 0), Mark::X), ((1, 1), Mark::O), ((0, 1), Mark::X), ((0, 2), Mark::X)];
+        for (coord, mark) in moves.iter() {
+            game.play(Move::new((*coord).into(), *mark)).expect("Failed to add move.");
+        }
+        assert_eq!(game.get_line_mark(), Some(Mark::X));
+        assert_eq!(game.status(), GameStatus::Winner(Player::O));
+
+        // Other rules only allow each player their own mark
+        let mut game = Game::new();
+        assert_eq!(game.play(Move::new((0, 0).into(), Mark::O)), Err(GameError::WrongMark));
+    }
+
+    #[test]
+    fn test_wrap() {
+        // X's diagonal wraps around the edges, so only counts when lines wrap
+        let moves = to_coord_vec(&[(0, 2), (0, 0), (1, 0), (1, 1), (2, 1)]);
+        let mut game = Game::new();
+        let mut wrapped = Game::new().with_wrap(true);
+        for coord in moves {
+            game.add_move(coord).expect("Failed to add move.");
+            wrapped.add_move(coord).expect("Failed to add move.");
+        }
+        assert_eq!(game.status(), GameStatus::Pending(Player::O));
+        assert_eq!(wrapped.status(), GameStatus::Winner(Player::X));
+        assert_eq!(wrapped.wrapped_line(), Some([(0, 2).into(), (1, 0).into(), (2, 1).into()]));
+
+        // Ordinary lines still count, but don't wrap
+        let wrapped = Game::from(&to_coord_vec(&[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)])).unwrap().with_wrap(true);
+        assert_eq!(wrapped.status(), GameStatus::Winner(Player::X));
+        assert_eq!(wrapped.wrapped_line(), None);
+    }
+
+    #[test]
+    fn test_morris() {
+        // Once all three marks are placed, they slide to neighboring spaces
+        let mut game = Game::with_rules(Rules::Morris);
+        for coord in to_coord_vec(&[(0, 0), (1, 1), (2, 2), (0, 2), (2, 0), (1, 0)]) {
+            game.add_move(coord).expect("Failed to add move.");
+        }
+        assert_eq!(game.add_move((0, 1).into()), Err(GameError::MustSlide));
+        assert_eq!(game.play(Move::slide((0, 0).into(), (1, 2).into(), Mark::X)), Err(GameError::IllegalSlide));
+        assert_eq!(game.play(Move::slide((1, 1).into(), (2, 1).into(), Mark::X)), Err(GameError::IllegalSlide));
+        game.play(Move::slide((2, 2).into(), (2, 1).into(), Mark::X)).expect("Failed to slide.");
+        assert_eq!(game.get_space((2, 2).into()).get_mark(), None);
+
+        // Sliding back and forth repeats the position, and the third time is a draw
+        let back_and_forth = [((0, 2), (0, 1)), ((2, 1), (2, 2)), ((0, 1), (0, 2)), ((2, 2), (2, 1))];
+        for _ in 0..2 {
+            assert_eq!(game.status(), GameStatus::Pending(Player::O));
+            for (from, to) in back_and_forth.iter() {
+                let mark = game.current_player().mark();
+                game.play(Move::slide((*from).into(), (*to).into(), mark)).expect("Failed to slide.");
+            }
+        }
+        assert_eq!(game.repetitions(), 3);
+        assert_eq!(game.status(), GameStatus::Draw);
+    }
+
+    #[test]
+    fn test_infinite() {
+        // X's fourth mark removes their first, so the top row isn't complete
+        let mut game = Game::with_rules(Rules::Infinite);
+        for coord in to_coord_vec(&[(0, 0), (1, 1), (0, 1), (2, 0), (2, 2)]) {
+            game.add_move(coord).expect("Failed to add move.");
+        }
+        assert_eq!(game.vanishing(), None);
+        game.add_move((1, 2).into()).expect("Failed to add move.");
+        assert_eq!(game.vanishing(), Some(Coordinate::new(0, 0)));
+        assert_eq!(game.add_move((0, 0).into()), Err(GameError::SpaceOccupied));
+        game.add_move((0, 2).into()).expect("Failed to add move.");
+        assert_eq!(game.get_space((0, 0).into()).get_mark(), None);
+        assert_eq!(game.aged(Mark::X), vec![Coordinate::new(0, 1), Coordinate::new(2, 2), Coordinate::new(0, 2)]);
+        assert_eq!(game.vanishing(), Some(Coordinate::new(1, 1)));
+        assert_eq!(game.status(), GameStatus::Pending(Player::O));
+    }
+
+    #[test]
+    fn test_numerical() {
+        // O completes the middle row with X's 7 in it, 2 + 7 + 6
+        let mut game = Game::with_rules(Rules::Numerical);
+        let number = |coord: (usize, usize), n| Move::new(coord.into(), Mark::Number(n));
+        for mv in [number((0, 0), 3), number((1, 0), 2), number((0, 1), 5)] {
+            game.play(mv).expect("Failed to play.");
+        }
+        assert_eq!(game.marks(), vec![Mark::Number(4), Mark::Number(6), Mark::Number(8)]);
+        assert_eq!(game.fifteen_threats(Player::X), vec![Coordinate::new(0, 2)]);
+        assert_eq!(game.play(number((2, 2), 7)), Err(GameError::WrongMark));
+        assert_eq!(game.play(number((2, 2), 2)), Err(GameError::NumberUsed));
+        game.play(number((0, 2), 8)).expect("Failed to play.");
+        game.play(number((1, 1), 7)).expect("Failed to play.");
+        assert_eq!(game.status(), GameStatus::Pending(Player::O));
+        game.play(number((1, 2), 6)).expect("Failed to play.");
+        assert_eq!(game.status(), GameStatus::Winner(Player::O));
+    }
+
     #[test]
     fn test_eight() {
         // Returns an error when attempting to add a duplicate move
@@ -0,0 +1,410 @@
+//! Gravity rules, as in Connect Four: each mark drops to the lowest empty space in the
+//! column it's dropped into. The board can be any size from 3x3 to 9x8, and the run
+//! needed to win from three to as many as fit.
+
+use crate::ai::Ai;
+use crate::cursor::{Cursor, Direction};
+use crate::describe;
+use crate::display::{small_grid, SMALL_CURSOR, SMALL_X, SMALL_O, SMALL_ROW_HEIGHT, SMALL_COL_WIDTH, Draw, DrawWithColor, Glyphs};
+use crate::error::{GameError, Result};
+use crate::game::{Coordinate, GameStatus, Mark, Player, Rules};
+use crate::input::Action;
+use crate::lines;
+use crate::plain;
+use crate::theme::Theme;
+use crate::variant::Variant;
+
+/// The smallest and largest number of columns
+pub(crate) const COLUMNS: std::ops::RangeInclusive<usize> = 3..=9;
+
+/// The smallest and largest number of rows
+pub(crate) const ROWS: std::ops::RangeInclusive<usize> = 3..=8;
+
+/// How many moves ahead the hard computer player looks
+const DEPTH: usize = 5;
+
+
+//--------------------------------------------------------------------------------------
+//-- Shape
+//--------------------------------------------------------------------------------------
+
+/// The size of the board, and how many in a row win
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) struct Shape {
+    pub(crate) cols: usize,  // The number of columns to drop marks into
+    pub(crate) rows: usize,  // The number of marks each column holds
+    pub(crate) run: usize,   // How many in a row win
+}
+
+impl Default for Shape {
+    /// The Connect Four board: seven columns of six, four in a row
+    fn default() -> Self {
+        Shape { cols: 7, rows: 6, run: 4 }
+    }
+}
+
+impl Shape {
+    /// Parse the columns and rows from a size like "7x6", if both are allowed
+    pub(crate) fn parse_size(size: &str) -> Option<(usize, usize)> {
+        let (cols, rows) = size.split_once('x')?;
+        let (cols, rows) = (cols.parse().ok()?, rows.parse().ok()?);
+        (COLUMNS.contains(&cols) && ROWS.contains(&rows)).then_some((cols, rows))
+    }
+
+    /// Does a run this long fit on the board, across or down?
+    pub(crate) fn fits(&self) -> bool {
+        self.run >= 3 && self.run <= self.cols.max(self.rows)
+    }
+
+    /// Every run of spaces long enough to win: across, down, and along both diagonals
+    pub(crate) fn windows(&self) -> Vec<Vec<Coordinate>> {
+        lines::windows(self.rows, self.cols, self.run)
+    }
+}
+
+impl std::fmt::Display for Shape {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}x{}", self.cols, self.rows)
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Gravity
+//--------------------------------------------------------------------------------------
+
+/// A game played by gravity rules
+#[derive(Debug, Clone)]
+pub(crate) struct Gravity {
+    shape: Shape,                       // The size of the board and the run to win
+    board: Vec<Vec<Option<Player>>>,    // Whose mark is in each space, rows top to bottom
+    moves: Vec<Coordinate>,             // Where every mark so far landed, in order
+    player: Player,                     // The current player
+}
+
+impl Gravity {
+    pub(crate) fn new(shape: Shape) -> Self {
+        Gravity { shape, board: vec![vec![None; shape.cols]; shape.rows], moves: Vec::new(), player: Player::X }
+    }
+
+    /// The size of the board and the run to win
+    pub(crate) fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    /// Return the current player
+    pub(crate) fn current_player(&self) -> Player {
+        self.player
+    }
+
+    /// Return where every mark so far landed, in order
+    pub(crate) fn history(&self) -> &[Coordinate] {
+        &self.moves
+    }
+
+    /// Whose mark is in a space, if anyone's
+    pub(crate) fn get_mark(&self, coord: Coordinate) -> Option<Player> {
+        let (row, col) = coord.into();
+        self.board[row][col]
+    }
+
+    /// The space a mark dropped into a column lands in, unless the column is full
+    pub(crate) fn landing(&self, col: usize) -> Option<Coordinate> {
+        if col >= self.shape.cols { return None; }
+        (0..self.shape.rows).rev().find(|row| self.board[*row][col].is_none()).map(|row| Coordinate::new(row, col))
+    }
+
+    /// Every column with room for another mark, left to right
+    pub(crate) fn open_columns(&self) -> Vec<usize> {
+        (0..self.shape.cols).filter(|col| self.board[0][*col].is_none()).collect()
+    }
+
+    /// Drop the current player's mark into a column. Returns the space it landed in, or
+    /// an error if the column is full or off the board.
+    pub(crate) fn play(&mut self, col: usize) -> Result<Coordinate> {
+        let coord = self.landing(col).ok_or(GameError::ColumnFull)?;
+        let (row, col) = coord.into();
+        self.board[row][col] = Some(self.player);
+        self.moves.push(coord);
+        self.player.toggle();
+        Ok(coord)
+    }
+
+    /// The game after a mark is dropped into a column, which must have room
+    pub(crate) fn after(&self, col: usize) -> Gravity {
+        let mut next = self.clone();
+        next.play(col).expect("Open columns can be played");
+        next
+    }
+
+    /// The winning run, if there is one. The game ends with the first, so it's always
+    /// through the last mark placed.
+    pub(crate) fn line(&self) -> Option<Vec<Coordinate>> {
+        let last = *self.moves.last()?;
+        let owner = self.get_mark(last);
+        self.shape.windows().into_iter()
+            .filter(|window| window.contains(&last))
+            .find(|window| window.iter().all(|coord| self.get_mark(*coord) == owner))
+    }
+
+    /// Determine and return the current status of the game
+    pub(crate) fn status(&self) -> GameStatus {
+        if let Some(line) = self.line() {
+            return GameStatus::Winner(self.get_mark(line[0]).expect("A line is made of marks"));
+        }
+        if self.moves.len() == self.shape.cols * self.shape.rows { return GameStatus::Draw; }
+        GameStatus::Pending(self.player)
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Search
+//--------------------------------------------------------------------------------------
+
+/// Score the game for the player to move, looking `depth` moves ahead with alpha-beta
+/// pruning. Wins score higher the sooner they come.
+fn negamax(gravity: &Gravity, depth: usize, mut alpha: i32, beta: i32) -> i32 {
+    match gravity.status() {
+        GameStatus::Winner(_) => return -(1_000_000 + depth as i32),
+        GameStatus::Draw => return 0,
+        GameStatus::Pending(player) if depth == 0 => {
+            // Runs of only the player's marks count for, and of only the opponent's against
+            let sign = |mark: &Player| if *mark == player { 1 } else { -1 };
+            return lines::openness(&gravity.shape().windows(), |coord| gravity.get_mark(coord), sign);
+        },
+        GameStatus::Pending(_) => {},
+    }
+    let mut best = i32::MIN + 1;
+    for col in by_center(gravity) {
+        let score = -negamax(&gravity.after(col), depth - 1, -beta, -alpha);
+        best = best.max(score);
+        alpha = alpha.max(score);
+        if alpha >= beta { break; }
+    }
+    best
+}
+
+/// The open columns, the middle first, since they're part of the most runs
+fn by_center(gravity: &Gravity) -> Vec<usize> {
+    let mut cols = gravity.open_columns();
+    cols.sort_by_key(|col| lines::from_center(*col, gravity.shape().cols));
+    cols
+}
+
+/// The columns that look best for the player to move, searching a few moves ahead
+pub(crate) fn best_columns(gravity: &Gravity) -> Vec<usize> {
+    let scores: Vec<_> = by_center(gravity).into_iter()
+        .map(|col| (col, -negamax(&gravity.after(col), DEPTH - 1, i32::MIN + 1, i32::MAX)))
+        .collect();
+    let best = scores.iter().map(|(_, score)| *score).max().unwrap_or(0);
+    scores.into_iter().filter(|(_, score)| *score == best).map(|(col, _)| col).collect()
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Drawing
+//--------------------------------------------------------------------------------------
+
+/// The board as the players see it, with the mark about to drop hovering over its
+/// column, or on its way down, and the winning run picked out
+pub(crate) struct View<'a> {
+    gravity: &'a Gravity,
+    hover: Option<usize>,           // The column the cursor is over, if it's a person's turn
+    falling: Option<Coordinate>,    // Where the current player's mark is as it drops
+}
+
+impl<'a> View<'a> {
+    pub(crate) fn new(gravity: &'a Gravity, hover: Option<usize>, falling: Option<Coordinate>) -> Self {
+        View { gravity, hover, falling }
+    }
+}
+
+impl Draw for View<'_> {
+    fn draw(&self, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> crossterm::Result<()> {
+        // The marks hover in a row above the board
+        let board_row = term_row + SMALL_ROW_HEIGHT;
+        let shape = self.gravity.shape();
+        small_grid(shape.rows, shape.cols).draw_with_color(board_row, term_col, theme.grid, theme, glyphs)?;
+        let origin = |row: u16, col: usize| (board_row + row * SMALL_ROW_HEIGHT, term_col + col as u16 * SMALL_COL_WIDTH);
+        let img = |player: Player| if player.mark() == Mark::X { SMALL_X } else { SMALL_O };
+
+        let line = self.gravity.line().unwrap_or_default();
+        for (row, col) in self.gravity.history().iter().map(|coord| coord.get_tuple()) {
+            let player = self.gravity.get_mark(Coordinate::new(row, col)).expect("Marks land where they're played");
+            let color = if line.contains(&Coordinate::new(row, col)) { theme.win } else { theme.player(player) };
+            let (out_row, out_col) = origin(row as u16, col);
+            img(player).draw_with_color(out_row + 1, out_col + 1, color, theme, glyphs)?;
+        }
+
+        let player = self.gravity.current_player();
+        if let Some(coord) = self.falling {
+            let (row, col) = coord.into();
+            let (out_row, out_col) = origin(row as u16, col);
+            img(player).draw_with_color(out_row + 1, out_col + 1, theme.player(player), theme, glyphs)?;
+        }
+        if let Some(col) = self.hover {
+            let (_, out_col) = origin(0, col);
+            img(player).draw_with_color(term_row, out_col + 1, theme.cursor, theme, glyphs)?;
+            if let Some(coord) = self.gravity.landing(col) {
+                let (out_row, out_col) = origin(coord.get_tuple().0 as u16, col);
+                SMALL_CURSOR.draw_with_color(out_row, out_col, theme.cursor, theme, glyphs)?;
+            }
+        }
+        self.gravity.status().draw(term_row, term_col + 104, theme, glyphs)
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Variant
+//--------------------------------------------------------------------------------------
+
+impl Variant for Gravity {
+    type Side = Player;
+    type Move = usize;  // The column to drop a mark into
+    type Search = ();
+
+    const MOVE: &'static str = "column";
+
+    fn rules(&self) -> Rules {
+        Rules::Gravity
+    }
+
+    fn status(&self) -> GameStatus {
+        Gravity::status(self)
+    }
+
+    fn turn(&self) -> Player {
+        self.current_player()
+    }
+
+    fn moves(&self) -> usize {
+        self.history().len()
+    }
+
+    fn play(&mut self, col: usize) -> Result<()> {
+        Gravity::play(self, col).map(|_| ())
+    }
+
+    fn choose(&self, ai: &mut Ai) -> usize {
+        ai.choose_gravity_move(self)
+    }
+
+    /// Says where the mark landed, so the game must be as it was just after
+    fn describe_move(&self, player: Player, _col: usize) -> String {
+        let landed = *self.history().last().expect("A move was just made");
+        describe::drop(player, landed)
+    }
+
+    fn details(&self) -> Option<String> {
+        Some(describe::shape(self.shape))
+    }
+
+    fn instructions(&self) -> &'static str {
+        plain::GRAVITY_INSTRUCTIONS
+    }
+
+    fn grid(&self) -> Vec<String> {
+        plain::gravity_grid(self)
+    }
+
+    fn parse(&self, line: &str) -> Option<usize> {
+        plain::parse_column(line, self.shape.cols)
+    }
+
+    fn draw(&self, cursor: Option<&Cursor>, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> crossterm::Result<()> {
+        let hover = cursor.map(|cursor| cursor.get_coordinate().get_tuple().1);
+        View::new(self, hover, None).draw(term_row, term_col, theme, glyphs)
+    }
+
+    /// The cursor only picks a column, and stays on it from turn to turn
+    fn cursor(&self, previous: Option<Cursor>) -> Cursor {
+        previous.unwrap_or_else(|| Cursor::new(Mark::X, 0, self.shape.cols / 2))
+    }
+
+    fn act(&self, cursor: &mut Cursor, action: Action) -> Option<usize> {
+        match action {
+            Action::Shift(direction @ Direction::Left) | Action::Shift(direction @ Direction::Right) => {
+                cursor.shift_on(direction, self.shape.cols);
+                None
+            },
+            Action::Place => Some(cursor.get_coordinate().get_tuple().1),
+            _ => None,
+        }
+    }
+
+    fn prompt(&self, _cursor: &Cursor) -> Option<String> {
+        Some(describe::shape(self.shape))
+    }
+
+    fn help(&self) -> Option<&'static str> {
+        Some("Left and right pick a column, Enter drops your mark into it.")
+    }
+
+    /// The mark falls from the top of the board to the space it lands in
+    fn animate(&self, col: usize, frame: &mut dyn FnMut(&dyn Draw) -> crossterm::Result<()>) -> crossterm::Result<()> {
+        let landing = match self.landing(col) {
+            Some(coord) => coord,
+            None => return Ok(()),
+        };
+        for row in 0..=landing.get_tuple().0 {
+            frame(&View::new(self, None, Some(Coordinate::new(row, col))))?;
+        }
+        Ok(())
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Tests
+//--------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variant::play;
+
+    #[test]
+    fn test_drop() {
+        let mut gravity = Gravity::new(Shape { cols: 3, rows: 3, run: 3 });
+        play(&mut gravity, &[1, 1]);
+        assert_eq!(gravity.get_mark((2, 1).into()), Some(Player::X));
+        assert_eq!(gravity.get_mark((1, 1).into()), Some(Player::O));
+        assert_eq!(gravity.play(1), Ok(Coordinate::new(0, 1)));
+        assert_eq!(gravity.play(1), Err(GameError::ColumnFull));
+        assert_eq!(gravity.play(3), Err(GameError::ColumnFull));
+        assert_eq!(gravity.open_columns(), vec![0, 2]);
+    }
+
+    #[test]
+    fn test_diagonal() {
+        // X climbs a staircase from the bottom left
+        let mut gravity = Gravity::new(Shape::default());
+        play(&mut gravity, &[0, 1, 1, 2, 2, 3, 2, 3, 3, 6]);
+        assert_eq!(gravity.status(), GameStatus::Pending(Player::X));
+        play(&mut gravity, &[3]);
+        assert_eq!(gravity.status(), GameStatus::Winner(Player::X));
+        let line: Vec<Coordinate> = vec![(2, 3).into(), (3, 2).into(), (4, 1).into(), (5, 0).into()];
+        assert_eq!(gravity.line(), Some(line));
+    }
+
+    #[test]
+    fn test_draw() {
+        // X O X along the bottom and O X O on top, with no room for a diagonal
+        let mut gravity = Gravity::new(Shape { cols: 3, rows: 2, run: 3 });
+        play(&mut gravity, &[0, 1, 1, 0, 2, 2]);
+        assert_eq!(gravity.status(), GameStatus::Draw);
+    }
+
+    #[test]
+    fn test_search() {
+        // O has three across the bottom, and X must block the open end
+        let mut gravity = Gravity::new(Shape::default());
+        play(&mut gravity, &[6, 2, 6, 3, 5, 4]);
+        assert_eq!(best_columns(&gravity), vec![1]);
+        assert_eq!(Shape::default().windows().len(), 69);
+        assert_eq!(Shape::parse_size("9x8"), Some((9, 8)));
+        assert_eq!(Shape::parse_size("10x6"), None);
+    }
+}
@@ -0,0 +1,90 @@
+//! Translates key presses into game actions, so that every way of showing the game
+//! responds to the same keys. Text, like a new player's name, is typed as edits.
+
+use crate::cursor::Direction;
+use crate::game::Mark;
+
+use crossterm::Result;
+use crossterm::event::{read, Event, KeyCode};
+
+/// The things a player can ask for from the keyboard
+#[derive(Debug, PartialEq)]
+pub(crate) enum Action {
+    Shift(Direction),  // Move the cursor one space
+    Place,             // Mark the space under the cursor
+    ToggleMark,        // Switch the mark to place, when the rules allow a choice
+    ChooseMark(Mark),  // Pick the mark to place: X or O, or a number under numerical rules
+    NextBoard,         // Move the cursor to the next board, when there's more than one
+    PreviousBoard,     // Move the cursor to the previous board
+    ReadBoard,         // Describe the whole board (only meaningful in text modes)
+    Hint,              // Suggest the best move
+    Analyze,           // Show (or hide) the outcome of every available move
+    Leaderboard,       // Show the players' ratings (only on the setup screen)
+    Quit,              // Leave the game
+}
+
+impl Action {
+    /// Return the action bound to a terminal event, if there is one
+    pub(crate) fn from_event(event: Event) -> Option<Self> {
+        let code = match event {
+            Event::Key(key) => key.code,
+            _ => return None,
+        };
+        match code {
+            KeyCode::Esc => Some(Action::Quit),
+            KeyCode::Char('h') | KeyCode::Left  => Some(Action::Shift(Direction::Left)),
+            KeyCode::Char('k') | KeyCode::Up    => Some(Action::Shift(Direction::Up)),
+            KeyCode::Char('j') | KeyCode::Down  => Some(Action::Shift(Direction::Down)),
+            KeyCode::Char('l') | KeyCode::Right => Some(Action::Shift(Direction::Right)),
+            KeyCode::Char('r') => Some(Action::ReadBoard),
+            KeyCode::Char('?') => Some(Action::Hint),
+            KeyCode::Char('a') => Some(Action::Analyze),
+            KeyCode::Char('s') => Some(Action::Leaderboard),
+            KeyCode::Char('m') | KeyCode::Tab => Some(Action::ToggleMark),
+            KeyCode::Char('x') => Some(Action::ChooseMark(Mark::X)),
+            KeyCode::Char('o') => Some(Action::ChooseMark(Mark::O)),
+            KeyCode::Char(c @ '1'..='9') => Some(Action::ChooseMark(Mark::Number(c as u8 - b'0'))),
+            KeyCode::Char(']') | KeyCode::Char('b') => Some(Action::NextBoard),
+            KeyCode::Char('[') => Some(Action::PreviousBoard),
+            KeyCode::Enter => Some(Action::Place),
+            _ => None,
+        }
+    }
+}
+
+/// Block until the next terminal event and return the action bound to it, if any
+pub(crate) fn read_action() -> Result<Option<Action>> {
+    Ok(Action::from_event(read()?))
+}
+
+/// The changes a key press can make to a line of text being typed
+#[derive(Debug, PartialEq)]
+pub(crate) enum Edit {
+    Type(char),  // Add a character to the end
+    Erase,       // Remove the last character
+    Done,        // Finish typing
+    Cancel,      // Give up on the text
+}
+
+impl Edit {
+    /// Return the edit bound to a terminal event, if there is one. Control characters,
+    /// tabs included, can't be typed.
+    pub(crate) fn from_event(event: Event) -> Option<Self> {
+        let code = match event {
+            Event::Key(key) => key.code,
+            _ => return None,
+        };
+        match code {
+            KeyCode::Esc => Some(Edit::Cancel),
+            KeyCode::Enter => Some(Edit::Done),
+            KeyCode::Backspace => Some(Edit::Erase),
+            KeyCode::Char(c) if !c.is_control() => Some(Edit::Type(c)),
+            _ => None,
+        }
+    }
+}
+
+/// Block until the next terminal event and return the edit bound to it, if any
+pub(crate) fn read_edit() -> Result<Option<Edit>> {
+    Ok(Edit::from_event(read()?))
+}
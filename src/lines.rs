@@ -0,0 +1,96 @@
+//! Runs of spaces in a line, for the games won with some number of marks in a row on a
+//! board of any shape, and a rough score of a board by those runs for the computer
+//! players that can't search to the end of the game.
+
+use crate::game::Coordinate;
+
+
+//--------------------------------------------------------------------------------------
+//-- Windows
+//--------------------------------------------------------------------------------------
+
+/// Every run of `run` spaces in a line on a board `rows` high and `cols` across:
+/// across, down, and along both diagonals
+pub(crate) fn windows(rows: usize, cols: usize, run: usize) -> Vec<Vec<Coordinate>> {
+    let directions: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+    let on_board = |row: isize, col: isize| (0..rows as isize).contains(&row) && (0..cols as isize).contains(&col);
+    let mut windows = Vec::new();
+    for (down, across) in directions {
+        for row in 0..rows as isize {
+            for col in 0..cols as isize {
+                let spaces: Option<Vec<_>> = (0..run as isize)
+                    .map(|step| {
+                        let (row, col) = (row + down * step, col + across * step);
+                        on_board(row, col).then(|| Coordinate::new(row as usize, col as usize))
+                    })
+                    .collect();
+                windows.extend(spaces);
+            }
+        }
+    }
+    windows
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Scoring
+//--------------------------------------------------------------------------------------
+
+/// How promising the board looks: each run holding marks of only one kind counts for
+/// more the fuller it is, for or against depending on the `sign` of that kind of mark.
+/// Empty runs, and runs with different marks in, count for nothing.
+pub(crate) fn openness<M, F, S>(windows: &[Vec<Coordinate>], mark: F, sign: S) -> i32
+where
+    M: PartialEq,
+    F: Fn(Coordinate) -> Option<M>,
+    S: Fn(&M) -> i32,
+{
+    windows.iter()
+        .map(|window| {
+            let marks: Vec<_> = window.iter().filter_map(|coord| mark(*coord)).collect();
+            match marks.first() {
+                None => 0,
+                Some(first) if marks.iter().any(|mark| mark != first) => 0,
+                Some(first) => sign(first) * 4_i32.pow(marks.len() as u32),
+            }
+        })
+        .sum()
+}
+
+/// How far a row or column is from the middle of a board `len` spaces across, in
+/// half spaces, for trying the spaces that are part of the most runs first
+pub(crate) fn from_center(idx: usize, len: usize) -> usize {
+    (2 * idx as isize - (len as isize - 1)).unsigned_abs()
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Tests
+//--------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows() {
+        // Two runs of three along each row and column and four along the diagonals on
+        // 4x4, and 69 runs of four on the 7x6 Connect Four board
+        assert_eq!(windows(4, 4, 3).len(), 2 * 4 * 2 + 2 * 4);
+        assert_eq!(windows(6, 7, 4).len(), 69);
+        assert!(windows(4, 4, 3).contains(&[(1, 3), (2, 2), (3, 1)].map(Coordinate::from).to_vec()));
+        assert_eq!(windows(2, 3, 3).len(), 2);
+    }
+
+    #[test]
+    fn test_openness() {
+        // Runs with two Xs and with one count for, one with an O against, and one with
+        // both for nothing
+        let windows = windows(1, 5, 2);
+        let marks = [Some('X'), Some('X'), None, Some('O'), Some('X')];
+        let score = openness(&windows, |coord| marks[coord.get_tuple().1], |mark| if *mark == 'X' { 1 } else { -1 });
+        assert_eq!(score, 16 + 4 - 4);
+        assert_eq!(from_center(0, 5), 4);
+        assert_eq!(from_center(3, 6), 1);
+    }
+}
@@ -1,61 +1,186 @@
+mod accessible;
+mod ai;
+mod analysis;
+mod bot;
+mod cube;
 mod cursor;
+mod describe;
 mod display;
+mod enumerate;
 mod error;
 mod game;
+mod gravity;
+mod input;
+mod lines;
+mod notakto;
+mod options;
+mod order;
+mod plain;
+mod players;
+mod profile;
+mod quantum;
+mod rating;
+mod review;
+mod setup;
+mod solver;
+mod storage;
+mod symmetry;
+mod theme;
+mod three;
+mod tournament;
+mod tui;
+mod variant;
 
-use crate::cursor::{Cursor, Direction};
-use crate::display::Draw;
-use crate::game::{Game, GameStatus};
-
-use crossterm::{execute, Result};
-use crossterm::cursor::MoveDown;
-use crossterm::event::{read, Event, KeyCode};
-use crossterm::style::Print;
-use crossterm::terminal::{enable_raw_mode, disable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, Clear, ClearType};
-
-const TOP: u16 = 10;
-const LEFT: u16 = 10;
-
-fn main() -> Result<()> {
-    // Setup
-    let mut stdout = std::io::stdout();
-    let mut game = Game::new();
-    enable_raw_mode()?;
-
-    // Draw the game in an alternate screen
-    execute!(stdout, EnterAlternateScreen)?;
-    'game: while let GameStatus::Pending(_) = game.status() {
-
-        // While the game status is pending, there must be at least one available space
-        let mut gc = Cursor::first_available(&game).unwrap();
-        'control: loop {
-            execute!(stdout, Clear(ClearType::All))?;
-            gc.draw(TOP, LEFT)?;
-            game.draw(TOP, LEFT)?;
-            if let Event::Key(event) = read()? {
-                match event.code {
-                    KeyCode::Esc => break 'game,
-                    KeyCode::Char('h') | KeyCode::Left  => gc.shift(Direction::Left),
-                    KeyCode::Char('k') | KeyCode::Up    => gc.shift(Direction::Up),
-                    KeyCode::Char('j') | KeyCode::Down  => gc.shift(Direction::Down),
-                    KeyCode::Char('l') | KeyCode::Right => gc.shift(Direction::Right),
-                    KeyCode::Enter => {
-                        if let Err(e) = game.add_move(gc.get_coordinate()) {
-                            println!("{}, please try again!", e);
-                        }
-                        continue 'game;
-                    },
-                    _ => continue 'control,
-                }
+use crate::options::{Command, Mode, Options, USAGE};
+use crate::cube::Cube;
+use crate::enumerate::Tree;
+use crate::game::{Coordinate, Game, Player, Rules};
+use crate::gravity::Gravity;
+use crate::ai::Rng;
+use crate::notakto::Notakto;
+use crate::order::OrderChaos;
+use crate::players::{PlayerSpec, Players, Seat};
+use crate::profile::Profiles;
+use crate::quantum::Quantum;
+use crate::rating::Ratings;
+use crate::three::Three;
+use crate::variant::Variant;
+
+fn main() {
+    let command = match Command::from_env() {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        },
+    };
+
+    let result = match command {
+        Command::Help => { print!("{}", USAGE); Ok(()) },
+        Command::Play(options) => play(&options),
+        Command::Tournament(options) => tournament::run(&options),
+        Command::Leaderboard => Ratings::load().and_then(|ratings| ratings.print(&mut std::io::stdout())),
+        Command::Profiles => Profiles::load().and_then(|profiles| profiles.print(&mut std::io::stdout())),
+        Command::Enumerate(rules, moves) => enumerate(rules, &moves),
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Play a single game, shown as the options ask, then update the players' profiles
+/// and ratings
+fn play(options: &Options) -> std::io::Result<()> {
+    let rules = options.rules.unwrap_or_default();
+    let (x, o, rules) = if options.uses_setup() {
+        match setup::choose(options, &options.theme, options.glyphs)? {
+            Some(choices) => choices,
+            None => return Ok(()),
+        }
+    } else {
+        let or_guest = |spec: &Option<PlayerSpec>| spec.as_ref().map_or(PlayerSpec::Human(None), |spec| spec.for_rules(rules));
+        (or_guest(&options.x), or_guest(&options.o), rules)
+    };
+    let mut players = Players::from_specs(&x, &o)?;
+    match rules {
+        Rules::Notakto if options.boards > 1 => play_variant(options, &mut players, Notakto::new(options.boards)),
+        Rules::Quantum => play_variant(options, &mut players, Quantum::new()),
+        Rules::Order => play_variant(options, &mut players, OrderChaos::new()),
+        Rules::Gravity => play_variant(options, &mut players, Gravity::new(options.shape())),
+        Rules::Cube | Rules::Qubic => play_variant(options, &mut players, Cube::with_rules(rules)),
+        Rules::Three => play_three(options, players),
+        _ => play_classical(options, &mut players, rules),
+    }
+}
+
+/// Play a game on the usual 3x3 board, then update the players' profiles and ratings
+fn play_classical(options: &Options, players: &mut Players, rules: Rules) -> std::io::Result<()> {
+    let game = match options.mode {
+        Mode::Fullscreen => tui::play(players, rules, options.wrap, &options.theme, options.glyphs)?,
+        Mode::Accessible => accessible::play(players, rules, options.wrap)?,
+        Mode::Plain => plain::play(players, rules, options.wrap)?,
+    };
+    record(options, players, rules, rating::score(&game.status()), |profiles, name, mark, opponent| {
+        profiles.record_game(name, &game, mark, opponent)
+    })
+}
+
+/// Play a two-player variant with rules of its own, then update the players' profiles
+/// and ratings
+fn play_variant<V: Variant<Side = Player>>(options: &Options, players: &mut Players, game: V) -> std::io::Result<()> {
+    let game = show_variant(options, players, game)?;
+    let (rules, status, moves) = (game.rules(), game.status(), game.moves());
+    let score = match game.half_points() {
+        Some(half_points) => Some(rating::share(half_points)),
+        None => rating::score(&status),
+    };
+    record(options, players, rules, score, |profiles, name, mark, opponent| {
+        profiles.record_result(name, rules, &status, mark, opponent, moves)
+    })
+}
+
+/// Play a three-player game on the board the options ask for. Profiles and ratings
+/// only compare two players, so the game isn't recorded in either.
+fn play_three(options: &Options, players: Players) -> std::io::Result<()> {
+    let y = options.y.as_ref().map_or(PlayerSpec::Human(None), |spec| spec.for_rules(Rules::Three));
+    let mut players = players.with_third(Seat::from_spec(&y, Rng::from_time())?);
+    show_variant(options, &mut players, Three::new(options.side()))?;
+    Ok(())
+}
+
+/// Play a variant, shown as the options ask. Returns the game as it stood when it
+/// ended or the players quit. The accessible mode only plays on one 3x3 board, so
+/// choosing it is an error, though the options refuse it before it gets here.
+fn show_variant<V: Variant>(options: &Options, players: &mut Players, game: V) -> std::io::Result<V> {
+    match options.mode {
+        Mode::Fullscreen => tui::play_variant(game, players, &options.theme, options.glyphs),
+        Mode::Plain => plain::play_variant(game, players),
+        Mode::Accessible => {
+            let msg = format!("The accessible mode can't play by {} rules", game.rules());
+            Err(std::io::Error::new(std::io::ErrorKind::Unsupported, msg))
+        },
+    }
+}
+
+/// Update the profiles of named humans with `record_profile`, and the ratings of both
+/// players in the pool for `rules`, after a game where X took `score`. Unfinished games
+/// have no score and aren't rated.
+fn record<F>(options: &Options, players: &Players, rules: Rules, score: Option<f64>, record_profile: F) -> std::io::Result<()>
+where
+    F: Fn(&mut Profiles, &str, Player, &str),
+{
+    let sides = [(Player::X, Player::O), (Player::O, Player::X)];
+    if sides.iter().any(|(mark, _)| players.seat(*mark).profile_name().is_some()) {
+        let mut profiles = Profiles::load()?;
+        for (mark, opponent) in sides {
+            if let Some(name) = players.seat(mark).profile_name() {
+                record_profile(&mut profiles, &name, mark, &players.seat(opponent).name());
             }
         }
+        profiles.save()?;
     }
 
-    // Cleanup
-    disable_raw_mode()?;
-    execute!(stdout, LeaveAlternateScreen)?;
-    execute!(stdout, Clear(ClearType::All))?;
-    game.draw(TOP, LEFT)?;
-    execute!(stdout, MoveDown(40), Print("\n"))?;
+    // A player can't be rated against itself
+    match (options.rated, players.rating_keys(), score) {
+        (true, Some(_), Some(_)) if !rating::is_rated(rules) => println!("Not rated: games by {} rules aren't rated.", rules),
+        (true, Some((x, o)), Some(_)) if x == o => println!("Not rated: {} played both sides.", x),
+        (true, Some((x, o)), Some(score)) => {
+            let mut ratings = Ratings::load()?;
+            ratings.record_score(&rating::pool_key(&x, rules), &rating::pool_key(&o, rules), score);
+            ratings.save()?;
+        },
+        _ => {},
+    }
     Ok(())
 }
+
+/// Count every game that can be played by the rules from the position the moves reach,
+/// and print the counts by depth
+fn enumerate(rules: Rules, moves: &[Coordinate]) -> std::io::Result<()> {
+    let mut game = Game::with_rules(rules);
+    for coord in moves {
+        game.add_move(*coord).expect("Moves were checked when parsing the options");
+    }
+    Tree::enumerate(&game).print(&mut std::io::stdout())
+}
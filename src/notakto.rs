@@ -0,0 +1,370 @@
+//! Notakto: both players place X on a row of boards, a board is dead once it has three
+//! in a row, and whoever kills the last board loses. Each board is a `Game` played by
+//! notakto rules, so lines are found just as they are on a single board.
+
+use crate::ai::Ai;
+use crate::cursor::Cursor;
+use crate::describe;
+use crate::display::{Draw, DrawWithColor, Glyphs, CharMatrix};
+use crate::error::{GameError, Result};
+use crate::game::{Coordinate, Game, GameStatus, Player, Rules};
+use crate::input::Action;
+use crate::plain;
+use crate::symmetry::canonicalize;
+use crate::theme::Theme;
+use crate::variant::Variant;
+
+use std::collections::HashMap;
+
+/// The most boards a game can be played on. The search for perfect play gets slow
+/// beyond this.
+pub(crate) const MAX_BOARDS: usize = 3;
+
+
+//--------------------------------------------------------------------------------------
+//-- Notakto
+//--------------------------------------------------------------------------------------
+
+/// A single move: the board and the space on it to mark
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub(crate) struct BoardMove {
+    pub(crate) board: usize,
+    pub(crate) coord: Coordinate,
+}
+
+impl BoardMove {
+    pub(crate) fn new(board: usize, coord: Coordinate) -> Self {
+        BoardMove { board, coord }
+    }
+}
+
+/// A game of notakto, on any number of boards
+#[derive(Debug, Clone)]
+pub(crate) struct Notakto {
+    boards: Vec<Game>,       // Every board, dead or alive
+    player: Player,          // The current player, Player::X or Player::O
+    moves: Vec<BoardMove>,   // Every move made so far, in order
+}
+
+impl Notakto {
+    /// Create a new game on `boards` empty boards
+    pub(crate) fn new(boards: usize) -> Self {
+        let boards = (0..boards).map(|_| Game::with_rules(Rules::Notakto)).collect();
+        Notakto { boards, player: Player::X, moves: Vec::new() }
+    }
+
+    /// Return every board, in order
+    pub(crate) fn boards(&self) -> &[Game] {
+        &self.boards
+    }
+
+    /// Return the current player
+    pub(crate) fn current_player(&self) -> Player {
+        self.player
+    }
+
+    /// Return every move made so far, in order. X made the moves at even indices.
+    pub(crate) fn history(&self) -> &[BoardMove] {
+        &self.moves
+    }
+
+    /// Is the board out of play, with three in a row on it?
+    pub(crate) fn is_dead(&self, board: usize) -> bool {
+        self.boards[board].get_line_mark().is_some()
+    }
+
+    /// Count the boards still in play
+    pub(crate) fn live_boards(&self) -> usize {
+        (0..self.boards.len()).filter(|board| !self.is_dead(*board)).count()
+    }
+
+    /// Mark a space on one of the boards. Returns an error if the board is dead or
+    /// the space is currently occupied.
+    pub(crate) fn play(&mut self, mv: BoardMove) -> Result<()> {
+        if self.is_dead(mv.board) { return Err(GameError::DeadBoard); }
+        self.boards[mv.board].add_move(mv.coord)?;
+        self.moves.push(mv);
+        self.player.toggle();
+        Ok(())
+    }
+
+    /// Return every move the current player may make: each unoccupied space on each
+    /// live board, board by board
+    pub(crate) fn legal_moves(&self) -> Vec<BoardMove> {
+        (0..self.boards.len())
+            .filter(|board| !self.is_dead(*board))
+            .flat_map(|board| {
+                self.boards[board].available_moves().into_iter().map(move |coord| BoardMove::new(board, coord))
+            })
+            .collect()
+    }
+
+    /// The game after a move, which must be legal
+    pub(crate) fn after(&self, mv: BoardMove) -> Notakto {
+        let mut next = self.clone();
+        next.play(mv).expect("Legal moves can be played");
+        next
+    }
+
+    /// A key shared by every position with the same boards in play, up to symmetry and
+    /// in any order. Dead boards can't change the game, so they're left out.
+    fn key(&self) -> Vec<u32> {
+        let mut key: Vec<_> = (0..self.boards.len())
+            .filter(|board| !self.is_dead(*board))
+            .map(|board| canonicalize(&self.boards[board]).key)
+            .collect();
+        key.sort_unstable();
+        key
+    }
+
+    /// Determine and return the current status of the game. There are no draws: once
+    /// the last board dies, the player who killed it has lost.
+    pub(crate) fn status(&self) -> GameStatus {
+        if self.live_boards() == 0 { return GameStatus::Winner(self.player); }
+        GameStatus::Pending(self.player)
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Search
+//--------------------------------------------------------------------------------------
+
+/// A search for perfect play that remembers every position it has solved. Every game
+/// ends with a winner, so each position is simply won or lost for the player to move.
+#[derive(Default)]
+pub(crate) struct Search {
+    memo: HashMap<Vec<u32>, bool>,  // Solved positions, keyed by `Notakto::key`
+}
+
+impl Search {
+    pub(crate) fn new() -> Self {
+        Search::default()
+    }
+
+    /// Does the player to move win with perfect play?
+    pub(crate) fn wins(&mut self, notakto: &Notakto) -> bool {
+        if notakto.live_boards() == 0 { return true; }
+        let key = notakto.key();
+        if let Some(wins) = self.memo.get(&key) { return *wins; }
+
+        let wins = notakto.legal_moves().into_iter().any(|mv| !self.wins(&notakto.after(mv)));
+        self.memo.insert(key, wins);
+        wins
+    }
+
+    /// Every move that wins for the player to move. Empty if every move loses.
+    pub(crate) fn winning_moves(&mut self, notakto: &Notakto) -> Vec<BoardMove> {
+        notakto.legal_moves()
+            .into_iter()
+            .filter(|mv| !self.wins(&notakto.after(*mv)))
+            .collect()
+    }
+
+    /// Suggest a move for the player to move, and whether it wins. When nothing wins,
+    /// suggests a move that doesn't lose right away, if there is one. Asking again
+    /// about the same position cycles through every suggestion in turn, starting after
+    /// `previous`.
+    pub(crate) fn hint(&mut self, notakto: &Notakto, previous: Option<BoardMove>) -> Option<(BoardMove, bool)> {
+        let winning = self.winning_moves(notakto);
+        let wins = !winning.is_empty();
+        let moves = if wins { winning } else { safe_moves(notakto) };
+        let next = previous
+            .and_then(|prev| moves.iter().position(|mv| *mv == prev))
+            .map(|idx| (idx + 1) % moves.len())
+            .unwrap_or(0);
+        Some((*moves.get(next)?, wins))
+    }
+}
+
+/// Every move that doesn't kill the last board, or every move if they all do
+pub(crate) fn safe_moves(notakto: &Notakto) -> Vec<BoardMove> {
+    let all = notakto.legal_moves();
+    let safe: Vec<_> = all.iter()
+        .copied()
+        .filter(|mv| notakto.after(*mv).live_boards() > 0)
+        .collect();
+    if safe.is_empty() { all } else { safe }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Drawing
+//--------------------------------------------------------------------------------------
+
+/// The width of each small board in the row of every board, including the gap after it
+const SMALL_WIDTH: u16 = 18;
+
+/// The boards as the players see them: one board drawn full size, and every board
+/// drawn small in a row under the status message
+pub(crate) struct View<'a> {
+    notakto: &'a Notakto,
+    board: usize,  // The board drawn full size
+}
+
+impl<'a> View<'a> {
+    pub(crate) fn new(notakto: &'a Notakto, board: usize) -> Self {
+        View { notakto, board }
+    }
+}
+
+impl Draw for View<'_> {
+    fn draw(&self, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> crossterm::Result<()> {
+        // Dead boards are drawn all in one color, like the 'Try Again' banner
+        let dead = Theme { x: theme.draw, o: theme.draw, grid: theme.draw, ..*theme };
+        let big_theme = if self.notakto.is_dead(self.board) { &dead } else { theme };
+        self.notakto.boards[self.board].draw_board(term_row, term_col, big_theme, glyphs)?;
+        self.notakto.status().draw(term_row, term_col + 104, theme, glyphs)?;
+
+        for (idx, board) in self.notakto.boards.iter().enumerate() {
+            let label = match self.notakto.is_dead(idx) {
+                true => format!("Board {} (dead)", idx + 1),
+                false => format!("Board {}", idx + 1),
+            };
+            let color = match (idx == self.board, self.notakto.is_dead(idx)) {
+                (true, _) => theme.cursor,
+                (false, true) => theme.draw,
+                (false, false) => theme.label,
+            };
+            let text = std::iter::once(label).chain(plain::grid(board)).collect::<Vec<_>>().join("\n");
+            let out_col = term_col + 104 + idx as u16 * SMALL_WIDTH;
+            CharMatrix::from(text.as_str()).draw_with_color(term_row + 21, out_col, color, theme, glyphs)?;
+        }
+        Ok(())
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Variant
+//--------------------------------------------------------------------------------------
+
+impl Variant for Notakto {
+    type Side = Player;
+    type Move = BoardMove;
+    type Search = Search;
+
+    fn rules(&self) -> Rules {
+        Rules::Notakto
+    }
+
+    fn status(&self) -> GameStatus {
+        Notakto::status(self)
+    }
+
+    fn turn(&self) -> Player {
+        self.current_player()
+    }
+
+    fn moves(&self) -> usize {
+        self.history().len()
+    }
+
+    fn play(&mut self, mv: BoardMove) -> Result<()> {
+        Notakto::play(self, mv)
+    }
+
+    fn choose(&self, ai: &mut Ai) -> BoardMove {
+        ai.choose_board_move(self)
+    }
+
+    fn hint(&self, search: &mut Search, previous: Option<BoardMove>) -> Option<(BoardMove, String)> {
+        let (mv, wins) = search.hint(self, previous)?;
+        Some((mv, describe::board_hint(self.current_player(), mv, wins)))
+    }
+
+    fn describe_move(&self, player: Player, mv: BoardMove) -> String {
+        describe::board_mark(player, mv)
+    }
+
+    fn instructions(&self) -> &'static str {
+        plain::NOTAKTO_INSTRUCTIONS
+    }
+
+    fn grid(&self) -> Vec<String> {
+        plain::boards(self)
+    }
+
+    fn parse(&self, line: &str) -> Option<BoardMove> {
+        plain::parse_board_move(line, self.boards.len())
+    }
+
+    /// The board under the cursor is drawn full size, or the board last moved on when
+    /// nobody is choosing a move
+    fn draw(&self, cursor: Option<&Cursor>, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> crossterm::Result<()> {
+        let board = match cursor {
+            Some(cursor) => {
+                if !self.is_dead(cursor.get_board()) { cursor.draw(term_row, term_col, theme, glyphs)?; }
+                cursor.get_board()
+            },
+            None => self.history().last().map_or(0, |mv| mv.board),
+        };
+        View::new(self, board).draw(term_row, term_col, theme, glyphs)
+    }
+
+    /// While the game is pending, there must be a live board with an empty space
+    fn cursor(&self, _previous: Option<Cursor>) -> Cursor {
+        Cursor::first_available_board(self).unwrap()
+    }
+
+    fn point(&self, cursor: &mut Cursor, mv: BoardMove) {
+        cursor.move_to_board(mv);
+    }
+
+    fn act(&self, cursor: &mut Cursor, action: Action) -> Option<BoardMove> {
+        match action {
+            Action::Shift(direction) => cursor.shift(direction),
+            Action::NextBoard => cursor.shift_board(true, self.boards.len()),
+            Action::PreviousBoard => cursor.shift_board(false, self.boards.len()),
+            Action::Place => return Some(cursor.get_board_move()),
+            _ => {},
+        }
+        None
+    }
+
+    fn prompt(&self, _cursor: &Cursor) -> Option<String> {
+        None
+    }
+
+    fn help(&self) -> Option<&'static str> {
+        Some("[ and ] switch boards, ? for a hint.")
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Tests
+//--------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variant::play;
+
+    /// A move on a board, by its row and column
+    fn on(board: usize, row: usize, col: usize) -> BoardMove {
+        BoardMove::new(board, (row, col).into())
+    }
+
+    #[test]
+    fn test_killing_the_last_board_loses() {
+        // X kills board 1, then O is left to kill board 2 and lose
+        let mut notakto = Notakto::new(2);
+        play(&mut notakto, &[on(0, 0, 0), on(0, 0, 1), on(0, 0, 2)]);
+        assert!(notakto.is_dead(0));
+        assert_eq!(notakto.play(on(0, 1, 1)), Err(GameError::DeadBoard));
+        play(&mut notakto, &[on(1, 0, 0), on(1, 1, 1), on(1, 2, 2)]);
+        assert_eq!(notakto.status(), GameStatus::Winner(Player::X));
+    }
+
+    #[test]
+    fn test_perfect_play() {
+        // The first player wins on one board by taking the center, and loses on two
+        let mut search = Search::new();
+        let one = Notakto::new(1);
+        assert!(search.wins(&one));
+        assert_eq!(search.winning_moves(&one), vec![BoardMove::new(0, (1, 1).into())]);
+        assert!(!search.wins(&Notakto::new(2)));
+        assert!(search.wins(&Notakto::new(3)));
+    }
+}
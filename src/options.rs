@@ -0,0 +1,614 @@
+//! Command line options, parsed by hand from `std::env::args()`
+
+use crate::display::Glyphs;
+use crate::error::OptionsError;
+use crate::game::{Coordinate, Game, GameStatus, Rules};
+use crate::gravity::Shape;
+use crate::notakto::MAX_BOARDS;
+use crate::plain;
+use crate::players::PlayerSpec;
+use crate::three;
+use crate::theme::Theme;
+
+
+//--------------------------------------------------------------------------------------
+//-- Usage message
+//--------------------------------------------------------------------------------------
+
+pub(crate) const USAGE: &str = "\
+Usage: tictacterminal [OPTIONS]
+       tictacterminal tournament [--games <N>] [--seed <N>] [--rules <RULES>] [--unrated] <PLAYER> <PLAYER>...
+       tictacterminal leaderboard
+       tictacterminal profiles
+       tictacterminal enumerate [--rules <RULES>] [<MOVE>...]
+
+Options:
+  --x <PLAYER>     Who plays X: human or human:<name>, ai:<level> for the built-in
+                   computer player (random, easy, medium, and perfect on the 3x3
+                   board or hard for the variants played on others), or
+                   bot:<command> to run a bot program
+  --o <PLAYER>     Who plays O, as for --x. Without either, the game starts with a
+                   setup screen to choose the players (or two guests with --plain).
+  --y <PLAYER>     Who plays Y, the third player in three-player games, as for --x
+                   (defaults to a guest)
+  --rules <RULES>  The rules to play by: standard, misere where three in a row
+                   loses, wild where either player may place X or O, notakto
+                   where both place X and three in a row loses, morris where
+                   each player places three marks and then slides them,
+                   infinite where a fourth mark removes the player's oldest,
+                   quantum where each mark goes in two spaces until it collapses,
+                   numerical where X places odd numbers, O even ones, and a
+                   line summing to 15 wins, order, Order and Chaos on a 6x6
+                   board where five in a row of either mark wins for Order,
+                   gravity where marks drop down columns, as in Connect Four, 3d
+                   on a 3x3x3 cube, qubic on a 4x4x4 cube, or three for three
+                   players, X, O and Y, with three in a row on a 4x4 board
+                   (defaults to standard, or the choice on the setup screen)
+  --boards <N>     Play notakto on N boards, from 1 to 3 (defaults to 1)
+  --size <C>x<R>   Play gravity on C columns of R rows, from 3x3 to 9x8
+                   (defaults to 7x6), or three players on 4x4 or 5x5
+  --run <K>        Play gravity with K in a row to win (defaults to 4)
+  --wrap           Lines wrap around the edges of the board, so (1,3), (2,1),
+                   (3,2) is a line too. Not for numerical, or rules played on
+                   other boards
+  --theme <NAME>   Color theme: dark, light, high-contrast, colorblind or none
+                   (defaults to dark, or none if NO_COLOR is set)
+  --glyphs <SET>   Characters to draw with: unicode or ascii
+                   (defaults to ascii if the locale isn't UTF-8)
+  --ascii          Same as --glyphs ascii
+  --unrated        Don't update the players' ratings after the game
+  --accessible     Screen reader friendly mode: announce the board, cursor and
+                   moves as lines of text instead of drawing them (one 3x3
+                   board only, use --plain for the other variants)
+  --plain          Print the board as text and read moves as lines from stdin,
+                   without taking over the terminal
+  -h, --help       Print this message
+
+Tournament:
+  Plays every pair of players (ai:<level> or bot:<command>) against each other,
+  alternating who plays X, and prints a table of results.
+  --games <N>      Games played by each pair (defaults to 100)
+  --seed <N>       Seed for the built-in players' random choices
+  --rules <RULES>  The rules every game is played by, any played on the usual
+                   board: not quantum, order, gravity, 3d or qubic
+  --unrated        Don't update the players' ratings
+
+Leaderboard:
+  Prints the rating of every named human, computer player and bot, by kind and
+  name, like human:alice or bot:python3 bot.py. Ratings are updated after every
+  finished game where both sides have a name, unless they're the same player. The
+  setup screen shows the leaderboard too.
+
+Profiles:
+  Prints lifetime statistics for every named human. A profile is created the first
+  time a name is used with human:<name> or as a new profile on the setup screen,
+  and then offered on the setup screen.
+
+Enumerate:
+  Plays out every possible game and prints how many games end in each result and
+  how many distinct positions there are, at each depth. Given moves, as space
+  numbers from 1 to 9 like 5 1, starts from the position they reach instead.
+  --rules <RULES>  The rules the games are played by, any but morris and
+                   infinite, whose games can go on forever, quantum, and
+                   numerical, order, gravity, 3d and qubic, whose games are too
+                   many
+";
+
+
+//--------------------------------------------------------------------------------------
+//-- Options
+//--------------------------------------------------------------------------------------
+
+/// The ways the game can be shown to the players
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum Mode {
+    Fullscreen,  // Big glyphs in the alternate screen
+    Accessible,  // Lines of text, for screen readers
+    Plain,       // Lines of text in and out, no terminal control at all
+}
+
+impl Mode {
+    /// Can games by these rules be shown this way? The accessible mode only plays on
+    /// the usual 3x3 board.
+    pub(crate) fn shows(&self, rules: Rules) -> bool {
+        *self != Mode::Accessible || rules.is_classical()
+    }
+}
+
+/// What the program was asked to do
+#[derive(Debug, PartialEq)]
+pub(crate) enum Command {
+    Play(Options),                  // Play a game
+    Tournament(TournamentOptions),  // Run a headless tournament
+    Leaderboard,                    // Print the ratings of every rated player
+    Profiles,                       // Print every profile's statistics
+    Enumerate(Rules, Vec<Coordinate>), // Count every game from the position these moves reach
+    Help,                           // Print the usage message
+}
+
+impl Command {
+    /// Parse the command from the program arguments, skipping the program name
+    pub(crate) fn from_env() -> Result<Self, OptionsError> {
+        Command::parse(std::env::args().skip(1).collect())
+    }
+
+    /// Parse the command from a list of arguments, not including the program name
+    pub(crate) fn parse(args: Vec<String>) -> Result<Self, OptionsError> {
+        if args.iter().any(|arg| arg == "-h" || arg == "--help") { return Ok(Command::Help); }
+        match args.first().map(String::as_str) {
+            Some("tournament") => Ok(Command::Tournament(TournamentOptions::parse(args.into_iter().skip(1))?)),
+            Some("leaderboard") if args.len() == 1 => Ok(Command::Leaderboard),
+            Some("profiles") if args.len() == 1 => Ok(Command::Profiles),
+            Some("enumerate") => {
+                let (rules, moves) = parse_moves(args.into_iter().skip(1))?;
+                Ok(Command::Enumerate(rules, moves))
+            },
+            Some("leaderboard") | Some("profiles") => Err(OptionsError::UnknownArgument(args[1].clone())),
+            _ => Ok(Command::Play(Options::parse(args)?)),
+        }
+    }
+}
+
+/// Parse the rules to play by, and a list of moves given as space numbers, checking
+/// the moves can all be played, each before the game is over
+fn parse_moves(mut args: impl Iterator<Item = String>) -> Result<(Rules, Vec<Coordinate>), OptionsError> {
+    let mut rules = Rules::Standard;
+    let mut moves = Vec::new();
+    while let Some(arg) = args.next() {
+        if arg == "--rules" {
+            rules = rules_for(&arg, &mut args)?;
+            continue;
+        }
+        match plain::parse_move(&arg) {
+            Some(coord) => moves.push(coord),
+            None if arg.starts_with('-') => return Err(OptionsError::UnknownArgument(arg)),
+            None => return Err(OptionsError::InvalidValue("enumerate".into(), arg)),
+        }
+    }
+    if !rules.is_classical() {
+        return Err(OptionsError::Usage(format!("Games by {} rules can't be enumerated", rules)));
+    }
+    if rules.can_repeat() {
+        return Err(OptionsError::Usage(format!("Games by {} rules can go on forever, so can't be enumerated", rules)));
+    }
+    if rules == Rules::Numerical {
+        return Err(OptionsError::Usage(format!("Games by {} rules are too many to enumerate", rules)));
+    }
+    let mut game = Game::with_rules(rules);
+    for coord in moves.iter() {
+        if !matches!(game.status(), GameStatus::Pending(_)) {
+            return Err(OptionsError::Usage("No moves can follow the end of the game".into()));
+        }
+        game.add_move(*coord).map_err(|_| OptionsError::Usage("Each move must be to an empty space".into()))?;
+    }
+    Ok((rules, moves))
+}
+
+/// Parse the name of a rule variant following an option
+fn rules_for(option: &str, args: &mut impl Iterator<Item = String>) -> Result<Rules, OptionsError> {
+    let name = value_for(option, args)?;
+    Rules::from_name(&name).ok_or_else(|| OptionsError::InvalidValue(option.to_string(), name))
+}
+
+/// Take the value following an option, or complain that it's missing
+fn value_for(option: &str, args: &mut impl Iterator<Item = String>) -> Result<String, OptionsError> {
+    args.next().ok_or_else(|| OptionsError::MissingValue(option.to_string()))
+}
+
+/// Parse a number following an option
+fn number_for<T: std::str::FromStr>(option: &str, args: &mut impl Iterator<Item = String>) -> Result<T, OptionsError> {
+    let value = value_for(option, args)?;
+    value.parse().map_err(|_| OptionsError::InvalidValue(option.to_string(), value))
+}
+
+/// The settings chosen on the command line for playing a game
+#[derive(Debug, PartialEq)]
+pub(crate) struct Options {
+    pub(crate) x: Option<PlayerSpec>,          // Who plays X, if given
+    pub(crate) o: Option<PlayerSpec>,          // Who plays O, if given
+    pub(crate) y: Option<PlayerSpec>,          // Who plays Y, if given, for three players
+    pub(crate) mode: Mode,                     // How the game is shown
+    pub(crate) rules: Option<Rules>,           // The rules to play by, if given
+    pub(crate) boards: usize,                  // The number of boards, for notakto
+    pub(crate) size: Option<(usize, usize)>,   // Columns and rows, for gravity or three players, if given
+    pub(crate) run: Option<usize>,             // How many in a row win, for gravity, if given
+    pub(crate) wrap: bool,                     // Lines wrap around the edges of the board
+    pub(crate) theme: Theme,                   // The colors used to draw the game
+    pub(crate) glyphs: Glyphs,                 // The characters used to draw the game
+    pub(crate) rated: bool,                    // Update the players' ratings after the game
+}
+
+impl Options {
+    /// Parse options from a list of arguments, not including the program name
+    pub(crate) fn parse<I, S>(args: I) -> Result<Self, OptionsError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut args = args.into_iter().map(Into::into);
+        let mut x = None;
+        let mut o = None;
+        let mut y = None;
+        let mut mode = Mode::Fullscreen;
+        let mut rules = None;
+        let mut boards = 1;
+        let mut size = None;
+        let mut run = None;
+        let mut wrap = false;
+        let mut rated = true;
+        let mut theme = None;
+        let mut glyphs = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--x" | "--o" | "--y" => {
+                    let name = value_for(&arg, &mut args)?;
+                    let spec = PlayerSpec::from_name(&name)
+                        .ok_or_else(|| OptionsError::InvalidValue(arg.clone(), name))?;
+                    match arg.as_str() {
+                        "--x" => x = Some(spec),
+                        "--o" => o = Some(spec),
+                        _ => y = Some(spec),
+                    }
+                },
+                "--theme" => {
+                    let name = value_for(&arg, &mut args)?;
+                    let preset = Theme::from_name(&name)
+                        .ok_or_else(|| OptionsError::InvalidValue(arg.clone(), name))?;
+                    theme = Some(preset);
+                },
+                "--glyphs" => {
+                    let name = value_for(&arg, &mut args)?;
+                    let set = Glyphs::from_name(&name)
+                        .ok_or_else(|| OptionsError::InvalidValue(arg.clone(), name))?;
+                    glyphs = Some(set);
+                },
+                "--rules" => rules = Some(rules_for(&arg, &mut args)?),
+                "--boards" => {
+                    let value = value_for(&arg, &mut args)?;
+                    boards = match value.parse() {
+                        Ok(n) if (1..=MAX_BOARDS).contains(&n) => n,
+                        _ => return Err(OptionsError::InvalidValue(arg, value)),
+                    };
+                },
+                "--size" => {
+                    let value = value_for(&arg, &mut args)?;
+                    size = Some(Shape::parse_size(&value).ok_or(OptionsError::InvalidValue(arg, value))?);
+                },
+                "--run" => run = Some(number_for(&arg, &mut args)?),
+                "--wrap" => wrap = true,
+                "--ascii" => glyphs = Some(Glyphs::Ascii),
+                "--accessible" => mode = Mode::Accessible,
+                "--plain" => mode = Mode::Plain,
+                "--unrated" => rated = false,
+                _ => return Err(OptionsError::UnknownArgument(arg)),
+            }
+        }
+
+        // Saying how many boards, who plays Y or the size of the board picks the rules
+        // that use it, unless the rules are given
+        if rules.is_none() {
+            if boards > 1 {
+                rules = Some(Rules::Notakto);
+            } else if y.is_some() {
+                rules = Some(Rules::Three);
+            } else if size.is_some() || run.is_some() {
+                rules = Some(Rules::Gravity);
+            }
+        }
+
+        // The accessible mode only plays on one 3x3 board
+        match rules {
+            Some(rules) if !mode.shows(rules) => {
+                return Err(OptionsError::Usage(format!("Games by {} rules can't be played in accessible mode, try --plain", rules)));
+            },
+            _ if mode == Mode::Accessible && boards > 1 => {
+                return Err(OptionsError::Usage("Notakto on more than one board can't be played in accessible mode, try --plain".into()));
+            },
+            _ => {},
+        }
+
+        let theme = theme.unwrap_or_else(Theme::from_env);
+        let glyphs = glyphs.unwrap_or_else(Glyphs::from_locale);
+        let options = Options { x, o, y, mode, rules, boards, size, run, wrap, theme, glyphs, rated };
+        // The setup screen only offers rules the rest of the options make sense for, so
+        // they only need checking here if it isn't shown, or there are none to offer
+        let offered = Rules::ALL.iter().any(|rules| mode.shows(*rules) && options.check_rules(*rules).is_ok());
+        if !options.uses_setup() || !offered {
+            options.check_rules(options.rules.unwrap_or_default())?;
+        }
+        Ok(options)
+    }
+
+    /// Are the players and rules chosen on the setup screen? They are unless either
+    /// player is given, or the game is played in plain text.
+    pub(crate) fn uses_setup(&self) -> bool {
+        self.x.is_none() && self.o.is_none() && self.mode != Mode::Plain
+    }
+
+    /// Check that the rest of the options make sense for the rules the game is played
+    /// by, once they're known
+    pub(crate) fn check_rules(&self, rules: Rules) -> Result<(), OptionsError> {
+        // Only notakto is played on more than one board, and three players have a third
+        if self.boards > 1 && rules != Rules::Notakto {
+            return Err(OptionsError::Usage("Only notakto is played on more than one board".into()));
+        }
+        if self.y.is_some() && rules != Rules::Three {
+            return Err(OptionsError::Usage("Only three-player games have a player Y".into()));
+        }
+
+        // Only gravity is played on other boards, and three players on bigger square ones
+        match rules {
+            Rules::Three => match (self.size, self.run) {
+                (None, None) => {},
+                (Some((cols, rows)), None) if cols == rows && three::SIZES.contains(&cols) => {},
+                _ => return Err(OptionsError::Usage("Three players play three in a row on a 4x4 or 5x5 board".into())),
+            },
+            Rules::Gravity => {
+                let shape = self.shape();
+                if !shape.fits() {
+                    return Err(OptionsError::Usage(format!("{} in a row doesn't fit on a {} board", shape.run, shape)));
+                }
+            },
+            _ if self.size.is_some() || self.run.is_some() => {
+                return Err(OptionsError::Usage("Only gravity is played with --size and --run".into()));
+            },
+            _ => {},
+        }
+
+        // Lines only wrap around the edges of the usual board
+        if self.wrap && !rules.can_wrap() {
+            return Err(OptionsError::Usage(format!("Lines can't wrap around the edges under {} rules", rules)));
+        }
+        if self.wrap && self.boards > 1 {
+            return Err(OptionsError::Usage("Lines can't wrap around the edges of more than one board".into()));
+        }
+        Ok(())
+    }
+
+    /// The board to play gravity on, and how many in a row win
+    pub(crate) fn shape(&self) -> Shape {
+        let shape = Shape::default();
+        let (cols, rows) = self.size.unwrap_or((shape.cols, shape.rows));
+        Shape { cols, rows, run: self.run.unwrap_or(shape.run) }
+    }
+
+    /// The number of spaces along each side of the board, for three players
+    pub(crate) fn side(&self) -> usize {
+        self.size.map_or(three::DEFAULT_SIZE, |(cols, _)| cols)
+    }
+}
+
+
+/// The settings chosen on the command line for a tournament
+#[derive(Debug, PartialEq)]
+pub(crate) struct TournamentOptions {
+    pub(crate) players: Vec<PlayerSpec>,  // Everyone taking part, never humans
+    pub(crate) games: u32,                // Games played by each pair of players
+    pub(crate) seed: Option<u64>,         // Seed for the built-in players
+    pub(crate) rules: Rules,              // The rules every game is played by
+    pub(crate) rated: bool,               // Update the players' ratings
+}
+
+impl TournamentOptions {
+    /// Parse tournament options from the arguments following `tournament`
+    pub(crate) fn parse<I, S>(args: I) -> Result<Self, OptionsError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut args = args.into_iter().map(Into::into);
+        let mut players = Vec::new();
+        let mut games = 100;
+        let mut seed = None;
+        let mut rules = Rules::Standard;
+        let mut rated = true;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--games" => games = number_for(&arg, &mut args)?,
+                "--seed" => seed = Some(number_for(&arg, &mut args)?),
+                "--rules" => rules = rules_for(&arg, &mut args)?,
+                "--unrated" => rated = false,
+                name => match PlayerSpec::from_name(name) {
+                    Some(PlayerSpec::Human(_)) => {
+                        return Err(OptionsError::Usage("Humans can't play in a tournament".into()));
+                    },
+                    Some(spec) => players.push(spec),
+                    None if name.starts_with('-') => return Err(OptionsError::UnknownArgument(arg)),
+                    None => return Err(OptionsError::InvalidValue("tournament".into(), arg)),
+                },
+            }
+        }
+
+        if !rules.is_classical() {
+            return Err(OptionsError::Usage(format!("Tournaments can't be played by {} rules", rules)));
+        }
+        if players.len() < 2 {
+            return Err(OptionsError::Usage("A tournament needs at least two players".into()));
+        }
+        // Computer players play at a level that can, which may make two of them the same
+        let players: Vec<_> = players.iter().map(|spec| spec.for_rules(rules)).collect();
+
+        // A player can't be rated against itself
+        let repeat = players.iter().enumerate().find(|(idx, spec)| players[..*idx].contains(spec));
+        if let (true, Some((_, spec))) = (rated, repeat) {
+            return Err(OptionsError::Usage(format!("{} can't be rated against itself, play more than once with --unrated", spec)));
+        }
+        Ok(TournamentOptions { players, games, seed, rules, rated })
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Tests
+//--------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::Level;
+
+    #[test]
+    fn test_theme_by_name() {
+        let options = Options::parse(vec!["--theme", "colorblind"]).expect("Failed to parse");
+        assert_eq!(options.theme, Theme::colorblind());
+    }
+
+    #[test]
+    fn test_ascii_flag() {
+        let options = Options::parse(vec!["--ascii", "--theme", "light"]).expect("Failed to parse");
+        assert_eq!(options.glyphs, Glyphs::Ascii);
+        assert_eq!(options.theme, Theme::light());
+    }
+
+    #[test]
+    fn test_bad_theme() {
+        let err = Options::parse(vec!["--theme", "neon"]).expect_err("Expected an error");
+        assert_eq!(err, OptionsError::InvalidValue("--theme".into(), "neon".into()));
+    }
+
+    #[test]
+    fn test_bot_player() {
+        let options = Options::parse(vec!["--o", "bot:python3 bot.py --fast"]).expect("Failed to parse");
+        assert_eq!(options.x, None);
+        assert_eq!(options.o, Some(PlayerSpec::Bot(vec!["python3".into(), "bot.py".into(), "--fast".into()])));
+    }
+
+    #[test]
+    fn test_tournament() {
+        let args = vec!["tournament", "--games", "10", "ai:perfect", "bot:./bot"];
+        let command = Command::parse(args.into_iter().map(String::from).collect()).expect("Failed to parse");
+        let expected = TournamentOptions {
+            players: vec![PlayerSpec::Ai(Level::Perfect), PlayerSpec::Bot(vec!["./bot".into()])],
+            games: 10,
+            seed: None,
+            rules: Rules::Standard,
+            rated: true,
+        };
+        assert_eq!(command, Command::Tournament(expected));
+    }
+
+    #[test]
+    fn test_tournament_needs_players() {
+        let err = TournamentOptions::parse(vec!["ai:easy"]).expect_err("Expected an error");
+        assert_eq!(err, OptionsError::Usage("A tournament needs at least two players".into()));
+        let err = TournamentOptions::parse(vec!["ai:easy", "human"]).expect_err("Expected an error");
+        assert_eq!(err, OptionsError::Usage("Humans can't play in a tournament".into()));
+        let err = TournamentOptions::parse(vec!["--rules", "quantum", "ai:easy", "ai:random"]).expect_err("Expected an error");
+        assert_eq!(err, OptionsError::Usage("Tournaments can't be played by quantum rules".into()));
+        let err = TournamentOptions::parse(vec!["bot:./a --fast", "ai:easy", "bot:./a  --fast"]).expect_err("Expected an error");
+        assert_eq!(err, OptionsError::Usage("bot:./a --fast can't be rated against itself, play more than once with --unrated".into()));
+        assert!(TournamentOptions::parse(vec!["ai:easy", "ai:easy", "--unrated"]).is_ok());
+    }
+
+    #[test]
+    fn test_enumerate() {
+        let parse = |args: &[&str]| Command::parse(args.iter().map(|arg| arg.to_string()).collect());
+        let expected = vec![Coordinate::new(1, 1), Coordinate::new(0, 0)];
+        assert_eq!(parse(&["enumerate", "5", "1"]), Ok(Command::Enumerate(Rules::Standard, expected)));
+        assert_eq!(parse(&["enumerate", "--rules", "misere"]), Ok(Command::Enumerate(Rules::Misere, Vec::new())));
+        assert!(parse(&["enumerate", "5", "5"]).is_err());
+        let err = parse(&["enumerate", "1", "4", "2", "5", "3", "6"]).expect_err("Expected an error");
+        assert_eq!(err, OptionsError::Usage("No moves can follow the end of the game".into()));
+        assert!(parse(&["enumerate", "1", "4", "2", "5", "3"]).is_ok());
+        assert!(parse(&["enumerate", "10"]).is_err());
+        assert!(parse(&["enumerate", "--rules", "morris"]).is_err());
+        assert!(parse(&["enumerate", "--rules", "infinite"]).is_err());
+        assert!(parse(&["enumerate", "--rules", "quantum"]).is_err());
+        assert!(parse(&["enumerate", "--rules", "numerical"]).is_err());
+        assert!(parse(&["enumerate", "--rules", "order"]).is_err());
+        assert!(parse(&["enumerate", "--rules", "gravity"]).is_err());
+        assert!(parse(&["enumerate", "--rules", "qubic"]).is_err());
+    }
+
+    #[test]
+    fn test_boards() {
+        let options = Options::parse(vec!["--boards", "3"]).expect("Failed to parse");
+        assert_eq!((options.rules, options.boards), (Some(Rules::Notakto), 3));
+        let err = Options::parse(vec!["--x", "human", "--rules", "wild", "--boards", "2"]).expect_err("Expected an error");
+        assert_eq!(err, OptionsError::Usage("Only notakto is played on more than one board".into()));
+        let err = Options::parse(vec!["--boards", "9"]).expect_err("Expected an error");
+        assert_eq!(err, OptionsError::InvalidValue("--boards".into(), "9".into()));
+    }
+
+    #[test]
+    fn test_shape() {
+        let options = Options::parse(vec!["--size", "9x8", "--run", "5"]).expect("Failed to parse");
+        assert_eq!((options.rules, options.shape()), (Some(Rules::Gravity), Shape { cols: 9, rows: 8, run: 5 }));
+        let options = Options::parse(vec!["--rules", "gravity"]).expect("Failed to parse");
+        assert_eq!(options.shape(), Shape::default());
+        let err = Options::parse(vec!["--plain", "--size", "3x3", "--run", "4"]).expect_err("Expected an error");
+        assert_eq!(err, OptionsError::Usage("4 in a row doesn't fit on a 3x3 board".into()));
+        let err = Options::parse(vec!["--plain", "--rules", "misere", "--run", "3"]).expect_err("Expected an error");
+        assert_eq!(err, OptionsError::Usage("Only gravity is played with --size and --run".into()));
+        let err = Options::parse(vec!["--size", "10x6"]).expect_err("Expected an error");
+        assert_eq!(err, OptionsError::InvalidValue("--size".into(), "10x6".into()));
+    }
+
+    #[test]
+    fn test_wrap() {
+        let options = Options::parse(vec!["--rules", "misere", "--wrap"]).expect("Failed to parse");
+        assert_eq!((options.rules, options.wrap), (Some(Rules::Misere), true));
+        let err = Options::parse(vec!["--plain", "--rules", "numerical", "--wrap"]).expect_err("Expected an error");
+        assert_eq!(err, OptionsError::Usage("Lines can't wrap around the edges under numerical rules".into()));
+        let err = Options::parse(vec!["--plain", "--wrap", "--boards", "2"]).expect_err("Expected an error");
+        assert_eq!(err, OptionsError::Usage("Lines can't wrap around the edges of more than one board".into()));
+    }
+
+    #[test]
+    fn test_rules_from_the_setup_screen() {
+        // The setup screen can still choose gravity, so it's offered in place of misere
+        let options = Options::parse(vec!["--rules", "misere", "--size", "9x8"]).expect("Failed to parse");
+        assert!(options.uses_setup());
+        assert_eq!(options.check_rules(Rules::Gravity), Ok(()));
+        let err = options.check_rules(Rules::Misere).expect_err("Expected an error");
+        assert_eq!(err, OptionsError::Usage("Only gravity is played with --size and --run".into()));
+
+        // Rules whose lines can't wrap aren't offered with --wrap, and options no rules
+        // make sense for are refused before the setup screen is shown
+        let options = Options::parse(vec!["--wrap"]).expect("Failed to parse");
+        let err = options.check_rules(Rules::Quantum).expect_err("Expected an error");
+        assert_eq!(err, OptionsError::Usage("Lines can't wrap around the edges under quantum rules".into()));
+        let err = Options::parse(vec!["--wrap", "--boards", "2"]).expect_err("Expected an error");
+        assert_eq!(err, OptionsError::Usage("Lines can't wrap around the edges of more than one board".into()));
+    }
+
+    #[test]
+    fn test_accessible() {
+        let options = Options::parse(vec!["--accessible", "--rules", "morris"]).expect("Failed to parse");
+        assert_eq!((options.mode, options.rules), (Mode::Accessible, Some(Rules::Morris)));
+        let err = Options::parse(vec!["--accessible", "--rules", "gravity"]).expect_err("Expected an error");
+        assert_eq!(err, OptionsError::Usage("Games by gravity rules can't be played in accessible mode, try --plain".into()));
+        let err = Options::parse(vec!["--boards", "2", "--accessible"]).expect_err("Expected an error");
+        assert_eq!(err, OptionsError::Usage("Notakto on more than one board can't be played in accessible mode, try --plain".into()));
+    }
+
+    #[test]
+    fn test_three() {
+        let options = Options::parse(vec!["--y", "ai:easy", "--size", "5x5"]).expect("Failed to parse");
+        assert_eq!((options.rules, options.y.clone(), options.side()), (Some(Rules::Three), Some(PlayerSpec::Ai(Level::Easy)), 5));
+        let options = Options::parse(vec!["--rules", "three"]).expect("Failed to parse");
+        assert_eq!((options.y.clone(), options.side()), (None, 4));
+        let err = Options::parse(vec!["--plain", "--rules", "three", "--size", "6x5"]).expect_err("Expected an error");
+        assert_eq!(err, OptionsError::Usage("Three players play three in a row on a 4x4 or 5x5 board".into()));
+        let err = Options::parse(vec!["--plain", "--rules", "wild", "--y", "human"]).expect_err("Expected an error");
+        assert_eq!(err, OptionsError::Usage("Only three-player games have a player Y".into()));
+    }
+
+    #[test]
+    fn test_levels_fit_the_rules() {
+        let options = TournamentOptions::parse(vec!["ai:hard", "ai:easy"]).expect("Failed to parse");
+        assert_eq!(options.players[0], PlayerSpec::Ai(Level::Perfect));
+        let err = TournamentOptions::parse(vec!["ai:hard", "ai:perfect"]).expect_err("Expected an error");
+        assert_eq!(err, OptionsError::Usage("ai:perfect can't be rated against itself, play more than once with --unrated".into()));
+        assert_eq!(PlayerSpec::Ai(Level::Perfect).for_rules(Rules::Qubic), PlayerSpec::Ai(Level::Hard));
+        assert_eq!(PlayerSpec::Ai(Level::Easy).for_rules(Rules::Qubic), PlayerSpec::Ai(Level::Easy));
+    }
+
+    #[test]
+    fn test_missing_theme() {
+        let err = Options::parse(vec!["--theme"]).expect_err("Expected an error");
+        assert_eq!(err, OptionsError::MissingValue("--theme".into()));
+    }
+}
@@ -0,0 +1,369 @@
+//! Order and Chaos: a game on a 6x6 board where both players may place either mark.
+//! The players want different things. Order, who moves first as X, wins by making
+//! five in a row of either mark, and Chaos, who moves second as O, wins by filling
+//! the board without that happening.
+
+use crate::ai::Ai;
+use crate::cursor::Cursor;
+use crate::describe;
+use crate::display::{small_grid, SMALL_CURSOR, SMALL_X, SMALL_O, SMALL_ROW_HEIGHT, SMALL_COL_WIDTH, Draw, DrawWithColor, Glyphs};
+use crate::error::{GameError, Result};
+use crate::game::{Coordinate, GameStatus, Mark, Move, Player, Rules};
+use crate::input::Action;
+use crate::lines;
+use crate::plain;
+use crate::theme::Theme;
+use crate::variant::Variant;
+
+/// The number of spaces along each side of the board
+pub(crate) const SIZE: usize = 6;
+
+/// How many marks in a row Order needs
+const RUN: usize = 5;
+
+
+//--------------------------------------------------------------------------------------
+//-- Roles
+//--------------------------------------------------------------------------------------
+
+/// The two sides and their goals
+/// - Order: Moves first, as X, and wins with five of the same mark in a row.
+/// - Chaos: Moves second, as O, and wins when the board fills up without that.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum Role {
+    Order,
+    Chaos,
+}
+
+impl Role {
+    /// The role a player takes
+    pub(crate) fn of(player: Player) -> Self {
+        match player {
+            Player::X => Role::Order,
+            Player::O => Role::Chaos,
+        }
+    }
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Role::Order => write!(f, "Order"),
+            Role::Chaos => write!(f, "Chaos"),
+        }
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Order and Chaos
+//--------------------------------------------------------------------------------------
+
+/// Every run of five spaces in a line: across, down, and along both diagonals
+pub(crate) fn windows() -> Vec<Vec<Coordinate>> {
+    lines::windows(SIZE, SIZE, RUN)
+}
+
+/// A game of Order and Chaos
+#[derive(Debug, Clone)]
+pub(crate) struct OrderChaos {
+    board: [[Option<Mark>; SIZE]; SIZE],  // The mark in each space, row by row
+    moves: Vec<Move>,                     // Every move so far. Order made the even indices.
+    player: Player,                       // The current player, Player::X (Order) or Player::O (Chaos)
+}
+
+impl OrderChaos {
+    pub(crate) fn new() -> Self {
+        OrderChaos { board: [[None; SIZE]; SIZE], moves: Vec::new(), player: Player::X }
+    }
+
+    /// Return the current player
+    pub(crate) fn current_player(&self) -> Player {
+        self.player
+    }
+
+    /// Return every move made so far, in order
+    pub(crate) fn history(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// The mark in a space, if there is one
+    pub(crate) fn get_mark(&self, coord: Coordinate) -> Option<Mark> {
+        let (row, col) = coord.into();
+        self.board[row][col]
+    }
+
+    /// Every empty space, left to right, top to bottom
+    pub(crate) fn available_moves(&self) -> Vec<Coordinate> {
+        (0..SIZE * SIZE)
+            .map(|idx| Coordinate::new(idx / SIZE, idx % SIZE))
+            .filter(|coord| self.get_mark(*coord).is_none())
+            .collect()
+    }
+
+    /// Every move the current player may make: X or O in each empty space
+    pub(crate) fn legal_moves(&self) -> Vec<Move> {
+        self.available_moves()
+            .into_iter()
+            .flat_map(|coord| [Mark::X, Mark::O].iter().map(move |mark| Move::new(coord, *mark)))
+            .collect()
+    }
+
+    /// Place X or O in an empty space. Returns an error if the space is occupied or
+    /// off the board, or the mark isn't X or O.
+    pub(crate) fn play(&mut self, mv: Move) -> Result<()> {
+        let (row, col) = mv.coord.into();
+        if row >= SIZE || col >= SIZE { return Err(GameError::OffBoard); }
+        if self.board[row][col].is_some() { return Err(GameError::SpaceOccupied); }
+        if !matches!(mv.mark, Mark::X | Mark::O) { return Err(GameError::WrongMark); }
+        self.board[row][col] = Some(mv.mark);
+        self.moves.push(mv);
+        self.player.toggle();
+        Ok(())
+    }
+
+    /// The game after a move, which must be legal
+    pub(crate) fn after(&self, mv: Move) -> OrderChaos {
+        let mut next = self.clone();
+        next.play(mv).expect("Legal moves can be played");
+        next
+    }
+
+    /// The first five in a row of the same mark, if there is one
+    pub(crate) fn line(&self) -> Option<Vec<Coordinate>> {
+        windows().into_iter().find(|window| {
+            let first = self.get_mark(window[0]);
+            first.is_some() && window.iter().all(|coord| self.get_mark(*coord) == first)
+        })
+    }
+
+    /// The empty spaces where one more mark makes five in a row: four of the same mark
+    /// and a gap
+    pub(crate) fn threats(&self) -> Vec<Coordinate> {
+        let mut threats = Vec::new();
+        for window in windows() {
+            let placed: Vec<_> = window.iter().filter_map(|coord| self.get_mark(*coord)).collect();
+            let gap = window.iter().find(|coord| self.get_mark(**coord).is_none());
+            if let (4, Some(gap)) = (placed.len(), gap) {
+                if placed.iter().all(|mark| *mark == placed[0]) && !threats.contains(gap) {
+                    threats.push(*gap);
+                }
+            }
+        }
+        threats
+    }
+
+    /// Determine and return the current status of the game. The goals are lopsided:
+    /// five in a row is a win for Order, whoever placed the last mark, and a full
+    /// board without one is a win for Chaos. There are no draws.
+    pub(crate) fn status(&self) -> GameStatus {
+        if self.line().is_some() { return GameStatus::Winner(Player::X); }
+        if self.moves.len() == SIZE * SIZE { return GameStatus::Winner(Player::O); }
+        GameStatus::Pending(self.player)
+    }
+}
+
+
+/// How promising the board looks for Order: each run of five that holds only one
+/// kind of mark counts for more the fuller it is, and a run with both is worth nothing
+pub(crate) fn openness(order: &OrderChaos) -> i32 {
+    lines::openness(&windows(), |coord| order.get_mark(coord), |_| 1)
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Drawing
+//--------------------------------------------------------------------------------------
+
+/// The board as the players see it, with small marks on a 6x6 grid, five in a row
+/// picked out once Order makes it, and the cursor showing the mark it would place
+pub(crate) struct View<'a> {
+    order: &'a OrderChaos,
+    cursor: Option<(Coordinate, Mark)>,  // The space the cursor is on and its mark, if it's a person's turn
+}
+
+impl<'a> View<'a> {
+    pub(crate) fn new(order: &'a OrderChaos, cursor: Option<(Coordinate, Mark)>) -> Self {
+        View { order, cursor }
+    }
+}
+
+impl Draw for View<'_> {
+    fn draw(&self, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> crossterm::Result<()> {
+        small_grid(SIZE, SIZE).draw_with_color(term_row, term_col, theme.grid, theme, glyphs)?;
+        let origin = |coord: Coordinate| {
+            let (row, col) = coord.into();
+            (term_row + row as u16 * SMALL_ROW_HEIGHT, term_col + col as u16 * SMALL_COL_WIDTH)
+        };
+        let img = |mark: Mark| if mark == Mark::X { SMALL_X } else { SMALL_O };
+
+        let line = self.order.line();
+        for idx in 0..SIZE * SIZE {
+            let coord = Coordinate::new(idx / SIZE, idx % SIZE);
+            if let Some(mark) = self.order.get_mark(coord) {
+                let in_line = line.as_ref().is_some_and(|line| line.contains(&coord));
+                let color = if in_line { theme.win } else { theme.mark(mark) };
+                let (out_row, out_col) = origin(coord);
+                img(mark).draw_with_color(out_row + 1, out_col + 1, color, theme, glyphs)?;
+            }
+        }
+
+        if let Some((coord, mark)) = self.cursor {
+            let (out_row, out_col) = origin(coord);
+            if self.order.get_mark(coord).is_none() {
+                img(mark).draw_with_color(out_row + 1, out_col + 1, theme.cursor, theme, glyphs)?;
+            }
+            SMALL_CURSOR.draw_with_color(out_row, out_col, theme.cursor, theme, glyphs)?;
+        }
+        self.order.status().draw(term_row, term_col + 104, theme, glyphs)
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Variant
+//--------------------------------------------------------------------------------------
+
+impl Variant for OrderChaos {
+    type Side = Player;
+    type Move = Move;
+    type Search = ();
+
+    fn rules(&self) -> Rules {
+        Rules::Order
+    }
+
+    fn status(&self) -> GameStatus {
+        OrderChaos::status(self)
+    }
+
+    fn turn(&self) -> Player {
+        self.current_player()
+    }
+
+    fn moves(&self) -> usize {
+        self.history().len()
+    }
+
+    fn play(&mut self, mv: Move) -> Result<()> {
+        OrderChaos::play(self, mv)
+    }
+
+    fn choose(&self, ai: &mut Ai) -> Move {
+        ai.choose_order_move(self)
+    }
+
+    /// The players go by their roles
+    fn name(&self, player: Player) -> String {
+        Role::of(player).to_string()
+    }
+
+    fn describe_move(&self, player: Player, mv: Move) -> String {
+        describe::order_mark(player, mv)
+    }
+
+    fn describe_status(&self) -> String {
+        describe::order_status(&self.status())
+    }
+
+    fn instructions(&self) -> &'static str {
+        plain::ORDER_INSTRUCTIONS
+    }
+
+    fn grid(&self) -> Vec<String> {
+        plain::order_grid(self)
+    }
+
+    /// Leaving the mark off places the player's own
+    fn parse(&self, line: &str) -> Option<Move> {
+        let (coord, mark) = plain::parse_order_move(line)?;
+        Some(Move::new(coord, mark.unwrap_or_else(|| self.current_player().mark())))
+    }
+
+    fn draw(&self, cursor: Option<&Cursor>, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> crossterm::Result<()> {
+        let cursor = cursor.map(|cursor| (cursor.get_coordinate(), cursor.get_mark()));
+        View::new(self, cursor).draw(term_row, term_col, theme, glyphs)
+    }
+
+    /// While the game is pending, there must be an empty space
+    fn cursor(&self, _previous: Option<Cursor>) -> Cursor {
+        let mut cursor = Cursor::new(self.current_player().mark(), 1, 1);
+        cursor.move_to(self.available_moves()[0]);
+        cursor
+    }
+
+    fn act(&self, cursor: &mut Cursor, action: Action) -> Option<Move> {
+        match action {
+            Action::Shift(direction) => cursor.shift_on(direction, SIZE),
+            Action::ToggleMark => {
+                let mark = if cursor.get_mark() == Mark::X { Mark::O } else { Mark::X };
+                cursor.set_mark(mark);
+            },
+            Action::ChooseMark(mark @ Mark::X) | Action::ChooseMark(mark @ Mark::O) => cursor.set_mark(mark),
+            Action::Place => return Some(cursor.get_move()),
+            _ => {},
+        }
+        None
+    }
+
+    fn prompt(&self, cursor: &Cursor) -> Option<String> {
+        Some(format!("{} to play, placing {}.", Role::of(self.current_player()), cursor.get_mark()))
+    }
+
+    fn help(&self) -> Option<&'static str> {
+        Some("Tab switches between X and O, or press x or o to pick one.")
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Tests
+//--------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variant::play;
+
+    /// A mark placed in a space, by its row and column
+    fn place(row: usize, col: usize, mark: Mark) -> Move {
+        Move::new((row, col).into(), mark)
+    }
+
+    #[test]
+    fn test_windows() {
+        // Two runs of five along each row and column, and six along the diagonals
+        let windows = windows();
+        assert_eq!(windows.len(), 2 * SIZE * 2 + 8);
+        assert!(windows.contains(&[(0, 5), (1, 4), (2, 3), (3, 2), (4, 1)].map(Coordinate::from).to_vec()));
+    }
+
+    #[test]
+    fn test_five_in_a_row() {
+        // Order's four Os down a diagonal, with one placed by Chaos, and one more wins
+        let mut order = OrderChaos::new();
+        play(&mut order, &[
+            place(1, 1, Mark::O), place(0, 0, Mark::X), place(2, 2, Mark::O), place(3, 3, Mark::O),
+            place(4, 4, Mark::O), place(0, 5, Mark::X),
+        ]);
+        assert_eq!(order.threats(), vec![Coordinate::new(5, 5)]);
+        assert_eq!(order.status(), GameStatus::Pending(Player::X));
+        assert_eq!(order.play(place(1, 1, Mark::X)), Err(GameError::SpaceOccupied));
+        assert_eq!(order.play(place(6, 0, Mark::X)), Err(GameError::OffBoard));
+        play(&mut order, &[place(5, 5, Mark::O)]);
+        assert_eq!(order.status(), GameStatus::Winner(Player::X));
+    }
+
+    #[test]
+    fn test_full_board() {
+        // Pairs of marks in every row, shifted by one each row, never line up five
+        let mut order = OrderChaos::new();
+        for idx in 0..SIZE * SIZE {
+            let (row, col) = (idx / SIZE, idx % SIZE);
+            let mark = if (row + 2 * col) / 2 % 2 == 0 { Mark::X } else { Mark::O };
+            play(&mut order, &[place(row, col, mark)]);
+        }
+        assert_eq!(order.line(), None);
+        assert_eq!(order.status(), GameStatus::Winner(Player::O));
+    }
+}
@@ -0,0 +1,637 @@
+//! Line oriented play over stdin and stdout. No raw mode and no alternate screen, so
+//! the game works through pipes, in CI logs and in shells that aren't full terminals.
+
+use crate::analysis::Analysis;
+use crate::cube::{Cube, CubeCoordinate};
+use crate::describe;
+use crate::game::{Coordinate, Game, GameStatus, Mark, Move, Rules};
+use crate::gravity::Gravity;
+use crate::notakto::{BoardMove, Notakto};
+use crate::order::{self, OrderChaos};
+use crate::players::Players;
+use crate::quantum::{Quantum, QuantumMove, Step};
+use crate::review;
+use crate::solver::Solver;
+use crate::three::Three;
+use crate::variant::Variant;
+
+use std::io::{BufRead, Result, Write};
+
+const INSTRUCTIONS: &str = "\
+Enter moves as a row and column, like '2 3', or as a space number from 1 to 9,
+counting left to right, top to bottom. In wild games, add 'x' or 'o' to choose the
+mark, like '2 3 o'. In numerical games, name the number first, like '7 at 2 3'. In
+morris games, once your marks are placed, slide one with 'from to', like '1 1 to
+2 1' or '1-4'. Enter '?' for a hint, 'a' for the outcome of every move, or 'q' to
+quit.";
+
+pub(crate) const QUANTUM_INSTRUCTIONS: &str = "\
+Enter moves as two spaces, each a row and column or a space number, joined by 'and'
+or a dash, like '1 1 and 2 2' or '1-5'. When the last move closed a cycle, enter the
+space it should settle in, like '5'. Enter 'q' to quit.";
+
+pub(crate) const ORDER_INSTRUCTIONS: &str = "\
+Enter moves as a row and column from 1 to 6, then 'x' or 'o' for the mark, like
+'2 5 o'. Leave the mark off to place your own. Enter 'q' to quit.";
+
+pub(crate) const CUBE_INSTRUCTIONS: &str = "\
+Enter moves as a layer, row and column, each counting from 1, like '2 1 3' for the
+second layer from the top. Enter 'q' to quit.";
+
+pub(crate) const GRAVITY_INSTRUCTIONS: &str = "\
+Enter moves as the number of the column to drop your mark into, counting from 1 on
+the left. Enter 'q' to quit.";
+
+pub(crate) const THREE_INSTRUCTIONS: &str = "\
+Enter moves as a row and column, each counting from 1, like '2 4'. X, O and Y take
+turns in that order. Enter 'q' to quit.";
+
+pub(crate) const NOTAKTO_INSTRUCTIONS: &str = "\
+Enter moves as a board number, then a row and column or a space number, like '2 1 3'
+or '2 5'. Enter '?' for a hint, or 'q' to quit.";
+
+/// Parse a move from a line of input. Accepts a 1-based row and column separated by
+/// spaces or a comma ("2 3", "2,3"), or a single space number from 1 to 9.
+pub(crate) fn parse_move(line: &str) -> Option<Coordinate> {
+    let numbers: Vec<usize> = line
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().ok())
+        .collect::<Option<_>>()?;
+    match numbers.as_slice() {
+        [n] if (1..=9).contains(n) => Some(((n - 1) / 3, (n - 1) % 3).into()),
+        [row, col] if (1..=3).contains(row) && (1..=3).contains(col) => {
+            Some((row - 1, col - 1).into())
+        },
+        _ => None,
+    }
+}
+
+/// Parse a move, and the mark to place if one is named, from a line of input. The
+/// mark is 'x' or 'o' after the space, e.g. "2 3 o" or "5 x".
+pub(crate) fn parse_marked_move(line: &str) -> Option<(Coordinate, Option<Mark>)> {
+    let (rest, mark) = split_mark(line)?;
+    Some((parse_move(rest)?, mark))
+}
+
+/// Parse a move in Order and Chaos from a line of input: a 1-based row and column up
+/// to 6, and the mark as `parse_marked_move` reads it, e.g. "2 5 o" or "6,6"
+pub(crate) fn parse_order_move(line: &str) -> Option<(Coordinate, Option<Mark>)> {
+    let (rest, mark) = split_mark(line)?;
+    let numbers: Vec<usize> = rest
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().ok())
+        .collect::<Option<_>>()?;
+    match numbers.as_slice() {
+        [row, col] if (1..=order::SIZE).contains(row) && (1..=order::SIZE).contains(col) => {
+            Some(((row - 1, col - 1).into(), mark))
+        },
+        _ => None,
+    }
+}
+
+/// Parse a space in a 3D game from a line of input: a 1-based layer, row and column,
+/// each up to `size`, separated by spaces or commas, e.g. "2 1 3"
+pub(crate) fn parse_cube_move(line: &str, size: usize) -> Option<CubeCoordinate> {
+    let numbers: Vec<usize> = line
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().ok())
+        .collect::<Option<_>>()?;
+    match numbers.as_slice() {
+        [layer, row, col] if [layer, row, col].iter().all(|n| (1..=size).contains(*n)) => {
+            Some(CubeCoordinate::new(layer - 1, row - 1, col - 1))
+        },
+        _ => None,
+    }
+}
+
+/// Parse a space on a square board `size` spaces across from a line of input: a 1-based
+/// row and column, each up to `size`, separated by spaces or a comma, e.g. "2 4"
+pub(crate) fn parse_square_move(line: &str, size: usize) -> Option<Coordinate> {
+    let numbers: Vec<usize> = line
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().ok())
+        .collect::<Option<_>>()?;
+    match numbers.as_slice() {
+        [row, col] if (1..=size).contains(row) && (1..=size).contains(col) => Some((row - 1, col - 1).into()),
+        _ => None,
+    }
+}
+
+/// Parse the column to drop a mark into under gravity rules from a line of input: a
+/// number from 1 to `cols`, e.g. "4"
+pub(crate) fn parse_column(line: &str, cols: usize) -> Option<usize> {
+    let col: usize = line.trim().parse().ok()?;
+    (1..=cols).contains(&col).then(|| col - 1)
+}
+
+/// Split an 'x' or 'o' naming the mark off the end of a line of input
+fn split_mark(line: &str) -> Option<(&str, Option<Mark>)> {
+    let line = line.trim();
+    match line.char_indices().last()? {
+        (idx, 'x') | (idx, 'X') => Some((&line[..idx], Some(Mark::X))),
+        (idx, 'o') | (idx, 'O') => Some((&line[..idx], Some(Mark::O))),
+        _ => Some((line, None)),
+    }
+}
+
+/// Parse a number to place and where, from a line of input under numerical rules: the
+/// number, "at", then the space as `parse_move` reads it, e.g. "7 at 2 3" or "7 at 5"
+pub(crate) fn parse_numbered_move(line: &str) -> Option<(Coordinate, Mark)> {
+    let (number, rest) = line.trim().split_once(" at ")?;
+    let number: u8 = number.trim().parse().ok()?;
+    if !(1..=9).contains(&number) { return None; }
+    Some((parse_move(rest)?, Mark::Number(number)))
+}
+
+/// Parse a quantum step from a line of input. While a cycle waits to collapse, that's
+/// one space as `parse_move` reads it. Otherwise it's two spaces separated by "and" or
+/// a dash, e.g. "1 1 and 2 2" or "1-5", or one space for the last free space.
+pub(crate) fn parse_step(line: &str, quantum: &Quantum) -> Option<Step> {
+    if quantum.pending_collapse().is_some() { return parse_move(line).map(Step::Collapse); }
+    match line.split_once(" and ").or_else(|| line.split_once('-')) {
+        Some((first, second)) => Some(Step::Place(QuantumMove::new(parse_move(first)?, parse_move(second)?))),
+        None => parse_move(line).map(|coord| Step::Place(QuantumMove::new(coord, coord))),
+    }
+}
+
+/// Parse a morris slide from a line of input: two spaces as `parse_move` reads them,
+/// separated by "to" or a dash, e.g. "1 1 to 2 1" or "1-4"
+pub(crate) fn parse_slide(line: &str) -> Option<(Coordinate, Coordinate)> {
+    let (from, to) = line.split_once(" to ").or_else(|| line.split_once('-'))?;
+    Some((parse_move(from)?, parse_move(to)?))
+}
+
+/// Parse a notakto move from a line of input: a board number from 1 to `boards`, then
+/// the space as `parse_move` reads it, e.g. "2 1 3" or "2 5"
+pub(crate) fn parse_board_move(line: &str, boards: usize) -> Option<BoardMove> {
+    let (board, rest) = line.trim().split_once(|c: char| c.is_whitespace() || c == ',')?;
+    let board: usize = board.parse().ok()?;
+    if !(1..=boards).contains(&board) { return None; }
+    Some(BoardMove::new(board - 1, parse_move(rest)?))
+}
+
+/// Draw the board as three lines of text with row and column numbers
+pub(crate) fn grid(game: &Game) -> Vec<String> {
+    let mut lines = vec![String::from("    1   2   3")];
+    for row in 0..3 {
+        if row > 0 { lines.push(String::from("   ---+---+---")); }
+        let marks: Vec<_> = (0..3)
+            .map(|col| match game.get_space((row, col).into()).get_mark() {
+                Some(player) => player.to_string(),
+                None => String::from(" "),
+            })
+            .collect();
+        let line = format!("{}   {} | {} | {}", row + 1, marks[0], marks[1], marks[2]);
+        lines.push(line.trim_end().to_string());
+    }
+    lines
+}
+
+/// Draw an Order and Chaos board as six lines of text with row and column numbers
+pub(crate) fn order_grid(order: &OrderChaos) -> Vec<String> {
+    let mut lines = vec![String::from("    1   2   3   4   5   6")];
+    for row in 0..order::SIZE {
+        if row > 0 { lines.push(String::from("   ---+---+---+---+---+---")); }
+        let marks: Vec<_> = (0..order::SIZE)
+            .map(|col| match order.get_mark((row, col).into()) {
+                Some(mark) => mark.to_string(),
+                None => String::from(" "),
+            })
+            .collect();
+        let line = format!("{}   {}", row + 1, marks.join(" | "));
+        lines.push(line.trim_end().to_string());
+    }
+    lines
+}
+
+/// Draw a three-player board as text, one line to a row, with row and column numbers
+pub(crate) fn three_grid(three: &Three) -> Vec<String> {
+    let size = three.size();
+    let numbers: Vec<_> = (1..=size).map(|col| col.to_string()).collect();
+    let mut lines = vec![format!("    {}", numbers.join("   "))];
+    for row in 0..size {
+        if row > 0 { lines.push(format!("   {}", vec!["---"; size].join("+"))); }
+        let marks: Vec<_> = (0..size)
+            .map(|col| match three.get_mark((row, col).into()) {
+                Some(side) => side.to_string(),
+                None => String::from(" "),
+            })
+            .collect();
+        let line = format!("{}   {}", row + 1, marks.join(" | "));
+        lines.push(line.trim_end().to_string());
+    }
+    lines
+}
+
+/// Draw every layer of a 3D game side by side as text, each headed by its number,
+/// with row and column numbers
+pub(crate) fn cube_grid(cube: &Cube) -> Vec<String> {
+    let size = cube.size();
+    let numbers: Vec<_> = (1..=size).map(|col| col.to_string()).collect();
+    let layers: Vec<Vec<String>> = (0..size)
+        .map(|layer| {
+            let mut lines = vec![format!("Layer {}", layer + 1), format!("    {}", numbers.join("   "))];
+            for row in 0..size {
+                if row > 0 { lines.push(format!("   {}", vec!["---"; size].join("+"))); }
+                let marks: Vec<_> = (0..size)
+                    .map(|col| match cube.get_mark(CubeCoordinate::new(layer, row, col)) {
+                        Some(player) => player.to_string(),
+                        None => String::from(" "),
+                    })
+                    .collect();
+                lines.push(format!("{}   {}", row + 1, marks.join(" | ")));
+            }
+            lines
+        })
+        .collect();
+    let width = 4 * size + 6;
+    (0..layers[0].len())
+        .map(|line| {
+            let line: String = layers.iter().map(|layer| format!("{:<width$}", layer[line], width = width)).collect();
+            line.trim_end().to_string()
+        })
+        .collect()
+}
+
+/// Draw a gravity board as text, one line to a row, with the column numbers on top
+pub(crate) fn gravity_grid(gravity: &Gravity) -> Vec<String> {
+    let shape = gravity.shape();
+    let numbers: Vec<_> = (1..=shape.cols).map(|col| col.to_string()).collect();
+    let mut lines = vec![format!("  {}", numbers.join("   "))];
+    for row in 0..shape.rows {
+        let marks: Vec<_> = (0..shape.cols)
+            .map(|col| match gravity.get_mark((row, col).into()) {
+                Some(player) => player.to_string(),
+                None => String::from(" "),
+            })
+            .collect();
+        lines.push(format!("| {} |", marks.join(" | ")));
+    }
+    lines.push(format!("+{}", "---+".repeat(shape.cols)));
+    lines
+}
+
+/// Draw every notakto board side by side, each headed by its number
+pub(crate) fn boards(notakto: &Notakto) -> Vec<String> {
+    let grids: Vec<Vec<String>> = notakto.boards().iter()
+        .enumerate()
+        .map(|(idx, board)| {
+            let label = match notakto.is_dead(idx) {
+                true => format!("Board {} (dead)", idx + 1),
+                false => format!("Board {}", idx + 1),
+            };
+            std::iter::once(label).chain(grid(board)).collect()
+        })
+        .collect();
+    (0..grids[0].len())
+        .map(|line| {
+            let line: String = grids.iter().map(|grid| format!("{:<18}", grid[line])).collect();
+            line.trim_end().to_string()
+        })
+        .collect()
+}
+
+/// Draw a quantum board as text with row and column numbers, three lines to a row of
+/// spaces. Spooky marks are listed in their spaces, up to three to a line, and
+/// classical marks are boxed, e.g. "[X3]".
+pub(crate) fn quantum_grid(quantum: &Quantum) -> Vec<String> {
+    let header: Vec<_> = (1..=3).map(|col| format!("{:^10}", col)).collect();
+    let mut lines = vec![format!("  {}", header.join(" ")).trim_end().to_string()];
+    for row in 0..3 {
+        if row > 0 { lines.push(String::from("  ----------+----------+----------")); }
+        for sub in 0..3 {
+            let cells: Vec<_> = (0..3)
+                .map(|col| {
+                    let coord = Coordinate::new(row, col);
+                    let marks: Vec<_> = match quantum.classical(coord) {
+                        Some(idx) if sub == 1 => vec![format!("[{}]", describe::quantum_mark(idx))],
+                        Some(_) => Vec::new(),
+                        None => quantum.spooky(coord).into_iter().skip(sub * 3).take(3).map(describe::quantum_mark).collect(),
+                    };
+                    format!("{:^10}", marks.join(" "))
+                })
+                .collect();
+            let label = if sub == 1 { (row + 1).to_string() } else { String::from(" ") };
+            lines.push(format!("{} {}", label, cells.join("|")).trim_end().to_string());
+        }
+    }
+    lines
+}
+
+fn print_grid(game: &Game, output: &mut impl Write) -> Result<()> {
+    print_lines(&grid(game), output)
+}
+
+/// Play a game reading moves from stdin and printing the board to stdout. Returns the
+/// game as it stood when it ended or the players quit. Lines
+/// wrap around the edges if `wrap` is set.
+pub(crate) fn play(players: &mut Players, rules: Rules, wrap: bool) -> Result<Game> {
+    let stdin = std::io::stdin();
+    play_with(players, rules, wrap, stdin.lock(), std::io::stdout())
+}
+
+/// Play a game reading moves, one per line, from `input` and writing to `output`.
+/// Reaching the end of `input` quits the game. Lines wrap around the edges if `wrap` is
+/// set.
+pub(crate) fn play_with(players: &mut Players, rules: Rules, wrap: bool, input: impl BufRead, mut output: impl Write) -> Result<Game> {
+    let mut game = Game::with_rules(rules).with_wrap(wrap);
+    let mut lines = input.lines();
+    let mut solver = Solver::new();
+    let mut hint = None;
+
+    players.new_game(&game)?;
+    writeln!(output, "{}", INSTRUCTIONS)?;
+    if let Some(line) = describe::rules(rules) { writeln!(output, "{}", line)?; }
+    if let Some(line) = describe::wrap(&game) { writeln!(output, "{}", line)?; }
+    while let GameStatus::Pending(player) = game.status() {
+        print_grid(&game, &mut output)?;
+
+        // Programs move on their own
+        if players.is_computer(player) {
+            let mv = players.play_computer_move(&mut game)?;
+            writeln!(output, "{}", describe::mark(player, mv))?;
+            continue;
+        }
+        if let Some(line) = describe::vanishing(&game) { writeln!(output, "{}", line)?; }
+        if let Some(line) = describe::numbers(&game) { writeln!(output, "{}", line)?; }
+        write!(output, "{} to play: ", player)?;
+        output.flush()?;
+
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => { writeln!(output)?; return Ok(game); },
+        };
+        let line = line.trim();
+        if line == "q" || line == "quit" { return Ok(game); }
+        if line == "?" || line == "hint" {
+            // Asking again moves on to the next equally good move
+            hint = solver.hint(&game, hint.map(|(mv, _)| mv));
+            if let Some((mv, outcome)) = hint {
+                writeln!(output, "{}", describe::hint(player, mv, outcome))?;
+            }
+            continue;
+        }
+        if line == "a" || line == "analyze" {
+            let analysis = Analysis::new(&game, &mut solver);
+            for line in describe::analysis(player, analysis.outcomes()) { writeln!(output, "{}", line)?; }
+            continue;
+        }
+
+        let mv = match (parse_slide(line), parse_numbered_move(line)) {
+            (Some((from, coord)), _) => Some(Move::slide(from, coord, game.usual_mark())),
+            (None, Some((coord, number))) => Some(Move::new(coord, number)),
+            (None, None) => parse_marked_move(line).map(|(coord, mark)| Move::new(coord, mark.unwrap_or_else(|| game.usual_mark()))),
+        };
+        match mv {
+            Some(mv) => {
+                match game.play(mv) {
+                    Ok(()) => writeln!(output, "{}", describe::mark(player, mv))?,
+                    Err(e) => writeln!(output, "{}, please try again!", e)?,
+                }
+            },
+            None => writeln!(output, "Couldn't read a move from '{}', please try again!", line)?,
+        }
+    }
+
+    print_grid(&game, &mut output)?;
+    writeln!(output, "{}", describe::status(&game.status()))?;
+    if let Some(line) = describe::wrapped_line(&game) { writeln!(output, "{}", line)?; }
+    print_review(&game, &mut output)?;
+    Ok(game)
+}
+
+/// Play a variant with rules of its own, reading moves from stdin and printing the
+/// board to stdout. Returns the game as it stood when it ended or the players quit.
+pub(crate) fn play_variant<V: Variant>(game: V, players: &mut Players) -> Result<V> {
+    let stdin = std::io::stdin();
+    play_variant_with(game, players, stdin.lock(), std::io::stdout())
+}
+
+/// Play a variant reading moves, one per line, from `input` and writing to `output`.
+/// Reaching the end of `input` quits the game.
+pub(crate) fn play_variant_with<V: Variant>(mut game: V, players: &mut Players, input: impl BufRead, mut output: impl Write) -> Result<V> {
+    let mut lines = input.lines();
+    let mut search = V::Search::default();
+    let mut hint = None;
+
+    players.new_variant_game()?;
+    writeln!(output, "{}", game.instructions())?;
+    if let Some(line) = describe::rules(game.rules()) { writeln!(output, "{}", line)?; }
+    if let Some(line) = game.details() { writeln!(output, "{}", line)?; }
+    while let GameStatus::Pending(side) = game.status() {
+        print_lines(&game.grid(), &mut output)?;
+        if let Some(line) = game.note() { writeln!(output, "{}", line)?; }
+
+        // Programs move on their own
+        if players.side(side.into()).is_computer() {
+            let mv = players.play_computer_variant_move(&mut game)?;
+            writeln!(output, "{}", game.describe_move(side, mv))?;
+            continue;
+        }
+        write!(output, "{} to play: ", game.name(side))?;
+        output.flush()?;
+
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => { writeln!(output)?; return Ok(game); },
+        };
+        let line = line.trim();
+        if line == "q" || line == "quit" { return Ok(game); }
+        if line == "?" || line == "hint" {
+            // Asking again moves on to the next equally good move
+            hint = game.hint(&mut search, hint.map(|(mv, _)| mv));
+            match &hint {
+                Some((_, line)) => writeln!(output, "{}", line)?,
+                None => writeln!(output, "There are no hints by these rules.")?,
+            }
+            continue;
+        }
+
+        match game.parse(line) {
+            Some(mv) => match game.play(mv) {
+                Ok(()) => writeln!(output, "{}", game.describe_move(side, mv))?,
+                Err(e) => writeln!(output, "{}, please try again!", e)?,
+            },
+            None => writeln!(output, "Couldn't read a {} from '{}', please try again!", V::MOVE, line)?,
+        }
+    }
+
+    print_lines(&game.grid(), &mut output)?;
+    writeln!(output, "{}", game.describe_status())?;
+    if let Some(line) = game.score() { writeln!(output, "{}", line)?; }
+    Ok(game)
+}
+
+fn print_lines(lines: &[String], output: &mut impl Write) -> Result<()> {
+    writeln!(output)?;
+    for line in lines { writeln!(output, "{}", line)?; }
+    writeln!(output)
+}
+
+/// Print every mistake in a finished game, with the board as it was before each
+fn print_review(game: &Game, output: &mut impl Write) -> Result<()> {
+    let blunders = review::blunders(game, &mut Solver::new());
+    writeln!(output)?;
+    writeln!(output, "{}", describe::review(blunders.len()))?;
+    for blunder in blunders.iter() {
+        writeln!(output)?;
+        writeln!(output, "{}", describe::blunder(blunder))?;
+        print_grid(&blunder.position, output)?;
+    }
+    Ok(())
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Tests
+//--------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Player;
+    use crate::gravity::Shape;
+    use crate::players::Seat;
+    use crate::three::Side;
+
+    #[test]
+    fn test_parse_move() {
+        assert_eq!(parse_move("2 3"), Some(Coordinate::new(1, 2)));
+        assert_eq!(parse_move(" 1,1 "), Some(Coordinate::new(0, 0)));
+        assert_eq!(parse_move("9"), Some(Coordinate::new(2, 2)));
+        assert!(parse_move("0").is_none());
+        assert!(parse_move("4 1").is_none());
+        assert!(parse_move("b2").is_none());
+        assert_eq!(parse_marked_move("2 3 o"), Some((Coordinate::new(1, 2), Some(Mark::O))));
+        assert_eq!(parse_marked_move("5x"), Some((Coordinate::new(1, 1), Some(Mark::X))));
+        assert_eq!(parse_marked_move("5"), Some((Coordinate::new(1, 1), None)));
+        assert_eq!(parse_board_move("2 1 3", 3), Some(BoardMove::new(1, Coordinate::new(0, 2))));
+        assert_eq!(parse_board_move("3,5", 3), Some(BoardMove::new(2, Coordinate::new(1, 1))));
+        assert!(parse_board_move("4 5", 3).is_none());
+        assert!(parse_board_move("5", 3).is_none());
+        assert_eq!(parse_slide("1 1 to 2 1"), Some((Coordinate::new(0, 0), Coordinate::new(1, 0))));
+        assert_eq!(parse_slide("1-5"), Some((Coordinate::new(0, 0), Coordinate::new(1, 1))));
+        assert!(parse_slide("1 1").is_none());
+        assert_eq!(parse_numbered_move("7 at 2 3"), Some((Coordinate::new(1, 2), Mark::Number(7))));
+        assert_eq!(parse_numbered_move("4 at 5"), Some((Coordinate::new(1, 1), Mark::Number(4))));
+        assert!(parse_numbered_move("10 at 5").is_none());
+        assert!(parse_numbered_move("7 5").is_none());
+        assert_eq!(parse_order_move("6 6 o"), Some((Coordinate::new(5, 5), Some(Mark::O))));
+        assert_eq!(parse_order_move("2,5"), Some((Coordinate::new(1, 4), None)));
+        assert!(parse_order_move("7 1 x").is_none());
+        assert_eq!(parse_column(" 7 ", 7), Some(6));
+        assert_eq!(parse_cube_move("2 1 3", 3), Some(CubeCoordinate::new(1, 0, 2)));
+        assert_eq!(parse_cube_move("4,4,4", 4), Some(CubeCoordinate::new(3, 3, 3)));
+        assert!(parse_cube_move("4 1 1", 3).is_none());
+        assert!(parse_cube_move("1 1", 3).is_none());
+        assert!(parse_column("8", 7).is_none());
+        assert_eq!(parse_square_move("5,5", 5), Some(Coordinate::new(4, 4)));
+        assert!(parse_square_move("5 5", 4).is_none());
+        assert!(parse_column("0", 7).is_none());
+    }
+
+    #[test]
+    fn test_scripted_game() {
+        let input = "1\n4\n?\n2 2\n7\nnonsense\n5\n9\n";
+        let mut output = Vec::new();
+        let mut players = Players::new(Seat::Human(None), Seat::Human(None));
+        play_with(&mut players, Rules::Standard, false, input.as_bytes(), &mut output).expect("Failed to play");
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Couldn't read a move from 'nonsense'"));
+        assert!(output.contains("Hint: row "));
+        assert!(output.contains("Cannot add a move to an already occupied space"));
+        assert!(output.contains("X wins!\n\nReview: "));
+    }
+
+    /// Play a variant between people from a script of input lines. Returns the game as
+    /// it ended and everything printed.
+    fn script<V: Variant>(game: V, input: &str) -> (V, String) {
+        let mut output = Vec::new();
+        let players = Players::new(Seat::Human(None), Seat::Human(None));
+        let mut players = players.with_third(Seat::Human(None));
+        let game = play_variant_with(game, &mut players, input.as_bytes(), &mut output).expect("Failed to play");
+        (game, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn test_scripted_quantum() {
+        // X1 in 1 and 2, O2 in 2 and 3, X3 in 3 and 1 closes a cycle, and O settles X3
+        // in 1, which settles X1 in 2 and O2 in 3
+        let input = "1-2\n2 and 3\n5\n3-1\n5\n1\n";
+        let (quantum, output) = script(Quantum::new(), input);
+        assert!(output.contains("O places a spooky O2 in row 1, column 2 and row 1, column 3."));
+        assert!(output.contains("A spooky mark goes in two different spaces"));
+        assert!(output.contains("X3 closed a cycle. O chooses where it settles: row 1, column 3 or row 1, column 1."));
+        assert!(output.contains("Can only collapse a cycle into one of the last move's two spaces"));
+        assert!(output.contains("O collapses the cycle, settling X3 in row 1, column 1."));
+        assert!(output.contains("1    [X3]   |   [X1]   |   [O2]"));
+        assert_eq!(quantum.classical((0, 2).into()), Some(1));
+        assert_eq!(quantum.status(), GameStatus::Pending(Player::O));
+    }
+
+    #[test]
+    fn test_scripted_three() {
+        // Y completes the last column, with X and O each failing to block in time
+        let input = "1 1\n2 2\n1 4\n2 1\n3 3\n2 4\n2 2\n4 4\n4 1\n3 4\n";
+        let (three, output) = script(Three::new(4), input);
+        assert!(output.contains("Y marks row 1, column 4."));
+        assert!(output.contains("Cannot add a move to an already occupied space"));
+        assert!(output.contains("3     |   | O | Y"));
+        assert!(output.contains("Y wins!"));
+        assert_eq!(three.status(), GameStatus::Winner(Side::Y));
+    }
+
+    #[test]
+    fn test_scripted_order() {
+        // Order lines up Os down the first column, and Chaos never blocks it
+        let input = "1 1 o\n1 2 x\n2 1 o\n2 2\n3 1 o\n3 2 o\n4 1 o\n1 1\n4 2 x\n5 1 o\n";
+        let (order, output) = script(OrderChaos::new(), input);
+        assert!(output.contains("Chaos places an O in row 2, column 2."));
+        assert!(output.contains("Cannot add a move to an already occupied space"));
+        assert!(output.contains("4   O | X |   |   |   |"));
+        assert!(output.contains("Order wins with five in a row!"));
+        assert_eq!(order.status(), GameStatus::Winner(Player::X));
+    }
+
+    #[test]
+    fn test_scripted_cube() {
+        // X goes straight down through the middle of every layer
+        let input = "1 2 2\n1 1 1\n2 2 2\n2 2 2\n1 1 2\n3 2 2\n";
+        let (cube, output) = script(Cube::with_rules(Rules::Cube), input);
+        assert!(output.contains("O marks layer 1, row 1, column 1."));
+        assert!(output.contains("Cannot add a move to an already occupied space"));
+        assert!(output.contains("Layer 1           Layer 2           Layer 3"));
+        assert!(output.contains("1   O | O |       1     |   |       1     |   |"));
+        assert!(output.contains("X wins!"));
+        assert_eq!(cube.status(), GameStatus::Winner(Player::X));
+    }
+
+    #[test]
+    fn test_scripted_gravity() {
+        // X stacks four in the first column while O stacks three in the second
+        let input = "1\n2\n1\n2\n1\n2\n8\n1\n";
+        let (gravity, output) = script(Gravity::new(Shape::default()), input);
+        assert!(output.contains("Four in a row wins on a 7x6 board."));
+        assert!(output.contains("O drops into column 2, landing in row 6."));
+        assert!(output.contains("Couldn't read a column from '8'"));
+        assert!(output.contains("| X | O |   |   |   |   |   |\n+---+"));
+        assert!(output.contains("X wins!"));
+        assert_eq!(gravity.status(), GameStatus::Winner(Player::X));
+    }
+
+    #[test]
+    fn test_scripted_notakto() {
+        // X kills board 1, and O has to kill board 2
+        let input = "1 1\n1 2\n1 3\n1 5\n2\n?\n2 1\n2 5\n2 9\n";
+        let (notakto, output) = script(Notakto::new(2), input);
+        assert!(output.contains("Couldn't read a move from '2'"));
+        assert!(output.contains("Cannot mark a board that already has three in a row"));
+        assert!(output.contains("Hint: board 2"));
+        assert!(output.contains("Board 1 (dead)"));
+        assert_eq!(notakto.status(), GameStatus::Winner(Player::X));
+    }
+}
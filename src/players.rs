@@ -0,0 +1,274 @@
+//! Who plays each side of a game: a person at the keyboard or a program
+
+use crate::ai::{Ai, Level, Rng};
+use crate::bot::Bot;
+use crate::error::BotError;
+use crate::game::{Game, Move, Player, Rules};
+use crate::three::Side;
+use crate::variant::Variant;
+
+
+//--------------------------------------------------------------------------------------
+//-- Player specifications, as given on the command line
+//--------------------------------------------------------------------------------------
+
+/// A description of who should play one side of the game
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) enum PlayerSpec {
+    Human(Option<String>), // Moves come from the keyboard (or stdin), maybe by name
+    Ai(Level),             // Moves come from the built-in computer player
+    Bot(Vec<String>),      // Moves come from a bot program, as program and arguments
+}
+
+impl PlayerSpec {
+    /// Parse a player from the command line: `human`, `human:<name>`, `ai:<level>`, or
+    /// `bot:<command>` where the command is split into program and arguments on
+    /// whitespace.
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        if name == "human" { return Some(PlayerSpec::Human(None)); }
+        if let Some(name) = name.strip_prefix("human:") {
+            let name = name.trim();
+            if name.is_empty() { return None; }
+            return Some(PlayerSpec::Human(Some(name.to_string())));
+        }
+        if let Some(level) = name.strip_prefix("ai:") {
+            return Level::from_name(level).map(PlayerSpec::Ai);
+        }
+        let command: Vec<_> = name.strip_prefix("bot:")?
+            .split_whitespace()
+            .map(String::from)
+            .collect();
+        if command.is_empty() { return None; }
+        Some(PlayerSpec::Bot(command))
+    }
+}
+
+impl PlayerSpec {
+    /// The player to seat for a game by these rules. Computer players play at the
+    /// closest level that can, see `Level::for_rules`.
+    pub(crate) fn for_rules(&self, rules: Rules) -> Self {
+        match self {
+            PlayerSpec::Ai(level) => PlayerSpec::Ai(level.for_rules(rules)),
+            spec => spec.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for PlayerSpec {
+    /// The player as given on the command line, e.g. `human:alice` or `ai:perfect`
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PlayerSpec::Human(None) => write!(f, "human"),
+            PlayerSpec::Human(Some(name)) => write!(f, "human:{}", name),
+            PlayerSpec::Ai(level) => write!(f, "ai:{}", level),
+            PlayerSpec::Bot(command) => write!(f, "bot:{}", command.join(" ")),
+        }
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Seats
+//--------------------------------------------------------------------------------------
+
+/// One side of the game, ready to play
+pub(crate) enum Seat {
+    Human(Option<String>),
+    Ai(Ai),
+    Bot(Bot),
+}
+
+impl Seat {
+    /// Start playing as described, launching a bot program if needed. The built-in
+    /// computer player draws its random numbers from `rng`.
+    pub(crate) fn from_spec(spec: &PlayerSpec, rng: Rng) -> Result<Self, BotError> {
+        match spec {
+            PlayerSpec::Human(name) => Ok(Seat::Human(name.clone())),
+            PlayerSpec::Ai(level) => Ok(Seat::Ai(Ai::new(*level, rng))),
+            PlayerSpec::Bot(command) => Ok(Seat::Bot(Bot::spawn(command)?)),
+        }
+    }
+
+    /// A name to show for this side of the game
+    pub(crate) fn name(&self) -> String {
+        match self {
+            Seat::Human(Some(name)) => name.clone(),
+            Seat::Human(None) => String::from("human"),
+            Seat::Ai(ai) => format!("ai:{}", ai.level()),
+            Seat::Bot(bot) => bot.name().to_string(),
+        }
+    }
+
+    /// The name of the profile this side of the game is recorded in. Only named humans
+    /// have profiles.
+    pub(crate) fn profile_name(&self) -> Option<String> {
+        match self {
+            Seat::Human(name) => name.clone(),
+            _ => None,
+        }
+    }
+
+    /// A name to show under the status banner. Guests have none.
+    pub(crate) fn shown_name(&self) -> Option<String> {
+        match self {
+            Seat::Human(None) => None,
+            _ => Some(self.name()),
+        }
+    }
+
+    /// The key this side of the game is rated under: the kind of player, then the
+    /// profile name, level or whole bot command, e.g. `human:alice`, `ai:perfect` or
+    /// `bot:python3 a.py`. Humans without a name aren't rated.
+    pub(crate) fn rating_key(&self) -> Option<String> {
+        match self {
+            Seat::Human(None) => None,
+            Seat::Human(Some(name)) => Some(format!("human:{}", name)),
+            Seat::Ai(ai) => Some(format!("ai:{}", ai.level())),
+            Seat::Bot(bot) => Some(format!("bot:{}", bot.command())),
+        }
+    }
+
+    /// Is this side of the game played by a program?
+    pub(crate) fn is_computer(&self) -> bool {
+        !matches!(self, Seat::Human(_))
+    }
+
+    /// Let a bot program know that a new game is starting
+    pub(crate) fn new_game(&mut self, rules: Rules) -> Result<(), BotError> {
+        match self {
+            Seat::Bot(bot) => bot.new_game(rules),
+            _ => Ok(()),
+        }
+    }
+
+    /// Ask the program playing this seat for a move and make it. Returns the move
+    /// that was made. Must not be called for a human seat.
+    pub(crate) fn play_move(&mut self, game: &mut Game) -> Result<Move, BotError> {
+        let mv = match self {
+            Seat::Human(_) => panic!("Asked a human for a computer move"),
+            Seat::Ai(ai) => ai.choose_move(game),
+            Seat::Bot(bot) => bot.choose_move(game)?,
+        };
+        game.play(mv).map_err(|_| BotError::IllegalMove(self.name()))?;
+        Ok(mv)
+    }
+
+    /// Check this side can play a variant the bot protocol can't describe: notakto on
+    /// more than one board, quantum tic-tac-toe, Order and Chaos, gravity rules, 3D,
+    /// or three players. Bots can't.
+    pub(crate) fn new_variant_game(&mut self) -> Result<(), BotError> {
+        match self {
+            Seat::Bot(bot) => Err(BotError::Unsupported(bot.name().to_string())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Ask the program playing this seat for a move in a variant and make it. Returns
+    /// the move that was made. Must not be called for a human seat.
+    pub(crate) fn play_variant_move<V: Variant>(&mut self, game: &mut V) -> Result<V::Move, BotError> {
+        let mv = match self {
+            Seat::Human(_) => panic!("Asked a human for a computer move"),
+            Seat::Ai(ai) => game.choose(ai),
+            Seat::Bot(bot) => return Err(BotError::Unsupported(bot.name().to_string())),
+        };
+        game.play(mv).map_err(|_| BotError::IllegalMove(self.name()))?;
+        Ok(mv)
+    }
+}
+
+/// Both sides of the game, and a third in three-player games
+pub(crate) struct Players {
+    x: Seat,
+    o: Seat,
+    y: Option<Seat>,  // The third player, Y, only in three-player games
+}
+
+impl Players {
+    pub(crate) fn new(x: Seat, o: Seat) -> Self {
+        Players { x, o, y: None }
+    }
+
+    /// The same players, joined by a third for a three-player game
+    pub(crate) fn with_third(self, y: Seat) -> Self {
+        Players { y: Some(y), ..self }
+    }
+
+    /// Start both sides of the game as described
+    pub(crate) fn from_specs(x: &PlayerSpec, o: &PlayerSpec) -> Result<Self, BotError> {
+        let mut rng = Rng::from_time();
+        let x = Seat::from_spec(x, Rng::new(rng.next_u64()))?;
+        let o = Seat::from_spec(o, Rng::new(rng.next_u64()))?;
+        Ok(Players::new(x, o))
+    }
+
+    pub(crate) fn seat(&self, player: Player) -> &Seat {
+        match player {
+            Player::X => &self.x,
+            Player::O => &self.o,
+        }
+    }
+
+    fn seat_mut(&mut self, player: Player) -> &mut Seat {
+        match player {
+            Player::X => &mut self.x,
+            Player::O => &mut self.o,
+        }
+    }
+
+    /// Is this side of the game played by a program?
+    pub(crate) fn is_computer(&self, player: Player) -> bool {
+        self.seat(player).is_computer()
+    }
+
+    /// The seat of one of the players of a three-player game
+    pub(crate) fn side(&self, side: Side) -> &Seat {
+        match side {
+            Side::X => &self.x,
+            Side::O => &self.o,
+            Side::Y => self.y.as_ref().expect("A three-player game has a third seat"),
+        }
+    }
+
+    fn side_mut(&mut self, side: Side) -> &mut Seat {
+        match side {
+            Side::X => &mut self.x,
+            Side::O => &mut self.o,
+            Side::Y => self.y.as_mut().expect("A three-player game has a third seat"),
+        }
+    }
+
+    /// The keys both sides are rated under, if both sides are rated
+    pub(crate) fn rating_keys(&self) -> Option<(String, String)> {
+        Some((self.x.rating_key()?, self.o.rating_key()?))
+    }
+
+    /// Let every program know that a new game is starting. The bot protocol can't say
+    /// that lines wrap around the edges, so bots can't play when they do.
+    pub(crate) fn new_game(&mut self, game: &Game) -> Result<(), BotError> {
+        if game.wraps() { return self.new_variant_game(); }
+        self.x.new_game(game.rules())?;
+        self.o.new_game(game.rules())
+    }
+
+    /// Ask the program playing the current player for a move and make it. Returns the
+    /// move that was made. Must only be called when `is_computer` is true for the
+    /// current player.
+    pub(crate) fn play_computer_move(&mut self, game: &mut Game) -> Result<Move, BotError> {
+        self.seat_mut(game.current_player()).play_move(game)
+    }
+
+    /// Check every side can play a variant the bot protocol can't describe
+    pub(crate) fn new_variant_game(&mut self) -> Result<(), BotError> {
+        self.x.new_variant_game()?;
+        self.o.new_variant_game()?;
+        match &mut self.y {
+            Some(y) => y.new_variant_game(),
+            None => Ok(()),
+        }
+    }
+
+    /// As `play_computer_move`, for a variant
+    pub(crate) fn play_computer_variant_move<V: Variant>(&mut self, game: &mut V) -> Result<V::Move, BotError> {
+        self.side_mut(game.turn().into()).play_variant_move(game)
+    }
+}
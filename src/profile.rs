@@ -0,0 +1,348 @@
+//! Named player profiles with lifetime statistics, saved between runs
+
+use crate::game::{Game, GameStatus, Player, Rules};
+use crate::storage;
+
+use std::collections::HashMap;
+use std::io::Write;
+
+/// The file, in the data directory, that profiles are saved to
+const PROFILES_FILE: &str = "profiles.tsv";
+
+
+//--------------------------------------------------------------------------------------
+//-- Record
+//--------------------------------------------------------------------------------------
+
+/// Wins, draws and losses for one player
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub(crate) struct Record {
+    pub(crate) wins: u32,
+    pub(crate) draws: u32,
+    pub(crate) losses: u32,
+}
+
+impl Record {
+    /// Count a finished game for the player who played `mark`
+    pub(crate) fn add(&mut self, status: &GameStatus, mark: Player) {
+        match status {
+            GameStatus::Winner(winner) if *winner == mark => self.wins += 1,
+            GameStatus::Winner(_) => self.losses += 1,
+            GameStatus::Draw => self.draws += 1,
+            GameStatus::Pending(_) => {},
+        }
+    }
+
+    pub(crate) fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    pub(crate) fn combine(&self, other: &Record) -> Record {
+        Record {
+            wins: self.wins + other.wins,
+            draws: self.draws + other.draws,
+            losses: self.losses + other.losses,
+        }
+    }
+}
+
+impl std::fmt::Display for Record {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}-{}-{}", self.wins, self.draws, self.losses)
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Profile
+//--------------------------------------------------------------------------------------
+
+/// Lifetime statistics for one named player
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct Profile {
+    pub(crate) name: String,
+    pub(crate) record: Record,                    // Results of every finished game
+    by_rules: HashMap<Rules, Record>,             // Results by the rules they were played by
+    total_moves: u32,                             // Moves by both players, over all games
+    openings: [u32; 9],                           // Times each space of the 3x3 board was this player's first move
+    head_to_head: HashMap<String, Record>,        // Results against each opponent, by name
+}
+
+impl Profile {
+    pub(crate) fn new(name: &str) -> Self {
+        Profile { name: name.to_string(), ..Profile::default() }
+    }
+
+    /// Count a finished game, played as `mark` against `opponent`
+    pub(crate) fn record_game(&mut self, game: &Game, mark: Player, opponent: &str) {
+        let status = game.status();
+        if let GameStatus::Pending(_) = status { return; }
+        self.record_result(game.rules(), &status, mark, opponent, game.history().len());
+
+        // X makes the first move, O the second. A `Game` is always played on the 3x3
+        // board, so the opening is one of the nine spaces; games on other boards are
+        // counted with `record_result`, without an opening.
+        let first = if mark == Player::X { 0 } else { 1 };
+        if let Some(coord) = game.history().get(first) {
+            let (row, col) = coord.coord.get_tuple();
+            self.openings[row * 3 + col] += 1;
+        }
+    }
+
+    /// Count the result of a finished game of `moves` moves by `rules`, played as `mark`
+    /// against `opponent`, for games with no single board to find the opening on
+    pub(crate) fn record_result(&mut self, rules: Rules, status: &GameStatus, mark: Player, opponent: &str, moves: usize) {
+        if let GameStatus::Pending(_) = status { return; }
+        self.record.add(status, mark);
+        self.by_rules.entry(rules).or_default().add(status, mark);
+        self.head_to_head.entry(opponent.to_string()).or_default().add(status, mark);
+        self.total_moves += moves as u32;
+    }
+
+    /// The average number of moves, by both players, in this player's games
+    pub(crate) fn average_game_length(&self) -> Option<f64> {
+        let games = self.record.games();
+        if games == 0 { return None; }
+        Some(self.total_moves as f64 / games as f64)
+    }
+
+    /// The space this player most often makes their first move in, as (row, col). Ties
+    /// go to the space that comes first, left to right, top to bottom.
+    pub(crate) fn favorite_opening(&self) -> Option<(usize, usize)> {
+        let most = *self.openings.iter().max()?;
+        if most == 0 { return None; }
+        let idx = self.openings.iter().position(|count| *count == most)?;
+        Some((idx / 3, idx % 3))
+    }
+
+    /// Results by the rules they were played by, most games first
+    pub(crate) fn by_rules(&self) -> Vec<(Rules, &Record)> {
+        let mut records: Vec<_> = self.by_rules.iter().map(|(rules, record)| (*rules, record)).collect();
+        records.sort_by(|a, b| b.1.games().cmp(&a.1.games()).then_with(|| a.0.to_string().cmp(&b.0.to_string())));
+        records
+    }
+
+    /// Results against each opponent, most games first
+    pub(crate) fn head_to_head(&self) -> Vec<(&String, &Record)> {
+        let mut records: Vec<_> = self.head_to_head.iter().collect();
+        records.sort_by(|a, b| b.1.games().cmp(&a.1.games()).then_with(|| a.0.cmp(b.0)));
+        records
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Profiles
+//--------------------------------------------------------------------------------------
+
+/// Every saved profile, by name
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Profiles {
+    profiles: HashMap<String, Profile>,
+}
+
+impl Profiles {
+    /// Load the saved profiles
+    pub(crate) fn load() -> std::io::Result<Self> {
+        Profiles::parse(&storage::read(PROFILES_FILE)?)
+    }
+
+    /// Save the profiles, replacing what was saved before
+    pub(crate) fn save(&self) -> std::io::Result<()> {
+        storage::write(PROFILES_FILE, &self.to_tsv())
+    }
+
+    /// Read profiles from tab separated lines. Each profile is a line starting with
+    /// `profile`, followed by a line starting with `rules` for each rules played by and
+    /// one starting with `versus` for each opponent:
+    ///
+    /// profile <name> <wins> <draws> <losses> <total moves> <9 comma separated openings>
+    /// rules <name> <rules> <wins> <draws> <losses>
+    /// versus <name> <opponent> <wins> <draws> <losses>
+    fn parse(text: &str) -> std::io::Result<Self> {
+        let invalid = |line: &str| {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Invalid profile: '{}'", line))
+        };
+        let number = |field: &str, line: &str| field.parse::<u32>().map_err(|_| invalid(line));
+        let record = |fields: &[&str], line: &str| -> std::io::Result<Record> {
+            Ok(Record { wins: number(fields[0], line)?, draws: number(fields[1], line)?, losses: number(fields[2], line)? })
+        };
+
+        let mut profiles = HashMap::new();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let fields: Vec<_> = line.split('\t').collect();
+            match fields.as_slice() {
+                ["profile", name, rest @ ..] if rest.len() == 5 => {
+                    let mut openings = [0; 9];
+                    let counts: Vec<_> = rest[4].split(',').collect();
+                    if counts.len() != 9 { return Err(invalid(line)); }
+                    for (slot, count) in openings.iter_mut().zip(counts) { *slot = number(count, line)?; }
+                    let profile = Profile {
+                        name: name.to_string(),
+                        record: record(&rest[..3], line)?,
+                        total_moves: number(rest[3], line)?,
+                        openings,
+                        by_rules: HashMap::new(),
+                        head_to_head: HashMap::new(),
+                    };
+                    profiles.insert(name.to_string(), profile);
+                },
+                ["rules", name, rules, rest @ ..] if rest.len() == 3 => {
+                    let rules = Rules::from_name(rules).ok_or_else(|| invalid(line))?;
+                    let profile = profiles.get_mut(*name).ok_or_else(|| invalid(line))?;
+                    profile.by_rules.insert(rules, record(rest, line)?);
+                },
+                ["versus", name, opponent, rest @ ..] if rest.len() == 3 => {
+                    let profile = profiles.get_mut(*name).ok_or_else(|| invalid(line))?;
+                    profile.head_to_head.insert(opponent.to_string(), record(rest, line)?);
+                },
+                _ => return Err(invalid(line)),
+            }
+        }
+        Ok(Profiles { profiles })
+    }
+
+    /// Write profiles as tab separated lines, in the format read by `parse`
+    fn to_tsv(&self) -> String {
+        let mut out = String::new();
+        for profile in self.all() {
+            let r = profile.record;
+            let openings: Vec<_> = profile.openings.iter().map(u32::to_string).collect();
+            out.push_str(&format!(
+                "profile\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                profile.name, r.wins, r.draws, r.losses, profile.total_moves, openings.join(","),
+            ));
+            for (rules, r) in profile.by_rules() {
+                out.push_str(&format!("rules\t{}\t{}\t{}\t{}\t{}\n", profile.name, rules, r.wins, r.draws, r.losses));
+            }
+            for (opponent, r) in profile.head_to_head() {
+                out.push_str(&format!("versus\t{}\t{}\t{}\t{}\t{}\n", profile.name, opponent, r.wins, r.draws, r.losses));
+            }
+        }
+        out
+    }
+
+    /// Every profile, in order by name
+    pub(crate) fn all(&self) -> Vec<&Profile> {
+        let mut profiles: Vec<_> = self.profiles.values().collect();
+        profiles.sort_by(|a, b| a.name.cmp(&b.name));
+        profiles
+    }
+
+    /// The names of every profile, in order
+    pub(crate) fn names(&self) -> Vec<String> {
+        self.all().into_iter().map(|profile| profile.name.clone()).collect()
+    }
+
+    /// Count a finished game for the profile called `name`, creating it if needed
+    pub(crate) fn record_game(&mut self, name: &str, game: &Game, mark: Player, opponent: &str) {
+        let (profile, opponent) = self.entry(name, opponent);
+        profile.record_game(game, mark, &opponent);
+    }
+
+    /// Count the result of a finished game for the profile called `name`, creating it
+    /// if needed. See `Profile::record_result`.
+    pub(crate) fn record_result(&mut self, name: &str, rules: Rules, status: &GameStatus, mark: Player, opponent: &str, moves: usize) {
+        let (profile, opponent) = self.entry(name, opponent);
+        profile.record_result(rules, status, mark, &opponent, moves);
+    }
+
+    /// The profile called `name`, created if needed, and the opponent's name, both
+    /// cleaned up for saving
+    fn entry(&mut self, name: &str, opponent: &str) -> (&mut Profile, String) {
+        let name = storage::field(name);
+        let profile = self.profiles.entry(name.clone()).or_insert_with(|| Profile::new(&name));
+        (profile, storage::field(opponent))
+    }
+
+    /// Print every profile's statistics
+    pub(crate) fn print(&self, output: &mut impl Write) -> std::io::Result<()> {
+        let profiles = self.all();
+        if profiles.is_empty() {
+            return writeln!(output, "No profiles yet. Play as 'human:<name>' to create one.");
+        }
+        for (idx, profile) in profiles.iter().enumerate() {
+            if idx > 0 { writeln!(output)?; }
+            let r = profile.record;
+            writeln!(output, "{}", profile.name)?;
+            writeln!(output, "  Games:            {} ({} wins, {} draws, {} losses)", r.games(), r.wins, r.draws, r.losses)?;
+            if let Some(length) = profile.average_game_length() {
+                writeln!(output, "  Average length:   {:.1} moves", length)?;
+            }
+            if let Some((row, col)) = profile.favorite_opening() {
+                writeln!(output, "  Favorite opening: row {}, column {}", row + 1, col + 1)?;
+            }
+            for (rules, record) in profile.by_rules() {
+                writeln!(output, "  {:<17} {} (W-D-L)", format!("{}:", rules), record)?;
+            }
+            for (opponent, record) in profile.head_to_head() {
+                writeln!(output, "  vs {:<14} {} (W-D-L)", opponent, record)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Tests
+//--------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Coordinate;
+
+    fn game(moves: &[(usize, usize)]) -> Game {
+        let moves: Vec<_> = moves.iter().map(|x| Coordinate::from(*x)).collect();
+        Game::from(&moves).expect("Failed to create game.")
+    }
+
+    #[test]
+    fn test_profile_statistics() {
+        let mut profiles = Profiles::default();
+        // alice plays X and wins, then plays O and loses
+        let first = game(&[(1, 1), (0, 0), (1, 0), (0, 1), (1, 2)]);
+        let second = game(&[(0, 0), (1, 1), (0, 1), (2, 2), (0, 2)]);
+        profiles.record_game("alice", &first, Player::X, "bob");
+        profiles.record_game("alice", &second, Player::O, "ai:easy");
+
+        let alice = &profiles.all()[0];
+        assert_eq!(alice.record, Record { wins: 1, draws: 0, losses: 1 });
+        assert_eq!(alice.average_game_length(), Some(5.0));
+        assert_eq!(alice.favorite_opening(), Some((1, 1)));
+        assert_eq!(alice.head_to_head().len(), 2);
+        assert_eq!(alice.by_rules(), vec![(Rules::Standard, &Record { wins: 1, draws: 0, losses: 1 })]);
+    }
+
+    #[test]
+    fn test_records_by_rules() {
+        let mut profiles = Profiles::default();
+        profiles.record_game("alice", &game(&[(1, 1), (0, 0), (1, 0), (0, 1), (1, 2)]), Player::X, "bob");
+        profiles.record_result("alice", Rules::Quantum, &GameStatus::Winner(Player::O), Player::X, "bob", 9);
+        let alice = &profiles.all()[0];
+        assert_eq!(alice.record, Record { wins: 1, draws: 0, losses: 1 });
+        assert_eq!(alice.by_rules().len(), 2);
+        let parsed = Profiles::parse(&profiles.to_tsv()).expect("Failed to parse");
+        assert_eq!(parsed, profiles);
+        assert!(Profiles::parse("profile\talice\t0\t0\t0\t0\t0,0,0,0,0,0,0,0,0\nrules\talice\tchess\t1\t0\t0").is_err());
+    }
+
+    #[test]
+    fn test_unfinished_games_dont_count() {
+        let mut profiles = Profiles::default();
+        profiles.record_game("alice", &game(&[(1, 1)]), Player::X, "bob");
+        assert_eq!(profiles.all()[0].record.games(), 0);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut profiles = Profiles::default();
+        let first = game(&[(1, 1), (0, 0), (1, 0), (0, 1), (1, 2)]);
+        profiles.record_game("alice", &first, Player::X, "bob");
+        profiles.record_game("bob", &first, Player::O, "alice");
+        let parsed = Profiles::parse(&profiles.to_tsv()).expect("Failed to parse");
+        assert_eq!(parsed, profiles);
+        assert!(Profiles::parse("versus\tnobody\tbob\t1\t0\t0").is_err());
+    }
+}
@@ -0,0 +1,497 @@
+//! Quantum tic-tac-toe: each move puts a "spooky" mark in two spaces at once, and it
+//! only settles into one of them once the spooky marks are entangled in a cycle. The
+//! player who didn't close the cycle chooses how it collapses, and the settled,
+//! classical marks decide the game. Every mark carries the number of the move that
+//! made it, and when both players complete a line in the same collapse, the line
+//! finished with the lower numbers wins.
+
+use crate::ai::Ai;
+use crate::cursor::Cursor;
+use crate::describe;
+use crate::display::{BIG_CURSOR, BIG_GRID, BIG_X, BIG_O, ROW_HEIGHT, COL_WIDTH, CharMatrix, Draw, DrawWithColor, Glyphs};
+use crate::error::{GameError, Result};
+use crate::game::{Coordinate, GameStatus, Player, Rules};
+use crate::input::Action;
+use crate::plain;
+use crate::theme::Theme;
+use crate::variant::Variant;
+
+use std::collections::VecDeque;
+
+/// The eight lines of three, as (row, col) pairs
+const LINES: [[(usize, usize); 3]; 8] = [
+    [(0, 0), (0, 1), (0, 2)],
+    [(1, 0), (1, 1), (1, 2)],
+    [(2, 0), (2, 1), (2, 2)],
+    [(0, 0), (1, 0), (2, 0)],
+    [(0, 1), (1, 1), (2, 1)],
+    [(0, 2), (1, 2), (2, 2)],
+    [(0, 0), (1, 1), (2, 2)],
+    [(0, 2), (1, 1), (2, 0)],
+];
+
+
+//--------------------------------------------------------------------------------------
+//-- Quantum
+//--------------------------------------------------------------------------------------
+
+/// A move: the two spaces a spooky mark goes in. Both are the same space only when it's
+/// the last free space, and then the mark is classical right away.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub(crate) struct QuantumMove {
+    pub(crate) first: Coordinate,
+    pub(crate) second: Coordinate,
+}
+
+impl QuantumMove {
+    pub(crate) fn new(first: Coordinate, second: Coordinate) -> Self {
+        QuantumMove { first, second }
+    }
+
+    /// Does the spooky mark have a foot in this space?
+    fn touches(&self, coord: Coordinate) -> bool {
+        self.first == coord || self.second == coord
+    }
+
+    /// The move's other space, given one of them
+    fn other(&self, coord: Coordinate) -> Coordinate {
+        if self.first == coord { self.second } else { self.first }
+    }
+}
+
+/// Something a player does on their turn: collapse the cycle the opponent closed, or
+/// make a move. After a collapse, the same player moves too, unless the game is over.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum Step {
+    Collapse(Coordinate),
+    Place(QuantumMove),
+}
+
+/// A game of quantum tic-tac-toe
+#[derive(Debug, Clone)]
+pub(crate) struct Quantum {
+    moves: Vec<QuantumMove>,               // Every move so far. X made the even indices.
+    settled: Vec<Option<Coordinate>>,      // Where each move's mark settled, once it has
+    classical: [[Option<usize>; 3]; 3],    // The move whose mark settled in each space
+    cycle: Option<usize>,                  // The move that closed a cycle, until it collapses
+    player: Player,                        // The current player, Player::X or Player::O
+}
+
+impl Quantum {
+    pub(crate) fn new() -> Self {
+        Quantum { moves: Vec::new(), settled: Vec::new(), classical: [[None; 3]; 3], cycle: None, player: Player::X }
+    }
+
+    /// Return the current player
+    pub(crate) fn current_player(&self) -> Player {
+        self.player
+    }
+
+    /// Return every move made so far, in order. Move `idx` is numbered `idx + 1`.
+    pub(crate) fn history(&self) -> &[QuantumMove] {
+        &self.moves
+    }
+
+    /// The player who made a move, by its index in the history
+    pub(crate) fn mover(idx: usize) -> Player {
+        if idx.is_multiple_of(2) { Player::X } else { Player::O }
+    }
+
+    /// The move whose mark settled in a space, by its index in the history
+    pub(crate) fn classical(&self, coord: Coordinate) -> Option<usize> {
+        let (row, col) = coord.into();
+        self.classical[row][col]
+    }
+
+    /// The moves with a spooky mark in a space, by their index in the history
+    pub(crate) fn spooky(&self, coord: Coordinate) -> Vec<usize> {
+        (0..self.moves.len())
+            .filter(|idx| self.settled[*idx].is_none() && self.moves[*idx].touches(coord))
+            .collect()
+    }
+
+    /// Every space without a classical mark, left to right, top to bottom
+    pub(crate) fn free_spaces(&self) -> Vec<Coordinate> {
+        (0..9)
+            .map(|idx| Coordinate::new(idx / 3, idx % 3))
+            .filter(|coord| self.classical(*coord).is_none())
+            .collect()
+    }
+
+    /// The move that closed a cycle, waiting for the current player to choose which of
+    /// its two spaces it settles in
+    pub(crate) fn pending_collapse(&self) -> Option<QuantumMove> {
+        self.cycle.map(|idx| self.moves[idx])
+    }
+
+    /// Make a move, or collapse a cycle
+    pub(crate) fn take(&mut self, step: Step) -> Result<()> {
+        match step {
+            Step::Collapse(coord) => self.collapse(coord),
+            Step::Place(mv) => self.play(mv),
+        }
+    }
+
+    /// Place a spooky mark in two free spaces, or a classical mark in the last one.
+    /// Returns an error if a cycle is waiting to collapse, either space holds a
+    /// classical mark, or both spaces are the same one while another is free.
+    pub(crate) fn play(&mut self, mv: QuantumMove) -> Result<()> {
+        if self.cycle.is_some() { return Err(GameError::MustCollapse); }
+        if self.classical(mv.first).is_some() || self.classical(mv.second).is_some() {
+            return Err(GameError::SpaceOccupied);
+        }
+        if mv.first == mv.second && self.free_spaces().len() > 1 { return Err(GameError::SameSpace); }
+
+        let closes_cycle = mv.first != mv.second && self.entangled(mv.first, mv.second);
+        self.moves.push(mv);
+        self.settled.push(None);
+        let idx = self.moves.len() - 1;
+        if mv.first == mv.second { self.settle(idx, mv.first); }
+        if closes_cycle { self.cycle = Some(idx); }
+        self.player.toggle();
+        Ok(())
+    }
+
+    /// Collapse the waiting cycle, settling the mark that closed it in one of its two
+    /// spaces. Every other mark in the cycle, and every mark hanging off it, is left
+    /// with only one space to settle in. Returns an error if there's no cycle to
+    /// collapse, or the space isn't one of the two.
+    pub(crate) fn collapse(&mut self, coord: Coordinate) -> Result<()> {
+        let idx = self.cycle.ok_or(GameError::IllegalCollapse)?;
+        if !self.moves[idx].touches(coord) { return Err(GameError::IllegalCollapse); }
+        self.cycle = None;
+        self.settle(idx, coord);
+        Ok(())
+    }
+
+    /// Settle a mark in a space, and every mark that's forced to settle with it
+    fn settle(&mut self, idx: usize, coord: Coordinate) {
+        let mut queue = VecDeque::from([(idx, coord)]);
+        while let Some((idx, coord)) = queue.pop_front() {
+            if self.settled[idx].is_some() { continue; }
+            self.settled[idx] = Some(coord);
+            let (row, col) = coord.into();
+            self.classical[row][col] = Some(idx);
+            for other in self.spooky(coord) {
+                queue.push_back((other, self.moves[other].other(coord)));
+            }
+        }
+    }
+
+    /// Are two spaces joined by a chain of spooky marks? A move between them would
+    /// close a cycle.
+    fn entangled(&self, from: Coordinate, to: Coordinate) -> bool {
+        let mut seen = vec![from];
+        let mut queue = VecDeque::from([from]);
+        while let Some(coord) = queue.pop_front() {
+            if coord == to { return true; }
+            for idx in self.spooky(coord) {
+                let next = self.moves[idx].other(coord);
+                if !seen.contains(&next) {
+                    seen.push(next);
+                    queue.push_back(next);
+                }
+            }
+        }
+        false
+    }
+
+    /// Every step the current player may take: the two ways to collapse the waiting
+    /// cycle, or else every pair of free spaces, or the last free space on its own
+    pub(crate) fn legal_steps(&self) -> Vec<Step> {
+        if let Some(mv) = self.pending_collapse() {
+            return vec![Step::Collapse(mv.first), Step::Collapse(mv.second)];
+        }
+        let free = self.free_spaces();
+        if let [last] = free.as_slice() {
+            return vec![Step::Place(QuantumMove::new(*last, *last))];
+        }
+        free.iter()
+            .enumerate()
+            .flat_map(|(idx, first)| free[idx + 1..].iter().map(move |second| Step::Place(QuantumMove::new(*first, *second))))
+            .collect()
+    }
+
+    /// The game after a step, which must be legal
+    pub(crate) fn after(&self, step: Step) -> Quantum {
+        let mut next = self.clone();
+        next.take(step).expect("Legal steps can be taken");
+        next
+    }
+
+    /// Every completed line of classical marks, with the player who owns it and the
+    /// highest move number in it, which is when the line was finished in the order of
+    /// play
+    pub(crate) fn lines(&self) -> Vec<(Player, usize)> {
+        LINES.iter()
+            .filter_map(|line| {
+                let moves: Vec<_> = line.iter().map(|(row, col)| self.classical[*row][*col]).collect::<Option<_>>()?;
+                let owner = Quantum::mover(moves[0]);
+                if moves.iter().any(|idx| Quantum::mover(*idx) != owner) { return None; }
+                Some((owner, moves.iter().max().copied()? + 1))
+            })
+            .collect()
+    }
+
+    /// Determine and return the current status of the game. When both players have a
+    /// line, the one finished with the lower highest move number wins.
+    pub(crate) fn status(&self) -> GameStatus {
+        if let Some((winner, _)) = self.lines().into_iter().min_by_key(|(_, number)| *number) {
+            return GameStatus::Winner(winner);
+        }
+        if self.free_spaces().is_empty() { return GameStatus::Draw; }
+        GameStatus::Pending(self.player)
+    }
+
+    /// The score of a finished game in half points, as (X, O). The winner scores a
+    /// whole point, and a loser who completed a line too scores half of one.
+    pub(crate) fn half_points(&self) -> Option<(u8, u8)> {
+        let has_line = |player| self.lines().iter().any(|(owner, _)| *owner == player);
+        let points = |player| if has_line(player) { 1 } else { 0 };
+        match self.status() {
+            GameStatus::Winner(Player::X) => Some((2, points(Player::O))),
+            GameStatus::Winner(Player::O) => Some((points(Player::X), 2)),
+            GameStatus::Draw => Some((1, 1)),
+            GameStatus::Pending(_) => None,
+        }
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Drawing
+//--------------------------------------------------------------------------------------
+
+/// Label a mark with its move number as a subscript, e.g. "X₃"
+pub(crate) fn label(idx: usize) -> String {
+    let subscript = char::from_u32('₀' as u32 + (idx as u32 + 1) % 10).expect("Subscript digits are contiguous");
+    format!("{}{}", Quantum::mover(idx), subscript)
+}
+
+/// The board as the players see them: classical marks drawn full size, spooky marks
+/// drawn small inside the spaces they're in, the spaces a waiting cycle can collapse
+/// into framed, and the cursor, with the first space of a move picked out
+pub(crate) struct View<'a> {
+    quantum: &'a Quantum,
+    cursor: Option<Coordinate>,    // The space the cursor is on, if it's a person's turn
+    selected: Option<Coordinate>,  // The first space of the move being made
+}
+
+impl<'a> View<'a> {
+    pub(crate) fn new(quantum: &'a Quantum, cursor: Option<Coordinate>, selected: Option<Coordinate>) -> Self {
+        View { quantum, cursor, selected }
+    }
+}
+
+impl Draw for View<'_> {
+    fn draw(&self, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> crossterm::Result<()> {
+        BIG_GRID.draw_with_color(term_row, term_col, theme.grid, theme, glyphs)?;
+        let origin = |coord: Coordinate| {
+            let (row, col) = coord.into();
+            (term_row + row as u16 * ROW_HEIGHT, term_col + col as u16 * COL_WIDTH)
+        };
+
+        for idx in 0..9 {
+            let coord = Coordinate::new(idx / 3, idx % 3);
+            let (out_row, out_col) = origin(coord);
+            if let Some(idx) = self.quantum.classical(coord) {
+                // Classical marks keep their move number, in the bottom corner
+                let player = Quantum::mover(idx);
+                let img = match player { Player::X => BIG_X, Player::O => BIG_O };
+                img.draw_with_color(out_row, out_col, theme.player(player), theme, glyphs)?;
+                CharMatrix::from(label(idx).as_str()).draw_with_color(out_row + 11, out_col + 26, theme.label, theme, glyphs)?;
+                continue;
+            }
+            // Up to three spooky marks to a line, three lines to a space
+            for (nth, idx) in self.quantum.spooky(coord).into_iter().enumerate() {
+                let (line, slot) = (nth as u16 / 3, nth as u16 % 3);
+                let color = theme.player(Quantum::mover(idx));
+                CharMatrix::from(label(idx).as_str()).draw_with_color(out_row + 2 + 4 * line, out_col + 6 + 8 * slot, color, theme, glyphs)?;
+            }
+        }
+
+        let frames = self.quantum.pending_collapse()
+            .map(|mv| vec![(mv.first, theme.win), (mv.second, theme.win)])
+            .unwrap_or_default()
+            .into_iter()
+            .chain(self.selected.map(|coord| (coord, theme.label)))
+            .chain(self.cursor.map(|coord| (coord, theme.cursor)));
+        for (coord, color) in frames {
+            let (out_row, out_col) = origin(coord);
+            BIG_CURSOR.draw_with_color(out_row, out_col, color, theme, glyphs)?;
+        }
+        self.quantum.status().draw(term_row, term_col + 104, theme, glyphs)
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Variant
+//--------------------------------------------------------------------------------------
+
+impl Variant for Quantum {
+    type Side = Player;
+    type Move = Step;
+    type Search = ();
+
+    fn rules(&self) -> Rules {
+        Rules::Quantum
+    }
+
+    fn status(&self) -> GameStatus {
+        Quantum::status(self)
+    }
+
+    fn turn(&self) -> Player {
+        self.current_player()
+    }
+
+    fn moves(&self) -> usize {
+        self.moves.len()
+    }
+
+    fn play(&mut self, step: Step) -> Result<()> {
+        self.take(step)
+    }
+
+    fn choose(&self, ai: &mut Ai) -> Step {
+        ai.choose_quantum_step(self)
+    }
+
+    fn half_points(&self) -> Option<(u8, u8)> {
+        Quantum::half_points(self)
+    }
+
+    fn describe_move(&self, player: Player, step: Step) -> String {
+        describe::quantum_step(self, player, step)
+    }
+
+    fn note(&self) -> Option<String> {
+        describe::quantum_collapse(self)
+    }
+
+    fn score(&self) -> Option<String> {
+        describe::quantum_score(self)
+    }
+
+    fn instructions(&self) -> &'static str {
+        plain::QUANTUM_INSTRUCTIONS
+    }
+
+    fn grid(&self) -> Vec<String> {
+        plain::quantum_grid(self)
+    }
+
+    fn parse(&self, line: &str) -> Option<Step> {
+        plain::parse_step(line, self)
+    }
+
+    fn draw(&self, cursor: Option<&Cursor>, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> crossterm::Result<()> {
+        let selected = cursor.and_then(|cursor| cursor.get_selected());
+        View::new(self, cursor.map(|cursor| cursor.get_coordinate()), selected).draw(term_row, term_col, theme, glyphs)
+    }
+
+    /// While the game is pending, there must be a free space
+    fn cursor(&self, _previous: Option<Cursor>) -> Cursor {
+        let mut cursor = Cursor::new(self.current_player().mark(), 1, 1);
+        cursor.move_to(self.free_spaces()[0]);
+        cursor
+    }
+
+    /// The first press picks a space for the move, and the second, on another space,
+    /// makes it. Collapsing a cycle or placing a mark in the last free space takes one.
+    fn act(&self, cursor: &mut Cursor, action: Action) -> Option<Step> {
+        match action {
+            Action::Shift(direction) => { cursor.shift(direction); None },
+            Action::Place => {
+                let coord = cursor.get_coordinate();
+                let free = self.free_spaces();
+                match cursor.get_selected() {
+                    _ if self.pending_collapse().is_some() => Some(Step::Collapse(coord)),
+                    _ if free == [coord] => Some(Step::Place(QuantumMove::new(coord, coord))),
+                    // Picking the first space again puts it back
+                    Some(first) if first == coord => { cursor.select(None); None },
+                    Some(first) => Some(Step::Place(QuantumMove::new(first, coord))),
+                    None if free.contains(&coord) => { cursor.select(Some(coord)); None },
+                    None => Some(Step::Place(QuantumMove::new(coord, coord))),
+                }
+            },
+            _ => None,
+        }
+    }
+
+    fn prompt(&self, cursor: &Cursor) -> Option<String> {
+        let prompt = match (describe::quantum_collapse(self), cursor.get_selected()) {
+            (Some(line), _) => line,
+            (None, Some(_)) => format!("Choose the other space for {}.", describe::quantum_mark(self.moves.len())),
+            (None, None) => format!("Choose two spaces for {}.", describe::quantum_mark(self.moves.len())),
+        };
+        Some(prompt)
+    }
+
+    fn help(&self) -> Option<&'static str> {
+        Some("Enter picks a space, and again on a second space places the mark.")
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Tests
+//--------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variant::play;
+
+    /// A spooky mark in two spaces, numbered 1 to 9 as on a phone keypad
+    fn spooky(first: usize, second: usize) -> Step {
+        let space = |n: usize| Coordinate::new((n - 1) / 3, (n - 1) % 3);
+        Step::Place(QuantumMove::new(space(first), space(second)))
+    }
+
+    #[test]
+    fn test_cycle_collapses() {
+        // X1 in 1 and 2, O2 in 2 and 3, X3 in 3 and 1 closes a cycle for O to collapse
+        let mut quantum = Quantum::new();
+        play(&mut quantum, &[spooky(1, 2), spooky(2, 3)]);
+        assert_eq!(quantum.pending_collapse(), None);
+        play(&mut quantum, &[spooky(3, 1)]);
+        assert_eq!(quantum.pending_collapse(), Some(QuantumMove::new((0, 2).into(), (0, 0).into())));
+        assert_eq!(quantum.play(QuantumMove::new((1, 1).into(), (2, 2).into())), Err(GameError::MustCollapse));
+        assert_eq!(quantum.collapse((1, 1).into()), Err(GameError::IllegalCollapse));
+
+        // X3 settles in 1, so X1 settles in 2 and O2 in 3
+        quantum.collapse((0, 0).into()).expect("Failed to collapse.");
+        assert_eq!(quantum.classical((0, 0).into()), Some(2));
+        assert_eq!(quantum.classical((0, 1).into()), Some(0));
+        assert_eq!(quantum.classical((0, 2).into()), Some(1));
+        assert_eq!(quantum.status(), GameStatus::Pending(Player::O));
+        assert_eq!(quantum.play(QuantumMove::new((0, 0).into(), (1, 1).into())), Err(GameError::SpaceOccupied));
+    }
+
+    #[test]
+    fn test_simultaneous_lines() {
+        // O6 closes a cycle, and settling it in 8 settles every other mark, completing
+        // a line for each player. X's highest mark in theirs is X5 and O's is O6, so X
+        // wins a whole point and O gets half of one.
+        let mut quantum = Quantum::new();
+        play(&mut quantum, &[spooky(1, 2), spooky(2, 5), spooky(4, 5), spooky(5, 8), spooky(7, 8), spooky(8, 1)]);
+        assert_eq!(quantum.status(), GameStatus::Pending(Player::X));
+        quantum.collapse((2, 1).into()).expect("Failed to collapse.");
+        assert_eq!(quantum.lines(), vec![(Player::X, 5), (Player::O, 6)]);
+        assert_eq!(quantum.status(), GameStatus::Winner(Player::X));
+        assert_eq!(quantum.half_points(), Some((2, 1)));
+    }
+
+    #[test]
+    fn test_prompt_is_plain_text() {
+        // The prompt is printed as it is, so it names marks without subscripts
+        let mut quantum = Quantum::new();
+        play(&mut quantum, &[spooky(1, 2)]);
+        let mut cursor = Cursor::new(Player::O.mark(), 1, 1);
+        assert_eq!(quantum.prompt(&cursor), Some("Choose two spaces for O2.".to_string()));
+        cursor.select(Some((1, 1).into()));
+        assert_eq!(quantum.prompt(&cursor), Some("Choose the other space for O2.".to_string()));
+    }
+}
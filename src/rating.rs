@@ -0,0 +1,228 @@
+//! Elo ratings for named players and bots, saved between runs. Players are rated under
+//! a key naming their kind as well, like `human:alice` or `bot:python3 a.py`, so two
+//! players only share a rating when they're the same player. Each rules but the standard
+//! ones has a pool of ratings of its own, like `human:alice (quantum)`, since being good
+//! at one game says little about another.
+
+use crate::game::{GameStatus, Player, Rules};
+use crate::storage;
+
+use std::collections::HashMap;
+use std::io::Write;
+
+/// The file, in the data directory, that ratings are saved to
+const RATINGS_FILE: &str = "ratings.tsv";
+
+/// The rating given to a player in their first game
+const INITIAL_RATING: f64 = 1500.0;
+
+/// How far a single game can move a rating
+const K_FACTOR: f64 = 32.0;
+
+
+//--------------------------------------------------------------------------------------
+//-- Rating
+//--------------------------------------------------------------------------------------
+
+/// One player's rating and the number of rated games it's based on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Rating {
+    pub(crate) rating: f64,
+    pub(crate) games: u32,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Rating { rating: INITIAL_RATING, games: 0 }
+    }
+}
+
+/// The key a player is rated under in the pool for `rules`: the key itself for the
+/// standard rules, or the key followed by the rules, e.g. `human:alice (quantum)`
+pub(crate) fn pool_key(key: &str, rules: Rules) -> String {
+    match rules {
+        Rules::Standard => key.to_string(),
+        _ => format!("{} ({})", key, rules),
+    }
+}
+
+/// Can games by these rules be rated? Order and Chaos have different goals, so a win
+/// as one says nothing about a game as the other.
+pub(crate) fn is_rated(rules: Rules) -> bool {
+    rules != Rules::Order
+}
+
+/// X's score from a game that ended with `status`: 1 for a win, 0 for a loss and a
+/// half for a draw. Games that haven't finished have no score.
+pub(crate) fn score(status: &GameStatus) -> Option<f64> {
+    match status {
+        GameStatus::Winner(Player::X) => Some(1.0),
+        GameStatus::Winner(Player::O) => Some(0.0),
+        GameStatus::Draw => Some(0.5),
+        GameStatus::Pending(_) => None,
+    }
+}
+
+/// X's share of the points from a game scored in half points, as (X, O), like quantum
+/// games where a loser can score too
+pub(crate) fn share(half_points: (u8, u8)) -> f64 {
+    let (x, o) = half_points;
+    f64::from(x) / f64::from(x + o)
+}
+
+/// The score a player rated `rating` is expected to take from a game against a player
+/// rated `opponent`, between 0 (a sure loss) and 1 (a sure win)
+fn expected_score(rating: f64, opponent: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent - rating) / 400.0))
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Ratings
+//--------------------------------------------------------------------------------------
+
+/// Every rated player, by key
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Ratings {
+    players: HashMap<String, Rating>,
+}
+
+impl Ratings {
+    /// Load the saved ratings
+    pub(crate) fn load() -> std::io::Result<Self> {
+        Ratings::parse(&storage::read(RATINGS_FILE)?)
+    }
+
+    /// Save the ratings, replacing what was saved before
+    pub(crate) fn save(&self) -> std::io::Result<()> {
+        storage::write(RATINGS_FILE, &self.to_tsv())
+    }
+
+    /// Read ratings from tab separated lines of key, rating and games played
+    fn parse(text: &str) -> std::io::Result<Self> {
+        let invalid = |line: &str| {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Invalid rating: '{}'", line))
+        };
+        let mut players = HashMap::new();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let fields: Vec<_> = line.split('\t').collect();
+            if fields.len() != 3 { return Err(invalid(line)); }
+            let rating = fields[1].parse().map_err(|_| invalid(line))?;
+            let games = fields[2].parse().map_err(|_| invalid(line))?;
+            players.insert(fields[0].to_string(), Rating { rating, games });
+        }
+        Ok(Ratings { players })
+    }
+
+    /// Write ratings as tab separated lines, best first
+    fn to_tsv(&self) -> String {
+        self.leaderboard()
+            .iter()
+            .map(|(name, rating)| format!("{}\t{:.1}\t{}\n", name, rating.rating, rating.games))
+            .collect()
+    }
+
+    /// The rating for a player, who may not have played a rated game yet
+    pub(crate) fn get(&self, name: &str) -> Rating {
+        self.players.get(name).copied().unwrap_or_default()
+    }
+
+    /// Update the ratings of both players after a game between `x` and `o`. Games that
+    /// haven't finished are ignored. Callers must check a player isn't playing itself,
+    /// which can't be rated and is ignored too.
+    pub(crate) fn record_game(&mut self, x: &str, o: &str, status: &GameStatus) {
+        if let Some(x_score) = score(status) {
+            self.record_score(x, o, x_score);
+        }
+    }
+
+    /// Update the ratings of both players after a game where `x` took `x_score`, between
+    /// 0 and 1, and `o` the rest. As with `record_game`, a player can't play itself.
+    pub(crate) fn record_score(&mut self, x: &str, o: &str, x_score: f64) {
+        let (x, o) = (storage::field(x), storage::field(o));
+        if x == o { return; }
+        let (x_rating, o_rating) = (self.get(&x), self.get(&o));
+        let x_change = K_FACTOR * (x_score - expected_score(x_rating.rating, o_rating.rating));
+        self.players.insert(x, Rating { rating: x_rating.rating + x_change, games: x_rating.games + 1 });
+        self.players.insert(o, Rating { rating: o_rating.rating - x_change, games: o_rating.games + 1 });
+    }
+
+    /// Every rated player, highest rating first
+    pub(crate) fn leaderboard(&self) -> Vec<(&String, &Rating)> {
+        let mut players: Vec<_> = self.players.iter().collect();
+        players.sort_by(|a, b| b.1.rating.total_cmp(&a.1.rating).then_with(|| a.0.cmp(b.0)));
+        players
+    }
+
+    /// The leaderboard as the lines of a table, headed by the column names
+    pub(crate) fn table(&self) -> Vec<String> {
+        let players = self.leaderboard();
+        if players.is_empty() {
+            return vec![String::from("No rated games yet.")];
+        }
+        let width = players.iter().map(|(name, _)| name.len()).max().unwrap_or(0).max(6);
+        let mut lines = vec![format!("{:>4}  {:<width$}  {:>6}  {:>6}", "Rank", "Player", "Rating", "Games", width = width)];
+        for (rank, (name, rating)) in players.iter().enumerate() {
+            lines.push(format!(
+                "{:>4}  {:<width$}  {:>6.0}  {:>6}",
+                rank + 1, name, rating.rating, rating.games, width = width,
+            ));
+        }
+        lines
+    }
+
+    /// Print the leaderboard as a table
+    pub(crate) fn print(&self, output: &mut impl Write) -> std::io::Result<()> {
+        for line in self.table() { writeln!(output, "{}", line)?; }
+        Ok(())
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Tests
+//--------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_win_between_equals() {
+        let mut ratings = Ratings::default();
+        ratings.record_game("human:alice", "human:bob", &GameStatus::Winner(Player::X));
+        assert_eq!(ratings.get("human:alice"), Rating { rating: 1516.0, games: 1 });
+        assert_eq!(ratings.get("human:bob"), Rating { rating: 1484.0, games: 1 });
+    }
+
+    #[test]
+    fn test_draw_favors_the_underdog() {
+        let mut ratings = Ratings::default();
+        ratings.record_game("alice", "bob", &GameStatus::Winner(Player::O));
+        ratings.record_game("alice", "bob", &GameStatus::Draw);
+        assert!(ratings.get("alice").rating > 1484.0);
+        ratings.record_game("alice", "bob", &GameStatus::Pending(Player::X));
+        assert_eq!(ratings.get("alice").games, 2);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut ratings = Ratings::default();
+        ratings.record_game("ai:perfect", "First\tEmpty", &GameStatus::Winner(Player::X));
+        let parsed = Ratings::parse(&ratings.to_tsv()).expect("Failed to parse");
+        assert_eq!(parsed, ratings);
+        assert!(Ratings::parse("alice\t1500").is_err());
+    }
+
+    #[test]
+    fn test_pools() {
+        let mut ratings = Ratings::default();
+        let (x, o) = (pool_key("human:alice", Rules::Quantum), pool_key("human:bob", Rules::Quantum));
+        assert_eq!(x, "human:alice (quantum)");
+        // Winning by a whole point to half of one is only two thirds of a win
+        ratings.record_score(&x, &o, share((2, 1)));
+        assert!((ratings.get(&x).rating - 1505.3).abs() < 0.1);
+        assert_eq!(ratings.get("human:alice").games, 0);
+        assert_eq!(pool_key("human:alice", Rules::Standard), "human:alice");
+    }
+}
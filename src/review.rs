@@ -0,0 +1,90 @@
+//! Reviewing a finished game for mistakes: every move that made the outcome under
+//! perfect play worse for the player who made it.
+
+use crate::game::{Game, Move, Player};
+use crate::solver::{Outcome, Solver};
+
+/// A move that turned a win into a draw or loss, or a draw into a loss
+#[derive(Debug)]
+pub(crate) struct Blunder {
+    pub(crate) number: usize,          // Which move of the game it was, counting from 1
+    pub(crate) player: Player,         // Who made it
+    pub(crate) mv: Move,               // The space they marked, and the mark
+    pub(crate) before: Outcome,        // The outcome they could have had
+    pub(crate) after: Outcome,         // The outcome after their move
+    pub(crate) position: Game,         // The game just before the move
+    pub(crate) best: Vec<Move>,        // The moves that would have kept `before`
+}
+
+/// Wins beat draws beat losses, however many moves they take
+fn rank(outcome: Outcome) -> i8 {
+    match outcome {
+        Outcome::Win(_) => 1,
+        Outcome::Draw => 0,
+        Outcome::Loss(_) => -1,
+    }
+}
+
+/// Replay a game's moves and return every blunder, in the order they were made
+pub(crate) fn blunders(game: &Game, solver: &mut Solver) -> Vec<Blunder> {
+    let mut position = Game::with_rules(game.rules()).with_wrap(game.wraps());
+    let mut found = Vec::new();
+    for (idx, mv) in game.history().iter().enumerate() {
+        let before = solver.solve(&position);
+        let after = solver.move_outcomes(&position)
+            .into_iter()
+            .find(|(legal, _)| legal == mv)
+            .map(|(_, outcome)| outcome)
+            .expect("Every move in the history was legal");
+        let next = {
+            let mut next = position.clone();
+            next.play(*mv).expect("Every move in the history was legal");
+            next
+        };
+        if rank(after) < rank(before) {
+            let best = solver.best_moves(&position);
+            let player = position.current_player();
+            found.push(Blunder { number: idx + 1, player, mv: *mv, before, after, position, best });
+        }
+        position = next;
+    }
+    found
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Tests
+//--------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Coordinate;
+
+    fn to_coord_vec(arr: &[(usize, usize)]) -> Vec<Coordinate> {
+        arr.iter().map(|x| Coordinate::from(*x)).collect()
+    }
+
+    #[test]
+    fn test_finds_the_losing_move() {
+        // O's edge reply to a corner opening loses, and X goes on to win
+        let moves = to_coord_vec(&[(0, 0), (0, 1), (1, 1), (2, 2), (1, 0), (2, 1), (2, 0)]);
+        let game = Game::from(&moves).unwrap();
+        let found = blunders(&game, &mut Solver::new());
+        let summary: Vec<_> = found.iter().map(|b| (b.number, b.player)).collect();
+        assert_eq!(summary[0], (2, Player::O));
+        assert_eq!(found[0].before, Outcome::Draw);
+        assert!(matches!(found[0].after, Outcome::Loss(_)));
+        assert!(found[0].best.iter().any(|mv| mv.coord == Coordinate::new(1, 1)));
+    }
+
+    #[test]
+    fn test_perfect_game_has_no_blunders() {
+        let moves = to_coord_vec(&[
+            (1, 1), (0, 0), (0, 1), (2, 1), (1, 0),
+            (1, 2), (2, 0), (0, 2), (2, 2)
+        ]);
+        let game = Game::from(&moves).unwrap();
+        assert!(blunders(&game, &mut Solver::new()).is_empty());
+    }
+}
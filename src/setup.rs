@@ -0,0 +1,469 @@
+//! The setup screen, where the players choose who plays X and who plays O before the
+//! game starts: a guest, one of the saved profiles, a new profile, or the computer.
+//! The rules to play by are chosen here too.
+
+use crate::ai::Level;
+use crate::cursor::Direction;
+use crate::display::{Draw, Glyphs};
+use crate::game::{Player, Rules};
+use crate::input::{read_action, read_edit, Action, Edit};
+use crate::options::{Mode, Options};
+use crate::players::PlayerSpec;
+use crate::profile::Profiles;
+use crate::rating::Ratings;
+use crate::theme::Theme;
+
+use crossterm::{execute, Result};
+use crossterm::cursor::MoveTo;
+use crossterm::style::{Print, ResetColor, SetForegroundColor};
+use crossterm::terminal::{enable_raw_mode, disable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, Clear, ClearType};
+use std::io::Write;
+
+const TOP: u16 = 10;
+const LEFT: u16 = 10;
+
+const INSTRUCTIONS: &str = "Up and down (or j and k) choose X, O or the rules, left \
+    and right (or h and l) change the choice, s shows the leaderboard, Enter starts the \
+    game and Escape quits.";
+
+const LEADERBOARD_INSTRUCTIONS: &str = "Press Enter or Escape to go back.";
+
+/// The most players the leaderboard screen has room for
+const LEADERBOARD_ROWS: usize = 20;
+
+const NAME_INSTRUCTIONS: &str = "Type a name for the new profile, then Enter to choose \
+    it, or Escape to go back.";
+
+/// The choice that asks for a name to create a new profile with
+const NEW_PROFILE: &str = "New profile";
+
+
+//--------------------------------------------------------------------------------------
+//-- Setup
+//--------------------------------------------------------------------------------------
+
+/// The lines of the setup screen that can be changed, from top to bottom
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Field {
+    Side(Player),
+    Rules,
+}
+
+impl Field {
+    const ALL: [Field; 3] = [Field::Side(Player::X), Field::Side(Player::O), Field::Rules];
+}
+
+/// The choices made so far on the setup screen
+pub(crate) struct Setup {
+    choices: Vec<(String, PlayerSpec)>,  // Everyone who can play, with a name to show
+    new_profile: usize,                  // The index of the choice that creates a profile
+    x: usize,                            // The index of the choice playing X
+    o: usize,                            // The index of the choice playing O
+    offered: Vec<Rules>,                 // The rules that can be chosen
+    rules: usize,                        // The index of the rules, in `offered`
+    focus: Field,                        // The line being changed
+    typing: Option<String>,              // The name typed so far for a new profile
+}
+
+impl Setup {
+    /// Start with guests playing both sides by the given rules. The choices are a
+    /// guest, each profile, a new profile, then each level of computer player, though
+    /// only the levels that can play by the chosen rules are offered. Only the rules
+    /// `offers` accepts are offered, starting with the given ones if it does.
+    pub(crate) fn new(profile_names: &[String], rules: Rules, offers: impl Fn(Rules) -> bool) -> Self {
+        let mut choices = vec![(String::from("Guest"), PlayerSpec::Human(None))];
+        for name in profile_names {
+            choices.push((name.clone(), PlayerSpec::Human(Some(name.clone()))));
+        }
+        let new_profile = choices.len();
+        choices.push((String::from(NEW_PROFILE), PlayerSpec::Human(None)));
+        for level in Level::ALL {
+            choices.push((format!("Computer ({})", level), PlayerSpec::Ai(level)));
+        }
+        let offered: Vec<_> = Rules::ALL.iter().copied().filter(|r| offers(*r)).collect();
+        let rules = offered.iter().position(|r| *r == rules).unwrap_or(0);
+        Setup { choices, new_profile, x: 0, o: 0, offered, rules, focus: Field::Side(Player::X), typing: None }
+    }
+
+    /// The first side still waiting for the name of a new profile, if any. The game
+    /// can't start until it has one.
+    pub(crate) fn unnamed(&self) -> Option<Player> {
+        [Player::X, Player::O].iter()
+            .copied()
+            .find(|player| self.selected(*player) == self.new_profile)
+    }
+
+    /// Start typing the name of a new profile for one side
+    pub(crate) fn start_typing(&mut self, player: Player) {
+        self.focus = Field::Side(player);
+        self.typing = Some(String::new());
+    }
+
+    /// Change the name being typed. Returns true when typing is over, whether the name
+    /// was chosen or not. An empty name can't be chosen.
+    pub(crate) fn edit(&mut self, edit: Edit) -> bool {
+        let typed = match &mut self.typing {
+            Some(typed) => typed,
+            None => return true,
+        };
+        match edit {
+            Edit::Type(c) => typed.push(c),
+            Edit::Erase => { typed.pop(); },
+            Edit::Cancel => self.typing = None,
+            Edit::Done => {
+                let name = typed.trim().to_string();
+                if name.is_empty() { return false; }
+                self.typing = None;
+                self.choose_profile(&name);
+            },
+        }
+        self.typing.is_none()
+    }
+
+    /// Choose the profile with this name for the focused side, adding it to the
+    /// choices, just before the new profile, if it isn't there yet
+    fn choose_profile(&mut self, name: &str) {
+        let spec = PlayerSpec::Human(Some(name.to_string()));
+        let idx = match self.choices.iter().position(|(_, choice)| *choice == spec) {
+            Some(idx) => idx,
+            None => {
+                let idx = self.new_profile;
+                self.choices.insert(idx, (name.to_string(), spec));
+                self.new_profile += 1;
+                for selected in [&mut self.x, &mut self.o] {
+                    if *selected >= idx { *selected += 1; }
+                }
+                idx
+            },
+        };
+        match self.focus {
+            Field::Side(Player::X) => self.x = idx,
+            Field::Side(Player::O) => self.o = idx,
+            Field::Rules => {},
+        }
+    }
+
+    /// The index of the choice for one side
+    fn selected(&self, player: Player) -> usize {
+        match player { Player::X => self.x, Player::O => self.o }
+    }
+
+    /// Respond to a cursor movement
+    pub(crate) fn shift(&mut self, direction: Direction) {
+        let fields = Field::ALL.len();
+        let field = Field::ALL.iter().position(|field| *field == self.focus).unwrap_or(0);
+        let (selected, count) = match self.focus {
+            Field::Side(Player::X) => (&mut self.x, self.choices.len()),
+            Field::Side(Player::O) => (&mut self.o, self.choices.len()),
+            Field::Rules => (&mut self.rules, self.offered.len()),
+        };
+        match direction {
+            Direction::Up => self.focus = Field::ALL[(field + fields - 1) % fields],
+            Direction::Down => self.focus = Field::ALL[(field + 1) % fields],
+            Direction::Left => *selected = (*selected + count - 1) % count,
+            Direction::Right => *selected = (*selected + 1) % count,
+        }
+        match self.focus {
+            // Skip the levels that can't play by the rules
+            Field::Side(player) if !self.playable(self.selected(player)) => self.shift(direction),
+            Field::Rules => self.fit_levels(),
+            _ => {},
+        }
+    }
+
+    /// Can the choice at this index play by the chosen rules? Only some levels of
+    /// computer player can't.
+    fn playable(&self, idx: usize) -> bool {
+        match &self.choices[idx].1 {
+            PlayerSpec::Ai(level) => level.plays(self.rules()),
+            _ => true,
+        }
+    }
+
+    /// Move computer players that can't play by newly chosen rules to the strongest
+    /// level that can, e.g. from perfect to hard
+    fn fit_levels(&mut self) {
+        let strongest = PlayerSpec::Ai(Level::strongest(self.rules()));
+        if let Some(idx) = self.choices.iter().position(|(_, spec)| *spec == strongest) {
+            if !self.playable(self.x) { self.x = idx; }
+            if !self.playable(self.o) { self.o = idx; }
+        }
+    }
+
+    /// Who was chosen to play X and O
+    pub(crate) fn specs(&self) -> (PlayerSpec, PlayerSpec) {
+        (self.choices[self.x].1.clone(), self.choices[self.o].1.clone())
+    }
+
+    /// The rules chosen to play by
+    pub(crate) fn rules(&self) -> Rules {
+        self.offered[self.rules]
+    }
+
+    /// The name of the choice for one side, e.g. "Guest", or the name being typed for
+    /// it, e.g. "ali_"
+    fn chosen_name(&self, player: Player) -> String {
+        match &self.typing {
+            Some(typed) if self.focus == Field::Side(player) => format!("{}_", typed),
+            _ => self.choices[self.selected(player)].0.clone(),
+        }
+    }
+
+    /// Describe the choice for one side, e.g. "X: Guest"
+    pub(crate) fn describe(&self, player: Player) -> String {
+        format!("{}: {}", player, self.chosen_name(player))
+    }
+
+    /// Describe the choice for one line of the screen, e.g. "Rules: misere"
+    fn describe_field(&self, field: Field) -> String {
+        match field {
+            Field::Side(player) => self.describe(player),
+            Field::Rules => format!("Rules: {}", self.rules()),
+        }
+    }
+}
+
+impl Draw for Setup {
+    fn draw(&self, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> Result<()> {
+        let mut stdout = std::io::stdout();
+        execute!(stdout, MoveTo(term_col, term_row), SetForegroundColor(theme.label), Print("Who's playing?"))?;
+        for (idx, field) in Field::ALL.iter().enumerate() {
+            let marker = if *field == self.focus { ">" } else { " " };
+            let (label, chosen, color) = match field {
+                Field::Side(player) => (player.to_string(), self.chosen_name(*player), theme.player(*player)),
+                Field::Rules => (String::from("Rules"), self.rules().to_string(), theme.label),
+            };
+            let color = if *field == self.focus { theme.cursor } else { color };
+            let line = glyphs.text(&format!("{} {:<6} < {} >", marker, format!("{}:", label), chosen));
+            execute!(stdout, MoveTo(term_col, term_row + 2 + idx as u16), SetForegroundColor(color), Print(line))?;
+        }
+        let instructions = if self.typing.is_some() { NAME_INSTRUCTIONS } else { INSTRUCTIONS };
+        execute!(stdout, MoveTo(term_col, term_row + 6), SetForegroundColor(theme.label), Print(instructions))?;
+        execute!(stdout, ResetColor)
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Running the setup screen
+//--------------------------------------------------------------------------------------
+
+/// Show the setup screen, as suits the mode, and return who was chosen to play X and
+/// O, and the rules chosen. Only rules the mode can show, and that the rest of the
+/// options make sense for, are offered. Returns None if the players quit instead.
+pub(crate) fn choose(options: &Options, theme: &Theme, glyphs: Glyphs) -> Result<Option<(PlayerSpec, PlayerSpec, Rules)>> {
+    let offers = |rules| options.mode.shows(rules) && options.check_rules(rules).is_ok();
+    let mut setup = Setup::new(&Profiles::load()?.names(), options.rules.unwrap_or_default(), offers);
+    enable_raw_mode()?;
+    let result = match options.mode {
+        Mode::Fullscreen => {
+            execute!(std::io::stdout(), EnterAlternateScreen)?;
+            let result = run_fullscreen(&mut setup, theme, glyphs);
+            execute!(std::io::stdout(), LeaveAlternateScreen)?;
+            result
+        },
+        _ => run_accessible(&mut setup),
+    };
+    disable_raw_mode()?;
+    result.map(|started| {
+        let (x, o) = setup.specs();
+        if started { Some((x, o, setup.rules())) } else { None }
+    })
+}
+
+/// Returns true when the players chose to start, false when they quit
+fn run_fullscreen(setup: &mut Setup, theme: &Theme, glyphs: Glyphs) -> Result<bool> {
+    loop {
+        execute!(std::io::stdout(), Clear(ClearType::All))?;
+        setup.draw(TOP, LEFT, theme, glyphs)?;
+        match read_action()? {
+            Some(Action::Quit) => return Ok(false),
+            Some(Action::Place) => match setup.unnamed() {
+                Some(player) => {
+                    setup.start_typing(player);
+                    loop {
+                        execute!(std::io::stdout(), Clear(ClearType::All))?;
+                        setup.draw(TOP, LEFT, theme, glyphs)?;
+                        if let Some(edit) = read_edit()? {
+                            if setup.edit(edit) { break; }
+                        }
+                    }
+                },
+                None => return Ok(true),
+            },
+            Some(Action::Shift(direction)) => setup.shift(direction),
+            Some(Action::Leaderboard) => show_leaderboard(theme, glyphs)?,
+            _ => {},
+        }
+    }
+}
+
+/// Show the best rated players until Enter or Escape is pressed
+fn show_leaderboard(theme: &Theme, glyphs: Glyphs) -> Result<()> {
+    let mut stdout = std::io::stdout();
+    let table = Ratings::load()?.table();
+    execute!(stdout, Clear(ClearType::All))?;
+    execute!(stdout, MoveTo(LEFT, TOP), SetForegroundColor(theme.label), Print("Leaderboard"))?;
+    for (idx, line) in table.iter().take(LEADERBOARD_ROWS + 1).enumerate() {
+        let color = if idx == 0 { theme.label } else { theme.cursor };
+        execute!(stdout, MoveTo(LEFT, TOP + 2 + idx as u16), SetForegroundColor(color), Print(glyphs.text(line)))?;
+    }
+    let bottom = TOP + 4 + LEADERBOARD_ROWS as u16;
+    execute!(stdout, MoveTo(LEFT, bottom), SetForegroundColor(theme.label), Print(LEADERBOARD_INSTRUCTIONS), ResetColor)?;
+    loop {
+        if let Some(Action::Place) | Some(Action::Quit) = read_action()? { return Ok(()); }
+    }
+}
+
+/// Returns true when the players chose to start, false when they quit
+fn run_accessible(setup: &mut Setup) -> Result<bool> {
+    // Raw mode is on, so the carriage return has to be explicit
+    let say = |line: &str| -> Result<()> {
+        let mut stdout = std::io::stdout();
+        write!(stdout, "{}\r\n", line)?;
+        stdout.flush()
+    };
+    say("Who's playing?")?;
+    say(INSTRUCTIONS)?;
+    say(&setup.describe(Player::X))?;
+    loop {
+        match read_action()? {
+            Some(Action::Quit) => return Ok(false),
+            Some(Action::Place) => match setup.unnamed() {
+                Some(player) => {
+                    // Nothing is echoed as the name is typed, the screen reader hears
+                    // the whole name once it's chosen
+                    setup.start_typing(player);
+                    say(NAME_INSTRUCTIONS)?;
+                    loop {
+                        if let Some(edit) = read_edit()? {
+                            if setup.edit(edit) { break; }
+                        }
+                    }
+                    say(&setup.describe(player))?;
+                },
+                None => return Ok(true),
+            },
+            Some(Action::Shift(direction)) => {
+                setup.shift(direction);
+                say(&setup.describe_field(setup.focus))?;
+            },
+            Some(Action::ReadBoard) => {
+                for field in Field::ALL.iter() { say(&setup.describe_field(*field))?; }
+            },
+            Some(Action::Leaderboard) => {
+                say("Leaderboard")?;
+                let ratings = Ratings::load()?;
+                let players = ratings.leaderboard();
+                if players.is_empty() { say("No rated games yet.")?; }
+                for (rank, (name, rating)) in players.iter().enumerate() {
+                    say(&format!("{}. {}, rated {:.0} after {} games.", rank + 1, name, rating.rating, rating.games))?;
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Tests
+//--------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choosing_players() {
+        let mut setup = Setup::new(&[String::from("alice")], Rules::Standard, |_| true);
+        setup.shift(Direction::Right);
+        setup.shift(Direction::Down);
+        setup.shift(Direction::Left);
+        assert_eq!(setup.describe(Player::X), "X: alice");
+        assert_eq!(setup.describe(Player::O), "O: Computer (perfect)");
+        assert_eq!(setup.specs(), (PlayerSpec::Human(Some("alice".into())), PlayerSpec::Ai(Level::Perfect)));
+
+        // The rules come after O, and going up from X wraps around to them
+        setup.shift(Direction::Down);
+        setup.shift(Direction::Right);
+        assert_eq!(setup.rules(), Rules::Misere);
+        setup.shift(Direction::Down);
+        setup.shift(Direction::Up);
+        setup.shift(Direction::Left);
+        assert_eq!(setup.rules(), Rules::Standard);
+    }
+
+    #[test]
+    fn test_levels_fit_the_rules() {
+        // Hard is skipped on the usual board, and perfect under quantum rules
+        let mut setup = Setup::new(&[], Rules::Standard, |_| true);
+        setup.shift(Direction::Left);
+        setup.shift(Direction::Left);
+        assert_eq!(setup.describe(Player::X), "X: Computer (medium)");
+        setup.shift(Direction::Right);
+        assert_eq!(setup.describe(Player::X), "X: Computer (perfect)");
+
+        // Choosing rules the perfect player can't play moves it to hard
+        setup.shift(Direction::Up);
+        while setup.rules() != Rules::Quantum { setup.shift(Direction::Right); }
+        assert_eq!(setup.describe(Player::X), "X: Computer (hard)");
+        setup.shift(Direction::Down);
+        setup.shift(Direction::Right);
+        assert_eq!(setup.describe(Player::X), "X: Guest");
+    }
+
+    #[test]
+    fn test_accessible_rules() {
+        // Only rules played on the usual board are offered, so going left from the
+        // first wraps around to the last of those
+        let mut setup = Setup::new(&[], Rules::Standard, |rules| Mode::Accessible.shows(rules));
+        setup.shift(Direction::Up);
+        setup.shift(Direction::Left);
+        assert!(setup.rules().is_classical());
+        assert!(Setup::new(&[], Rules::Standard, |_| true).offered.contains(&Rules::Quantum));
+        assert!(!setup.offered.contains(&Rules::Quantum));
+    }
+
+    #[test]
+    fn test_rules_fit_the_options() {
+        // Only rules whose lines can wrap are offered with --wrap, and only gravity and
+        // three players with a 5x5 board
+        let options = Options::parse(vec!["--wrap", "--rules", "numerical"]).expect("Failed to parse");
+        let setup = Setup::new(&[], Rules::Numerical, |rules| options.check_rules(rules).is_ok());
+        assert_eq!(setup.rules(), Rules::Standard);
+        assert!(!setup.offered.contains(&Rules::Numerical));
+        let options = Options::parse(vec!["--size", "5x5"]).expect("Failed to parse");
+        let setup = Setup::new(&[], Rules::Gravity, |rules| options.check_rules(rules).is_ok());
+        assert_eq!(setup.offered, vec![Rules::Gravity, Rules::Three]);
+    }
+
+    #[test]
+    fn test_new_profile() {
+        let mut setup = Setup::new(&[String::from("alice")], Rules::Standard, |_| true);
+        setup.shift(Direction::Down);
+        setup.shift(Direction::Left);
+        assert_eq!(setup.describe(Player::O), "O: Computer (perfect)");
+        setup.shift(Direction::Up);
+        setup.shift(Direction::Right);
+        setup.shift(Direction::Right);
+        assert_eq!(setup.describe(Player::X), "X: New profile");
+        assert_eq!(setup.unnamed(), Some(Player::X));
+
+        // Escape goes back, and an empty name can't be chosen
+        setup.start_typing(Player::X);
+        assert!(setup.edit(Edit::Cancel));
+        setup.start_typing(Player::X);
+        assert!(!setup.edit(Edit::Done));
+        for c in "bobb".chars() { setup.edit(Edit::Type(c)); }
+        setup.edit(Edit::Erase);
+        assert_eq!(setup.describe(Player::X), "X: bob_");
+        assert!(setup.edit(Edit::Done));
+
+        // The new profile is offered from now on, and O's choice stays the same
+        assert_eq!(setup.unnamed(), None);
+        assert_eq!(setup.specs(), (PlayerSpec::Human(Some("bob".into())), PlayerSpec::Ai(Level::Perfect)));
+        setup.shift(Direction::Right);
+        assert_eq!(setup.describe(Player::X), "X: New profile");
+    }
+}
@@ -0,0 +1,353 @@
+//! Solves positions by searching the whole game tree, so that every position can be
+//! given its outcome under perfect play.
+
+use crate::game::{Game, GameStatus, Move, Rules};
+use crate::symmetry::solver_key;
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+
+//--------------------------------------------------------------------------------------
+//-- Outcome
+//--------------------------------------------------------------------------------------
+
+/// The result of a position under perfect play, from the point of view of the player
+/// to move. Wins and losses carry the number of moves (by either player) until the
+/// game ends.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum Outcome {
+    Win(u8),
+    Draw,
+    Loss(u8),
+}
+
+impl Outcome {
+    /// A single number that orders outcomes from worst to best. Quicker wins and
+    /// slower losses are better.
+    fn score(&self) -> i32 {
+        match self {
+            Outcome::Win(n) => 100 - *n as i32,
+            Outcome::Draw => 0,
+            Outcome::Loss(n) => -100 + *n as i32,
+        }
+    }
+
+    /// The same outcome, seen by the other player one move earlier
+    fn flip(&self) -> Self {
+        match self {
+            Outcome::Win(n) => Outcome::Loss(n + 1),
+            Outcome::Draw => Outcome::Draw,
+            Outcome::Loss(n) => Outcome::Win(n + 1),
+        }
+    }
+}
+
+impl Ord for Outcome {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score().cmp(&other.score())
+    }
+}
+
+impl PartialOrd for Outcome {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Outcome::Win(n) => write!(f, "win in {}", n),
+            Outcome::Draw => write!(f, "draw"),
+            Outcome::Loss(n) => write!(f, "loss in {}", n),
+        }
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Solver
+//--------------------------------------------------------------------------------------
+
+/// A minimax search over the game tree that remembers every position it has solved.
+/// Positions that are the same up to symmetry share one entry, since turning or
+/// flipping the board can't change the outcome. When positions can repeat, every
+/// position is solved at once instead, the first time one is asked about.
+#[derive(Default)]
+pub(crate) struct Solver {
+    memo: HashMap<(Rules, bool, u32), Outcome>,               // Solved positions, keyed by their rules, whether lines wrap, and `solver_key`
+    repeating: HashMap<(Rules, bool), HashMap<u32, Outcome>>, // Every position under rules that repeat, keyed by `Game::repetition_key`
+}
+
+impl Solver {
+    pub(crate) fn new() -> Self {
+        Solver::default()
+    }
+
+    /// The outcome of a position for the player to move
+    pub(crate) fn solve(&mut self, game: &Game) -> Outcome {
+        match game.status() {
+            // Under misere rules, the player who made the last move can lose with it
+            GameStatus::Winner(player) if player == game.current_player() => return Outcome::Win(0),
+            GameStatus::Winner(_) => return Outcome::Loss(0),
+            GameStatus::Draw => return Outcome::Draw,
+            GameStatus::Pending(_) => {},
+        }
+        if game.rules().can_repeat() {
+            let outcomes = self.repeating.entry((game.rules(), game.wraps()))
+                .or_insert_with(|| solve_repeating(game.rules(), game.wraps()));
+            return outcomes[&game.repetition_key()];
+        }
+        let key = (game.rules(), game.wraps(), solver_key(game));
+        if let Some(outcome) = self.memo.get(&key) { return *outcome; }
+
+        // Nothing beats winning right away, so look for that before searching deeper.
+        // Under numerical rules it's quick to spot, and so is a line the player must
+        // block, which cuts the search down a lot.
+        let mut moves = game.legal_moves();
+        if game.rules() == Rules::Numerical {
+            let player = game.current_player();
+            let mut opponent = player;
+            opponent.toggle();
+            let forced = if !game.fifteen_threats(player).is_empty() {
+                Some(Outcome::Win(1))
+            } else {
+                match game.fifteen_threats(opponent).as_slice() {
+                    [] => None,
+                    [space] => { moves.retain(|mv| mv.coord == *space); None },
+                    _ => Some(Outcome::Loss(2)),
+                }
+            };
+            if let Some(outcome) = forced {
+                self.memo.insert(key, outcome);
+                return outcome;
+            }
+        }
+        let children: Vec<_> = moves
+            .into_iter()
+            .map(|mv| {
+                let mut next = game.clone();
+                next.play(mv).expect("Legal moves can be played");
+                next
+            })
+            .collect();
+        let outcome = if children.iter().any(|next| next.get_winner() == Some(game.current_player())) {
+            Outcome::Win(1)
+        } else {
+            children.iter()
+                .map(|next| self.solve(next).flip())
+                .max()
+                .expect("A pending game has at least one move")
+        };
+        self.memo.insert(key, outcome);
+        outcome
+    }
+
+    /// The outcome of each legal move, for the player making it
+    pub(crate) fn move_outcomes(&mut self, game: &Game) -> Vec<(Move, Outcome)> {
+        game.legal_moves()
+            .into_iter()
+            .map(|mv| {
+                let mut next = game.clone();
+                next.play(mv).expect("Legal moves can be played");
+                (mv, self.solve(&next).flip())
+            })
+            .collect()
+    }
+
+    /// Suggest a move for the player to move, with the outcome it leads to. Asking
+    /// again about the same position cycles through every best move in turn, starting
+    /// after `previous`.
+    pub(crate) fn hint(&mut self, game: &Game, previous: Option<Move>) -> Option<(Move, Outcome)> {
+        let best = self.best_moves(game);
+        let next = previous
+            .and_then(|prev| best.iter().position(|mv| *mv == prev))
+            .map(|idx| (idx + 1) % best.len())
+            .unwrap_or(0);
+        let mv = *best.get(next)?;
+        Some((mv, self.solve(game)))
+    }
+
+    /// Every move that achieves the best outcome for the player to move
+    pub(crate) fn best_moves(&mut self, game: &Game) -> Vec<Move> {
+        let outcomes = self.move_outcomes(game);
+        let best = outcomes.iter().map(|(_, outcome)| *outcome).max();
+        outcomes.into_iter()
+            .filter(|(_, outcome)| Some(*outcome) == best)
+            .map(|(mv, _)| mv)
+            .collect()
+    }
+}
+
+/// Work out the outcome of every position that can come up under rules where positions
+/// repeat, starting from the positions where the game is over and working backwards a
+/// move at a time. Searching forwards, as `solve` does, would go round in circles.
+/// Positions that are never settled are draws: neither player can force a win.
+fn solve_repeating(rules: Rules, wrap: bool) -> HashMap<u32, Outcome> {
+    // Find every position that can come up, and the positions each move leads to.
+    // Repetition draws depend on the history, so only lines end the game here.
+    let mut outcomes = HashMap::new();
+    let mut unsettled = Vec::new();
+    let start = Game::with_rules(rules).with_wrap(wrap);
+    let mut seen = HashSet::from([start.repetition_key()]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(game) = queue.pop_front() {
+        let key = game.repetition_key();
+        match game.get_winner() {
+            Some(winner) if winner == game.current_player() => { outcomes.insert(key, Outcome::Win(0)); },
+            Some(_) => { outcomes.insert(key, Outcome::Loss(0)); },
+            None => {
+                let mut next = Vec::new();
+                for mv in game.legal_moves() {
+                    let mut child = game.clone();
+                    child.play(mv).expect("Legal moves can be played");
+                    next.push(child.repetition_key());
+                    if seen.insert(child.repetition_key()) { queue.push_back(child); }
+                }
+                unsettled.push((key, next));
+            },
+        }
+    }
+
+    // Each round settles the positions won or lost in one more move than the last
+    loop {
+        let mut settled = Vec::new();
+        for (key, next) in unsettled.iter() {
+            let known: Vec<_> = next.iter().map(|key| outcomes.get(key).copied()).collect();
+            let quickest_win = known.iter()
+                .filter_map(|outcome| match outcome { Some(Outcome::Loss(n)) => Some(*n), _ => None })
+                .min();
+            if let Some(n) = quickest_win {
+                settled.push((*key, Outcome::Win(n + 1)));
+            } else if known.iter().all(|outcome| matches!(outcome, Some(Outcome::Win(_)))) {
+                let slowest_loss = known.iter()
+                    .filter_map(|outcome| match outcome { Some(Outcome::Win(n)) => Some(*n), _ => None })
+                    .max()
+                    .unwrap_or(0);
+                settled.push((*key, Outcome::Loss(slowest_loss + 1)));
+            }
+        }
+        if settled.is_empty() { break; }
+        outcomes.extend(settled);
+        unsettled.retain(|(key, _)| !outcomes.contains_key(key));
+    }
+    outcomes.extend(unsettled.into_iter().map(|(key, _)| (key, Outcome::Draw)));
+    outcomes
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Tests
+//--------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Coordinate, Mark};
+
+    fn to_coord_vec(arr: &[(usize, usize)]) -> Vec<Coordinate> {
+        arr.iter().map(|x| Coordinate::from(*x)).collect()
+    }
+
+    fn coords(moves: &[Move]) -> Vec<Coordinate> {
+        moves.iter().map(|mv| mv.coord).collect()
+    }
+
+    #[test]
+    fn test_empty_board_is_a_draw() {
+        assert_eq!(Solver::new().solve(&Game::new()), Outcome::Draw);
+    }
+
+    #[test]
+    fn test_finds_the_winning_move() {
+        // X to move, and can win at (0, 2)
+        let game = Game::from(&to_coord_vec(&[(0, 0), (1, 0), (0, 1), (1, 1)])).unwrap();
+        let mut solver = Solver::new();
+        assert_eq!(solver.solve(&game), Outcome::Win(1));
+        assert_eq!(coords(&solver.best_moves(&game)), to_coord_vec(&[(0, 2)]));
+    }
+
+    #[test]
+    fn test_hint_cycles_through_best_moves() {
+        // O must block at (0, 2), the only move that doesn't lose right away
+        let game = Game::from(&to_coord_vec(&[(0, 0), (1, 1), (0, 1)])).unwrap();
+        let mut solver = Solver::new();
+        let (mv, outcome) = solver.hint(&game, None).unwrap();
+        assert_eq!((mv.coord, outcome), (Coordinate::new(0, 2), Outcome::Draw));
+        assert_eq!(solver.hint(&game, Some(mv)).unwrap().0, mv);
+
+        // Every opening move draws with perfect play
+        let empty = Game::new();
+        let first = solver.hint(&empty, None).unwrap().0;
+        let second = solver.hint(&empty, Some(first)).unwrap().0;
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_misere() {
+        // Still a draw, but only if X opens in the center
+        let game = Game::with_rules(Rules::Misere);
+        let mut solver = Solver::new();
+        assert_eq!(solver.solve(&game), Outcome::Draw);
+        assert_eq!(coords(&solver.best_moves(&game)), to_coord_vec(&[(1, 1)]));
+    }
+
+    #[test]
+    fn test_notakto() {
+        // On a single board, the first player wins by taking the center
+        let game = Game::with_rules(Rules::Notakto);
+        let mut solver = Solver::new();
+        assert!(matches!(solver.solve(&game), Outcome::Win(_)));
+        assert_eq!(coords(&solver.best_moves(&game)), to_coord_vec(&[(1, 1)]));
+    }
+
+    #[test]
+    fn test_morris() {
+        // Three men's morris is a win for the first player
+        let game = Game::with_rules(Rules::Morris);
+        let mut solver = Solver::new();
+        assert!(matches!(solver.solve(&game), Outcome::Win(_)));
+    }
+
+    #[test]
+    fn test_infinite() {
+        // Unlike the standard game, infinite tic-tac-toe is a win for the first player
+        let game = Game::with_rules(Rules::Infinite);
+        let mut solver = Solver::new();
+        assert!(matches!(solver.solve(&game), Outcome::Win(_)));
+    }
+
+    #[test]
+    fn test_wild() {
+        // The first player wins, by opening in the center with either mark
+        let game = Game::with_rules(Rules::Wild);
+        let mut solver = Solver::new();
+        assert!(matches!(solver.solve(&game), Outcome::Win(_)));
+        assert_eq!(coords(&solver.best_moves(&game)), to_coord_vec(&[(1, 1), (1, 1)]));
+    }
+
+    #[test]
+    fn test_numerical() {
+        // Opening with 5 in the center draws
+        let mut game = Game::with_rules(Rules::Numerical);
+        game.play(Move::new((1, 1).into(), Mark::Number(5))).unwrap();
+        let mut solver = Solver::new();
+        assert_eq!(solver.solve(&game), Outcome::Draw);
+
+        // X threatens 3 + 5 + 7 along the top row, and O must put something in the way
+        let mut game = Game::with_rules(Rules::Numerical);
+        for (coord, n) in [((0, 0), 3), ((1, 0), 2), ((0, 1), 5)] {
+            game.play(Move::new(coord.into(), Mark::Number(n))).unwrap();
+        }
+        let best = coords(&solver.best_moves(&game));
+        assert!(!best.is_empty() && best.iter().all(|coord| *coord == Coordinate::new(0, 2)));
+    }
+
+    #[test]
+    fn test_edge_opening_loses_for_o() {
+        // After X takes the center, O on an edge loses to perfect play
+        let game = Game::from(&to_coord_vec(&[(1, 1), (0, 1)])).unwrap();
+        assert!(matches!(Solver::new().solve(&game), Outcome::Win(_)));
+    }
+}
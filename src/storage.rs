@@ -0,0 +1,44 @@
+//! Where the game keeps the files it saves between runs
+
+use std::path::PathBuf;
+
+/// The directory for saved files. `TICTACTERMINAL_HOME` overrides the default, which
+/// follows the XDG base directory convention, or uses `%APPDATA%` on Windows.
+pub(crate) fn data_dir() -> std::io::Result<PathBuf> {
+    let var = |name| std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+    let dir = var("TICTACTERMINAL_HOME")
+        .or_else(|| var("XDG_DATA_HOME").map(|dir| dir.join("tictacterminal")))
+        .or_else(|| var("APPDATA").map(|dir| dir.join("tictacterminal")))
+        .or_else(|| var("HOME").map(|dir| dir.join(".local").join("share").join("tictacterminal")))
+        .ok_or_else(|| std::io::Error::other("Couldn't find a directory to save files in, set TICTACTERMINAL_HOME"))?;
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// The path to a saved file, making sure its directory exists
+pub(crate) fn path(file_name: &str) -> std::io::Result<PathBuf> {
+    Ok(data_dir()?.join(file_name))
+}
+
+/// Read a saved file, or an empty string if it hasn't been saved yet
+pub(crate) fn read(file_name: &str) -> std::io::Result<String> {
+    match std::fs::read_to_string(path(file_name)?) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        result => result,
+    }
+}
+
+/// A name made safe to save as a field of a tab separated line, with tabs and line
+/// breaks, which would corrupt the file, replaced by spaces
+pub(crate) fn field(name: &str) -> String {
+    name.replace(['\t', '\n', '\r'], " ")
+}
+
+/// Replace a saved file. Writes to a temporary file first, so an interrupted save
+/// doesn't lose what was there before.
+pub(crate) fn write(file_name: &str, contents: &str) -> std::io::Result<()> {
+    let path = path(file_name)?;
+    let temp = path.with_extension("tmp");
+    std::fs::write(&temp, contents)?;
+    std::fs::rename(&temp, &path)
+}
@@ -0,0 +1,206 @@
+//! The 8 symmetries of the square (rotations and reflections), and the canonical form
+//! of a position under them. Positions that are the same up to symmetry have the same
+//! canonical key, which is stable from run to run.
+
+use crate::game::{Coordinate, Game, Mark, Rules};
+
+
+//--------------------------------------------------------------------------------------
+//-- Symmetry
+//--------------------------------------------------------------------------------------
+
+/// One of the 8 ways to turn or flip the board onto itself
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum Symmetry {
+    Identity,
+    Rotate90,        // A quarter turn clockwise
+    Rotate180,
+    Rotate270,
+    FlipVertical,    // Mirror left to right, across the vertical axis
+    FlipHorizontal,  // Mirror top to bottom, across the horizontal axis
+    FlipDiagonal,    // Mirror across the top left to bottom right diagonal
+    FlipAntiDiagonal,// Mirror across the top right to bottom left diagonal
+}
+
+impl Symmetry {
+    pub(crate) const ALL: [Symmetry; 8] = [
+        Symmetry::Identity,
+        Symmetry::Rotate90,
+        Symmetry::Rotate180,
+        Symmetry::Rotate270,
+        Symmetry::FlipVertical,
+        Symmetry::FlipHorizontal,
+        Symmetry::FlipDiagonal,
+        Symmetry::FlipAntiDiagonal,
+    ];
+
+    /// Where a space ends up when the board is transformed
+    pub(crate) fn apply(&self, coord: Coordinate) -> Coordinate {
+        let (row, col) = coord.into();
+        let (row, col) = match self {
+            Symmetry::Identity => (row, col),
+            Symmetry::Rotate90 => (col, 2 - row),
+            Symmetry::Rotate180 => (2 - row, 2 - col),
+            Symmetry::Rotate270 => (2 - col, row),
+            Symmetry::FlipVertical => (row, 2 - col),
+            Symmetry::FlipHorizontal => (2 - row, col),
+            Symmetry::FlipDiagonal => (col, row),
+            Symmetry::FlipAntiDiagonal => (2 - col, 2 - row),
+        };
+        Coordinate::new(row, col)
+    }
+
+    /// The symmetry that undoes this one
+    #[cfg(test)]
+    pub(crate) fn inverse(&self) -> Symmetry {
+        match self {
+            Symmetry::Rotate90 => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            other => *other,
+        }
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Position keys
+//--------------------------------------------------------------------------------------
+
+/// A number identifying the marks on the board, reading each space as a base-3 digit
+/// (0 for empty, 1 for X, 2 for O) from the top left, row by row. Under numerical
+/// rules each space is a base-10 digit instead, the number in it or 0. The player to
+/// move follows from the number of marks, so it isn't part of the key.
+pub(crate) fn position_key(game: &Game) -> u32 {
+    key_under(game, Symmetry::Identity, false)
+}
+
+/// The position key of the board after transforming it, and under numerical rules,
+/// swapping every number n for 10 - n if `complement` is set
+fn key_under(game: &Game, symmetry: Symmetry, complement: bool) -> u32 {
+    let mut digits = [0; 9];
+    for space in game.iter() {
+        let (row, col) = symmetry.apply(space.get_coordinate()).into();
+        digits[row * 3 + col] = match space.get_mark() {
+            None => 0,
+            Some(Mark::X) => 1,
+            Some(Mark::O) => 2,
+            Some(Mark::Number(n)) if complement => 10 - n as u32,
+            Some(Mark::Number(n)) => n as u32,
+        };
+    }
+    let base = if game.rules() == Rules::Numerical { 10 } else { 3 };
+    digits.iter().fold(0, |key, digit| key * base + digit)
+}
+
+/// The canonical form of a position: the smallest key of any of its 8 transformations,
+/// and the symmetry that produces it
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) struct Canonical {
+    pub(crate) key: u32,
+    pub(crate) symmetry: Symmetry,
+}
+
+/// Find the canonical form of a position. Ties go to the first symmetry in
+/// `Symmetry::ALL`, so the result never depends on anything but the marks.
+pub(crate) fn canonicalize(game: &Game) -> Canonical {
+    Symmetry::ALL.iter()
+        .map(|symmetry| Canonical { key: key_under(game, *symmetry, false), symmetry: *symmetry })
+        .min_by_key(|canonical| canonical.key)
+        .expect("There are always 8 symmetries")
+}
+
+/// The key the solver remembers a position by: its canonical key, or under numerical
+/// rules the smaller of that and the canonical key with every number n swapped for
+/// 10 - n. The swap keeps odd numbers odd, even numbers even, and lines summing to 15,
+/// so it can't change the outcome either.
+pub(crate) fn solver_key(game: &Game) -> u32 {
+    let key = canonicalize(game).key;
+    if game.rules() != Rules::Numerical { return key; }
+    Symmetry::ALL.iter()
+        .map(|symmetry| key_under(game, *symmetry, true))
+        .fold(key, u32::min)
+}
+
+/// The game transformed into its canonical form, with the same moves in the same order
+#[cfg(test)]
+pub(crate) fn canonical_game(game: &Game) -> Game {
+    use crate::game::Move;
+
+    let symmetry = canonicalize(game).symmetry;
+    let mut canonical = Game::with_rules(game.rules()).with_wrap(game.wraps());
+    for mv in game.history() {
+        let mv = Move { coord: symmetry.apply(mv.coord), from: mv.from.map(|from| symmetry.apply(from)), ..*mv };
+        canonical.play(mv).expect("Transformed moves are still unique");
+    }
+    canonical
+}
+
+/// Are two positions the same, up to turning and flipping the board?
+#[cfg(test)]
+pub(crate) fn same_position(a: &Game, b: &Game) -> bool {
+    canonicalize(a).key == canonicalize(b).key
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Tests
+//--------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(moves: &[(usize, usize)]) -> Game {
+        let moves: Vec<_> = moves.iter().map(|x| Coordinate::from(*x)).collect();
+        Game::from(&moves).expect("Failed to create game.")
+    }
+
+    #[test]
+    fn test_inverse_undoes_each_symmetry() {
+        for symmetry in Symmetry::ALL.iter() {
+            for row in 0..3 {
+                for col in 0..3 {
+                    let coord = Coordinate::new(row, col);
+                    assert_eq!(symmetry.inverse().apply(symmetry.apply(coord)), coord);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_corner_openings_are_the_same() {
+        let corners = [game(&[(0, 0)]), game(&[(0, 2)]), game(&[(2, 0)]), game(&[(2, 2)])];
+        for corner in corners.iter() {
+            assert!(same_position(&corners[0], corner));
+        }
+        assert!(!same_position(&corners[0], &game(&[(0, 1)])));
+        assert!(!same_position(&corners[0], &game(&[(1, 1)])));
+    }
+
+    #[test]
+    fn test_canonical_game() {
+        let original = game(&[(2, 2), (1, 1), (2, 1)]);
+        let canonical = canonical_game(&original);
+        assert_eq!(position_key(&canonical), canonicalize(&original).key);
+        assert_eq!(canonicalize(&canonical).key, canonicalize(&original).key);
+        assert_eq!(canonical.history().len(), 3);
+    }
+
+    #[test]
+    fn test_positions_up_to_symmetry() {
+        // There are 3 distinct openings, and 12 distinct positions after two moves
+        let mut keys = std::collections::HashSet::new();
+        for first in Game::new().available_moves() {
+            keys.insert(canonicalize(&Game::from(&[first]).unwrap()).key);
+        }
+        assert_eq!(keys.len(), 3);
+        keys.clear();
+        for first in Game::new().available_moves() {
+            let after_first = Game::from(&[first]).unwrap();
+            for second in after_first.available_moves() {
+                keys.insert(canonicalize(&Game::from(&[first, second]).unwrap()).key);
+            }
+        }
+        assert_eq!(keys.len(), 12);
+    }
+}
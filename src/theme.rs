@@ -0,0 +1,153 @@
+//! Color themes used to draw the game. Every color that appears on screen comes from
+//! a `Theme`, so swapping the theme re-colors the whole game.
+
+use crate::game::{Mark, Player};
+use crate::three::Side;
+
+use crossterm::style::Color;
+
+
+//--------------------------------------------------------------------------------------
+//-- Theme
+//--------------------------------------------------------------------------------------
+
+/// The colors used for each of the elements drawn on screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Theme {
+    pub(crate) x: Color,       // Player::X and the X mark
+    pub(crate) o: Color,       // Player::O and the O mark
+    pub(crate) y: Color,       // The third player's Y mark, in three-player games
+    pub(crate) cursor: Color,  // The cursor and the 'ghost' mark inside it
+    pub(crate) grid: Color,    // The game board grid lines
+    pub(crate) label: Color,   // Plain status text, like 'Player'
+    pub(crate) win: Color,     // The 'Wins!' banner
+    pub(crate) draw: Color,    // The 'Try Again' banner
+}
+
+impl Theme {
+    /// The original colors, best viewed on a terminal with a dark background
+    pub(crate) fn dark() -> Self {
+        Theme {
+            x: Color::DarkCyan,
+            o: Color::DarkMagenta,
+            y: Color::Blue,
+            cursor: Color::DarkYellow,
+            grid: Color::Grey,
+            label: Color::Grey,
+            win: Color::DarkGreen,
+            draw: Color::DarkRed,
+        }
+    }
+
+    /// Darker colors that stay readable on a terminal with a light background
+    pub(crate) fn light() -> Self {
+        Theme {
+            x: Color::DarkBlue,
+            o: Color::DarkRed,
+            y: Color::DarkCyan,
+            cursor: Color::DarkYellow,
+            grid: Color::DarkGrey,
+            label: Color::Black,
+            win: Color::DarkGreen,
+            draw: Color::DarkMagenta,
+        }
+    }
+
+    /// Bright, saturated colors for maximum contrast against a dark background
+    pub(crate) fn high_contrast() -> Self {
+        Theme {
+            x: Color::Cyan,
+            o: Color::Magenta,
+            y: Color::Blue,
+            cursor: Color::Yellow,
+            grid: Color::White,
+            label: Color::White,
+            win: Color::Green,
+            draw: Color::Red,
+        }
+    }
+
+    /// Colors from the Okabe-Ito palette, which stay distinguishable under the common
+    /// forms of color blindness. X and O are blue and orange, and Y reddish purple.
+    pub(crate) fn colorblind() -> Self {
+        Theme {
+            x: Color::Rgb { r: 0, g: 114, b: 178 },
+            o: Color::Rgb { r: 230, g: 159, b: 0 },
+            y: Color::Rgb { r: 204, g: 121, b: 167 },
+            cursor: Color::Rgb { r: 240, g: 228, b: 66 },
+            grid: Color::Grey,
+            label: Color::Grey,
+            win: Color::Rgb { r: 0, g: 158, b: 115 },
+            draw: Color::Rgb { r: 213, g: 94, b: 0 },
+        }
+    }
+
+    /// No colors at all, everything is drawn in the terminal's default color
+    pub(crate) fn no_color() -> Self {
+        Theme {
+            x: Color::Reset,
+            o: Color::Reset,
+            y: Color::Reset,
+            cursor: Color::Reset,
+            grid: Color::Reset,
+            label: Color::Reset,
+            win: Color::Reset,
+            draw: Color::Reset,
+        }
+    }
+
+    /// The color of the marks placed by a player
+    pub(crate) fn player(&self, player: Player) -> Color {
+        match player {
+            Player::X => self.x,
+            Player::O => self.o,
+        }
+    }
+
+    /// The color of the marks placed by one of the players of a three-player game
+    pub(crate) fn side(&self, side: Side) -> Color {
+        match side {
+            Side::X => self.x,
+            Side::O => self.o,
+            Side::Y => self.y,
+        }
+    }
+
+    /// The color of a mark, whoever placed it
+    pub(crate) fn mark(&self, mark: Mark) -> Color {
+        match mark {
+            Mark::X => self.x,
+            Mark::O => self.o,
+            Mark::Number(n) if n % 2 == 1 => self.x,
+            Mark::Number(_) => self.o,
+        }
+    }
+
+    /// Look up a theme preset by the name used on the command line
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Theme::dark()),
+            "light" => Some(Theme::light()),
+            "high-contrast" => Some(Theme::high_contrast()),
+            "colorblind" => Some(Theme::colorblind()),
+            "none" => Some(Theme::no_color()),
+            _ => None,
+        }
+    }
+
+    /// The theme to use when none was requested. Honors the `NO_COLOR` convention
+    /// (https://no-color.org): if it is set to anything but an empty string, colors
+    /// are turned off.
+    pub(crate) fn from_env() -> Self {
+        match std::env::var_os("NO_COLOR") {
+            Some(value) if !value.is_empty() => Theme::no_color(),
+            _ => Theme::dark(),
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
@@ -0,0 +1,397 @@
+//! Tic-tac-toe for three players: X, O and Y take turns round the table on a 4x4 or
+//! 5x5 board, and the first to make three in a row wins. Blocking one opponent's line
+//! can leave the other free to finish theirs, so nobody is ever safe for long.
+
+use crate::ai::Ai;
+use crate::cursor::Cursor;
+use crate::describe;
+use crate::display::{small_grid, BIG_X, BIG_O, BIG_Y, SMALL_CURSOR, SMALL_X, SMALL_O, SMALL_Y, SMALL_ROW_HEIGHT, SMALL_COL_WIDTH, Draw, DrawWithColor, Glyphs};
+use crate::error::{GameError, Result};
+use crate::game::{Coordinate, GameStatus, Mark, Player, Rules};
+use crate::input::Action;
+use crate::lines::{self, windows};
+use crate::plain;
+use crate::theme::Theme;
+use crate::variant::Variant;
+
+/// The number of spaces along each side of the boards three players can play on
+pub(crate) const SIZES: std::ops::RangeInclusive<usize> = 4..=5;
+
+/// The number of spaces along each side of the board, unless another size is chosen
+pub(crate) const DEFAULT_SIZE: usize = 4;
+
+/// How many marks in a row win
+const RUN: usize = 3;
+
+/// How many moves ahead the hard computer player looks
+const DEPTH: usize = 4;
+
+
+//--------------------------------------------------------------------------------------
+//-- Sides
+//--------------------------------------------------------------------------------------
+
+/// The three players, in the order they take turns. X and O are as usual, and Y is
+/// the third.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub(crate) enum Side { X, O, Y }
+
+impl Side {
+    /// Pass the turn on to the next player round the table
+    pub(crate) fn next(&mut self) {
+        *self = match self {
+            Side::X => Side::O,
+            Side::O => Side::Y,
+            Side::Y => Side::X,
+        };
+    }
+
+    /// The mark this player places on the small grid
+    pub(crate) fn glyph(&self) -> [&'static str; 3] {
+        match self {
+            Side::X => SMALL_X,
+            Side::O => SMALL_O,
+            Side::Y => SMALL_Y,
+        }
+    }
+}
+
+impl std::fmt::Display for Side {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Side::X => write!(f, "X"),
+            Side::O => write!(f, "O"),
+            Side::Y => write!(f, "Y"),
+        }
+    }
+}
+
+impl From<Player> for Side {
+    fn from(player: Player) -> Self {
+        match player {
+            Player::X => Side::X,
+            Player::O => Side::O,
+        }
+    }
+}
+
+impl Draw for Side {
+    fn draw(&self, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> crossterm::Result<()> {
+        let img = match self { Side::X => BIG_X, Side::O => BIG_O, Side::Y => BIG_Y };
+        img.draw_with_color(term_row, term_col, theme.side(*self), theme, glyphs)
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Three-player game
+//--------------------------------------------------------------------------------------
+
+/// A game for three players
+#[derive(Debug, Clone)]
+pub(crate) struct Three {
+    size: usize,                     // The number of spaces along each side of the board
+    board: Vec<Vec<Option<Side>>>,   // Whose mark is in each space, row by row
+    moves: Vec<Coordinate>,          // Every mark so far, in order. X made every third from the first.
+    side: Side,                      // The current player
+}
+
+impl Three {
+    pub(crate) fn new(size: usize) -> Self {
+        Three { size, board: vec![vec![None; size]; size], moves: Vec::new(), side: Side::X }
+    }
+
+    /// The number of spaces along each side of the board
+    pub(crate) fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Return the current player
+    pub(crate) fn current_side(&self) -> Side {
+        self.side
+    }
+
+    /// Return every mark placed so far, in order
+    pub(crate) fn history(&self) -> &[Coordinate] {
+        &self.moves
+    }
+
+    /// Whose mark is in a space, if anyone's
+    pub(crate) fn get_mark(&self, coord: Coordinate) -> Option<Side> {
+        let (row, col) = coord.into();
+        self.board[row][col]
+    }
+
+    /// Every empty space, left to right, top to bottom
+    pub(crate) fn available_moves(&self) -> Vec<Coordinate> {
+        (0..self.size * self.size)
+            .map(|idx| Coordinate::new(idx / self.size, idx % self.size))
+            .filter(|coord| self.get_mark(*coord).is_none())
+            .collect()
+    }
+
+    /// Place the current player's mark in an empty space, and pass the turn on.
+    /// Returns an error if the space is occupied or off the board.
+    pub(crate) fn play(&mut self, coord: Coordinate) -> Result<()> {
+        let (row, col) = coord.into();
+        if row >= self.size || col >= self.size { return Err(GameError::OffBoard); }
+        if self.board[row][col].is_some() { return Err(GameError::SpaceOccupied); }
+        self.board[row][col] = Some(self.side);
+        self.moves.push(coord);
+        self.side.next();
+        Ok(())
+    }
+
+    /// The game after a mark is placed in a space, which must be empty
+    pub(crate) fn after(&self, coord: Coordinate) -> Three {
+        let mut next = self.clone();
+        next.play(coord).expect("Empty spaces can be played");
+        next
+    }
+
+    /// The winning three in a row, if there is one. The game ends with the first, so
+    /// it's always through the last mark placed.
+    pub(crate) fn line(&self) -> Option<Vec<Coordinate>> {
+        let last = *self.moves.last()?;
+        let owner = self.get_mark(last);
+        windows(self.size, self.size, RUN).into_iter()
+            .filter(|window| window.contains(&last))
+            .find(|window| window.iter().all(|coord| self.get_mark(*coord) == owner))
+    }
+
+    /// The empty spaces where a player's mark would make three in a row
+    pub(crate) fn threats(&self, side: Side) -> Vec<Coordinate> {
+        let mut threats = Vec::new();
+        for window in windows(self.size, self.size, RUN) {
+            let theirs = window.iter().filter(|coord| self.get_mark(**coord) == Some(side)).count();
+            let gap = window.iter().find(|coord| self.get_mark(**coord).is_none());
+            if let (2, Some(gap)) = (theirs, gap) {
+                if !threats.contains(gap) { threats.push(*gap); }
+            }
+        }
+        threats
+    }
+
+    /// Determine and return the current status of the game
+    pub(crate) fn status(&self) -> GameStatus<Side> {
+        if let Some(line) = self.line() {
+            return GameStatus::Winner(self.get_mark(line[0]).expect("A line is made of marks"));
+        }
+        if self.moves.len() == self.size * self.size { return GameStatus::Draw; }
+        GameStatus::Pending(self.side)
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Search
+//--------------------------------------------------------------------------------------
+
+/// Score the game for `me`, looking `depth` moves ahead. The search is paranoid: it
+/// takes the other two players to be working together against `me`, which turns the
+/// game into one for two sides, so alpha-beta pruning still works. Wins score higher
+/// the sooner they come.
+fn paranoid(three: &Three, windows: &[Vec<Coordinate>], me: Side, depth: usize, mut alpha: i32, mut beta: i32) -> i32 {
+    let side = match three.status() {
+        GameStatus::Winner(winner) if winner == me => return 1_000_000 + depth as i32,
+        GameStatus::Winner(_) => return -(1_000_000 + depth as i32),
+        GameStatus::Draw => return 0,
+        GameStatus::Pending(_) if depth == 0 => {
+            // Runs of only `me`'s marks count for, and of only one opponent's against
+            return lines::openness(windows, |coord| three.get_mark(coord), |side| if *side == me { 1 } else { -1 });
+        },
+        GameStatus::Pending(side) => side,
+    };
+    let mut best = if side == me { i32::MIN } else { i32::MAX };
+    for coord in by_center(three) {
+        let score = paranoid(&three.after(coord), windows, me, depth - 1, alpha, beta);
+        if side == me {
+            best = best.max(score);
+            alpha = alpha.max(score);
+        } else {
+            best = best.min(score);
+            beta = beta.min(score);
+        }
+        if alpha >= beta { break; }
+    }
+    best
+}
+
+/// The empty spaces, those nearest the middle first, since they're part of the most
+/// runs
+fn by_center(three: &Three) -> Vec<Coordinate> {
+    let size = three.size();
+    let mut spaces = three.available_moves();
+    spaces.sort_by_key(|coord| {
+        let (row, col) = coord.get_tuple();
+        lines::from_center(row, size) + lines::from_center(col, size)
+    });
+    spaces
+}
+
+/// The spaces that look best for the player to move, searching a few moves ahead
+pub(crate) fn best_moves(three: &Three) -> Vec<Coordinate> {
+    let windows = windows(three.size(), three.size(), RUN);
+    let me = three.current_side();
+    let scores: Vec<_> = by_center(three).into_iter()
+        .map(|coord| (coord, paranoid(&three.after(coord), &windows, me, DEPTH - 1, i32::MIN, i32::MAX)))
+        .collect();
+    let best = scores.iter().map(|(_, score)| *score).max().unwrap_or(0);
+    scores.into_iter().filter(|(_, score)| *score == best).map(|(coord, _)| coord).collect()
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Drawing
+//--------------------------------------------------------------------------------------
+
+/// The board as the players see it, with small marks on the grid, the winning three
+/// in a row picked out, and the cursor showing the mark it would place
+pub(crate) struct View<'a> {
+    three: &'a Three,
+    cursor: Option<Coordinate>,  // The space the cursor is on, if it's a person's turn
+}
+
+impl<'a> View<'a> {
+    pub(crate) fn new(three: &'a Three, cursor: Option<Coordinate>) -> Self {
+        View { three, cursor }
+    }
+}
+
+impl Draw for View<'_> {
+    fn draw(&self, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> crossterm::Result<()> {
+        let size = self.three.size();
+        small_grid(size, size).draw_with_color(term_row, term_col, theme.grid, theme, glyphs)?;
+        let origin = |coord: Coordinate| {
+            let (row, col) = coord.into();
+            (term_row + row as u16 * SMALL_ROW_HEIGHT, term_col + col as u16 * SMALL_COL_WIDTH)
+        };
+
+        let line = self.three.line();
+        for coord in self.three.history() {
+            let side = self.three.get_mark(*coord).expect("Marks stay where they're played");
+            let in_line = line.as_ref().is_some_and(|line| line.contains(coord));
+            let color = if in_line { theme.win } else { theme.side(side) };
+            let (out_row, out_col) = origin(*coord);
+            side.glyph().draw_with_color(out_row + 1, out_col + 1, color, theme, glyphs)?;
+        }
+
+        if let Some(coord) = self.cursor {
+            let (out_row, out_col) = origin(coord);
+            if self.three.get_mark(coord).is_none() {
+                self.three.current_side().glyph().draw_with_color(out_row + 1, out_col + 1, theme.cursor, theme, glyphs)?;
+            }
+            SMALL_CURSOR.draw_with_color(out_row, out_col, theme.cursor, theme, glyphs)?;
+        }
+        self.three.status().draw(term_row, term_col + 104, theme, glyphs)
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Variant
+//--------------------------------------------------------------------------------------
+
+impl Variant for Three {
+    type Side = Side;
+    type Move = Coordinate;
+    type Search = ();
+
+    fn rules(&self) -> Rules {
+        Rules::Three
+    }
+
+    fn status(&self) -> GameStatus<Side> {
+        Three::status(self)
+    }
+
+    fn turn(&self) -> Side {
+        self.current_side()
+    }
+
+    fn moves(&self) -> usize {
+        self.history().len()
+    }
+
+    fn play(&mut self, coord: Coordinate) -> Result<()> {
+        Three::play(self, coord)
+    }
+
+    fn choose(&self, ai: &mut Ai) -> Coordinate {
+        ai.choose_three_move(self)
+    }
+
+    fn describe_move(&self, side: Side, coord: Coordinate) -> String {
+        describe::side_mark(side, coord)
+    }
+
+    fn instructions(&self) -> &'static str {
+        plain::THREE_INSTRUCTIONS
+    }
+
+    fn grid(&self) -> Vec<String> {
+        plain::three_grid(self)
+    }
+
+    fn parse(&self, line: &str) -> Option<Coordinate> {
+        plain::parse_square_move(line, self.size)
+    }
+
+    fn draw(&self, cursor: Option<&Cursor>, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> crossterm::Result<()> {
+        View::new(self, cursor.map(|cursor| cursor.get_coordinate())).draw(term_row, term_col, theme, glyphs)
+    }
+
+    /// Only the cursor's space matters, and it stays where it was from turn to turn
+    fn cursor(&self, previous: Option<Cursor>) -> Cursor {
+        previous.unwrap_or_else(|| Cursor::new(Mark::X, self.size / 2, self.size / 2))
+    }
+
+    fn act(&self, cursor: &mut Cursor, action: Action) -> Option<Coordinate> {
+        match action {
+            Action::Shift(direction) => { cursor.shift_on(direction, self.size); None },
+            Action::Place => Some(cursor.get_coordinate()),
+            _ => None,
+        }
+    }
+
+    fn prompt(&self, cursor: &Cursor) -> Option<String> {
+        Some(describe::coordinate(cursor.get_coordinate()))
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Tests
+//--------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variant::play;
+
+    #[test]
+    fn test_round_robin() {
+        // Turns go X, O, Y and back to X, and Y wins down the last column
+        let mut three = Three::new(4);
+        play(&mut three, &[(0, 0), (1, 1), (0, 3), (1, 0), (2, 2), (1, 3)].map(Coordinate::from));
+        assert_eq!(three.current_side(), Side::X);
+        assert_eq!(three.get_mark((0, 3).into()), Some(Side::Y));
+        assert_eq!(three.play((1, 1).into()), Err(GameError::SpaceOccupied));
+        assert_eq!(three.play((0, 4).into()), Err(GameError::OffBoard));
+        assert_eq!(three.threats(Side::Y), vec![Coordinate::new(2, 3)]);
+        play(&mut three, &[(3, 3), (3, 0), (2, 3)].map(Coordinate::from));
+        assert_eq!(three.status(), GameStatus::Winner(Side::Y));
+        assert_eq!(three.line(), Some([(0, 3), (1, 3), (2, 3)].map(Coordinate::from).to_vec()));
+    }
+
+    #[test]
+    fn test_blocks_the_next_player() {
+        // X to move can't win, and O threatens to on their turn, so X blocks
+        let mut three = Three::new(4);
+        play(&mut three, &[(0, 0), (3, 0), (3, 3), (2, 3), (3, 1), (0, 3)].map(Coordinate::from));
+        assert_eq!(three.current_side(), Side::X);
+        assert!(three.threats(Side::X).is_empty());
+        assert_eq!(three.threats(Side::O), vec![Coordinate::new(3, 2)]);
+        assert_eq!(best_moves(&three), vec![Coordinate::new(3, 2)]);
+    }
+}
@@ -0,0 +1,197 @@
+//! Headless tournaments: many games between computer players, with nothing drawn, and
+//! a table of results at the end.
+
+use crate::ai::Rng;
+use crate::error::BotError;
+use crate::game::{Game, GameStatus, Player, Rules};
+use crate::options::TournamentOptions;
+use crate::players::Seat;
+use crate::profile::Record;
+use crate::rating::{self, Ratings};
+
+use std::io::Write;
+
+
+//--------------------------------------------------------------------------------------
+//-- Results
+//--------------------------------------------------------------------------------------
+
+/// Results for one player (or one side of a pairing), split by the mark they played
+#[derive(Debug, Default, Clone, Copy)]
+struct Split {
+    as_x: Record,
+    as_o: Record,
+}
+
+impl Split {
+    fn add(&mut self, status: &GameStatus, mark: Player) {
+        match mark {
+            Player::X => self.as_x.add(status, mark),
+            Player::O => self.as_o.add(status, mark),
+        }
+    }
+
+    fn total(&self) -> Record {
+        self.as_x.combine(&self.as_o)
+    }
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Playing games
+//--------------------------------------------------------------------------------------
+
+/// Play one game between two computer players without drawing anything
+pub(crate) fn play_game(x: &mut Seat, o: &mut Seat, rules: Rules) -> Result<Game, BotError> {
+    x.new_game(rules)?;
+    o.new_game(rules)?;
+    let mut game = Game::with_rules(rules);
+    while let GameStatus::Pending(player) = game.status() {
+        let seat = match player { Player::X => &mut *x, Player::O => &mut *o };
+        seat.play_move(&mut game)?;
+    }
+    Ok(game)
+}
+
+/// Borrow two different seats at once, `i` must be less than `j`
+fn pair_mut(seats: &mut [Seat], i: usize, j: usize) -> (&mut Seat, &mut Seat) {
+    let (left, right) = seats.split_at_mut(j);
+    (&mut left[i], &mut right[0])
+}
+
+/// Give each seat a distinct name, numbering any repeats
+fn unique_names(seats: &[Seat]) -> Vec<String> {
+    let names: Vec<_> = seats.iter().map(Seat::name).collect();
+    names.iter().enumerate().map(|(idx, name)| {
+        let repeats = names.iter().filter(|other| *other == name).count();
+        if repeats == 1 { return name.clone(); }
+        let nth = names[..=idx].iter().filter(|other| *other == name).count();
+        format!("{} #{}", name, nth)
+    }).collect()
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Tournament
+//--------------------------------------------------------------------------------------
+
+/// Play every pair of players against each other, alternating who goes first, then
+/// print the results to stdout. Each game counts towards the players' ratings.
+pub(crate) fn run(options: &TournamentOptions) -> std::io::Result<()> {
+    let mut ratings = if options.rated { Some(Ratings::load()?) } else { None };
+    let mut rng = options.seed.map(Rng::new).unwrap_or_else(Rng::from_time);
+    let mut seats = Vec::new();
+    for spec in options.players.iter() {
+        seats.push(Seat::from_spec(spec, Rng::new(rng.next_u64()))?);
+    }
+    let names = unique_names(&seats);
+
+    let mut standings = vec![Split::default(); seats.len()];
+    let mut pairings = Vec::new();
+    for i in 0..seats.len() {
+        for j in (i + 1)..seats.len() {
+            let mut pairing = Split::default();
+            for round in 0..options.games {
+                let (a, b) = pair_mut(&mut seats, i, j);
+                let (game, a_mark) = if round % 2 == 0 {
+                    (play_game(a, b, options.rules)?, Player::X)
+                } else {
+                    (play_game(b, a, options.rules)?, Player::O)
+                };
+                let mut b_mark = a_mark;
+                b_mark.toggle();
+                let status = game.status();
+                if let Some(ratings) = ratings.as_mut() {
+                    let (x, o) = if a_mark == Player::X { (i, j) } else { (j, i) };
+                    if let (Some(x), Some(o)) = (seats[x].rating_key(), seats[o].rating_key()) {
+                        let pool_key = |key: &str| rating::pool_key(key, options.rules);
+                        ratings.record_game(&pool_key(&x), &pool_key(&o), &status);
+                    }
+                }
+                pairing.add(&status, a_mark);
+                standings[i].add(&status, a_mark);
+                standings[j].add(&status, b_mark);
+            }
+            pairings.push((i, j, pairing));
+        }
+    }
+
+    let mut stdout = std::io::stdout();
+    let rows: Vec<_> = pairings.iter()
+        .map(|(i, j, split)| (format!("{} vs {}", names[*i], names[*j]), *split))
+        .collect();
+    print_table(&mut stdout, "Pairing (results for the first player)", &rows)?;
+    writeln!(stdout)?;
+    let rows: Vec<_> = names.iter().cloned().zip(standings).collect();
+    print_table(&mut stdout, "Player", &rows)?;
+
+    match ratings {
+        Some(ratings) => ratings.save(),
+        None => Ok(()),
+    }
+}
+
+/// Print one row per entry, with overall results and results as X and as O
+fn print_table(output: &mut impl Write, title: &str, rows: &[(String, Split)]) -> std::io::Result<()> {
+    let width = rows.iter().map(|(name, _)| name.len()).chain(Some(title.len())).max().unwrap_or(0);
+    writeln!(
+        output, "{:<width$}  {:>6}  {:>6} {:>6} {:>6}  | X {:>6} {:>6} {:>6}  | O {:>6} {:>6} {:>6}",
+        title, "Games", "W", "D", "L", "W", "D", "L", "W", "D", "L", width = width,
+    )?;
+    for (name, split) in rows {
+        let (total, x, o) = (split.total(), split.as_x, split.as_o);
+        writeln!(
+            output, "{:<width$}  {:>6}  {:>6} {:>6} {:>6}  |   {:>6} {:>6} {:>6}  |   {:>6} {:>6} {:>6}",
+            name, total.games(), total.wins, total.draws, total.losses,
+            x.wins, x.draws, x.losses, o.wins, o.draws, o.losses, width = width,
+        )?;
+    }
+    Ok(())
+}
+
+
+//--------------------------------------------------------------------------------------
+//-- Tests
+//--------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{Ai, Level};
+
+    #[test]
+    fn test_perfect_players_draw() {
+        let mut x = Seat::Ai(Ai::new(Level::Perfect, Rng::new(1)));
+        let mut o = Seat::Ai(Ai::new(Level::Perfect, Rng::new(2)));
+        for _ in 0..20 {
+            let game = play_game(&mut x, &mut o, Rules::Standard).expect("Failed to play");
+            assert_eq!(game.status(), GameStatus::Draw);
+        }
+    }
+
+    #[test]
+    fn test_perfect_never_loses_to_random() {
+        let mut perfect = Seat::Ai(Ai::new(Level::Perfect, Rng::new(3)));
+        let mut random = Seat::Ai(Ai::new(Level::Random, Rng::new(4)));
+        let mut record = Record::default();
+        for _ in 0..50 {
+            record.add(&play_game(&mut perfect, &mut random, Rules::Standard).unwrap().status(), Player::X);
+            record.add(&play_game(&mut random, &mut perfect, Rules::Standard).unwrap().status(), Player::O);
+        }
+        assert_eq!(record.losses, 0);
+        assert!(record.wins > 0);
+    }
+
+    #[test]
+    fn test_misere_perfect_never_loses_to_medium() {
+        let mut perfect = Seat::Ai(Ai::new(Level::Perfect, Rng::new(5)));
+        let mut medium = Seat::Ai(Ai::new(Level::Medium, Rng::new(6)));
+        let mut record = Record::default();
+        for _ in 0..50 {
+            record.add(&play_game(&mut perfect, &mut medium, Rules::Misere).unwrap().status(), Player::X);
+            record.add(&play_game(&mut medium, &mut perfect, Rules::Misere).unwrap().status(), Player::O);
+        }
+        assert_eq!(record.losses, 0);
+        assert!(record.wins > 0);
+    }
+}
@@ -0,0 +1,319 @@
+//! The full-screen game, drawn with big glyphs in the terminal's alternate screen
+
+use crate::analysis::Analysis;
+use crate::cursor::{Cursor, Direction};
+use crate::describe;
+use crate::display::{Draw, Glyphs};
+use crate::game::{Game, GameStatus, Player, Rules};
+use crate::input::{read_action, Action};
+use crate::players::Players;
+use crate::review::{self, Blunder};
+use crate::solver::Solver;
+use crate::theme::Theme;
+use crate::variant::Variant;
+
+use crossterm::{execute, Result};
+use crossterm::cursor::{MoveDown, MoveTo};
+use crossterm::style::{Color, Print, ResetColor, SetForegroundColor};
+use crossterm::terminal::{enable_raw_mode, disable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, Clear, ClearType};
+
+use std::time::Duration;
+
+const TOP: u16 = 10;
+const LEFT: u16 = 10;
+
+/// How long to show the board before a program makes its move, so people can follow
+const COMPUTER_DELAY: Duration = Duration::from_millis(500);
+
+/// How long a mark dropped under gravity rules takes to fall each row
+const FALL_DELAY: Duration = Duration::from_millis(40);
+
+/// Play a game in the alternate screen, leaving the final board on screen afterwards.
+/// Returns the game as it stood when it ended or the players quit. Lines
+/// wrap around the edges if `wrap` is set.
+pub(crate) fn play(players: &mut Players, rules: Rules, wrap: bool, theme: &Theme, glyphs: Glyphs) -> Result<Game> {
+    // Setup
+    let mut stdout = std::io::stdout();
+    let mut game = Game::with_rules(rules).with_wrap(wrap);
+    enable_raw_mode()?;
+
+    // Draw the game in an alternate screen
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut result = run(&mut game, players, theme, glyphs);
+    if let (Ok(()), GameStatus::Winner(_) | GameStatus::Draw) = (&result, game.status()) {
+        result = review_game(&game, players, theme, glyphs);
+    }
+
+    // Cleanup, even if the game was cut short by an error
+    disable_raw_mode()?;
+    execute!(stdout, LeaveAlternateScreen)?;
+    execute!(stdout, Clear(ClearType::All))?;
+    game.draw(TOP, LEFT, theme, glyphs)?;
+    draw_name(&game, players, theme, glyphs)?;
+    execute!(stdout, MoveDown(40), Print("\n"))?;
+    result.map(|_| game)
+}
+
+/// Print the name of the player to move, or the winner, under the status banner.
+/// Nothing is printed for guests, who have no name. Unusual rules are printed below.
+fn draw_name(game: &Game, players: &Players, theme: &Theme, glyphs: Glyphs) -> Result<()> {
+    if let Some(line) = describe::wrap(game) {
+        execute!(std::io::stdout(), MoveTo(LEFT + 104, TOP + 18), SetForegroundColor(theme.label), Print(line), ResetColor)?;
+    }
+    draw_status_name(&game.status(), game.rules(), players, theme, glyphs)
+}
+
+/// As `draw_name`, for a game with the given status and rules
+fn draw_status_name(status: &GameStatus, rules: Rules, players: &Players, theme: &Theme, glyphs: Glyphs) -> Result<()> {
+    let seat = |player: Player| (players.seat(player).shown_name(), theme.player(player));
+    draw_named_status(status, rules, seat, theme, glyphs)
+}
+
+/// As `draw_name`, whoever the players are. `seat` gives the name shown for a player,
+/// if any, and their color.
+fn draw_named_status<P: Copy>(status: &GameStatus<P>, rules: Rules, seat: impl Fn(P) -> (Option<String>, Color), theme: &Theme, glyphs: Glyphs) -> Result<()> {
+    if let Some(rules) = describe::rules(rules) {
+        execute!(std::io::stdout(), MoveTo(LEFT + 104, TOP + 16), SetForegroundColor(theme.label), Print(glyphs.text(rules)), ResetColor)?;
+    }
+
+    // Everyone but guests has a name to show
+    let (player, verb) = match *status {
+        GameStatus::Pending(player) => (player, "to play"),
+        GameStatus::Winner(player) => (player, "wins!"),
+        GameStatus::Draw => return Ok(()),
+    };
+    if let (Some(name), color) = seat(player) {
+        let line = glyphs.text(&format!("{} {}", name, verb));
+        execute!(std::io::stdout(), MoveTo(LEFT + 104, TOP + 15), SetForegroundColor(color), Print(line), ResetColor)?;
+    }
+    Ok(())
+}
+
+/// Print a line of text under the player's name, in the cursor's color
+fn draw_hint(line: &str, theme: &Theme, glyphs: Glyphs) -> Result<()> {
+    execute!(std::io::stdout(), MoveTo(LEFT + 104, TOP + 17), SetForegroundColor(theme.cursor), Print(glyphs.text(line)), ResetColor)
+}
+
+/// Offer to review the mistakes in a finished game, then step through them one at a
+/// time, showing the position each was made in
+fn review_game(game: &Game, players: &Players, theme: &Theme, glyphs: Glyphs) -> Result<()> {
+    let mut stdout = std::io::stdout();
+    let blunders = review::blunders(game, &mut Solver::new());
+    if blunders.is_empty() { return Ok(()); }
+
+    execute!(stdout, Clear(ClearType::All))?;
+    game.draw(TOP, LEFT, theme, glyphs)?;
+    draw_name(game, players, theme, glyphs)?;
+    let plural = if blunders.len() == 1 { "mistake" } else { "mistakes" };
+    draw_hint(&format!("Press Enter to review {} {}, or Escape to finish.", blunders.len(), plural), theme, glyphs)?;
+    loop {
+        match read_action()? {
+            Some(Action::Place) => break,
+            Some(Action::Quit) => return Ok(()),
+            _ => {},
+        }
+    }
+
+    let mut idx = 0;
+    loop {
+        draw_blunder(&blunders[idx], idx, blunders.len(), theme, glyphs)?;
+        match read_action()? {
+            Some(Action::Shift(Direction::Left)) | Some(Action::Shift(Direction::Up)) => {
+                idx = idx.checked_sub(1).unwrap_or(blunders.len() - 1);
+            },
+            Some(Action::Shift(Direction::Right)) | Some(Action::Shift(Direction::Down)) => {
+                idx = (idx + 1) % blunders.len();
+            },
+            Some(Action::Place) | Some(Action::Quit) => return Ok(()),
+            _ => {},
+        }
+    }
+}
+
+/// Draw the position a blunder was made in, with the move that was made in place of
+/// the cursor
+fn draw_blunder(blunder: &Blunder, idx: usize, count: usize, theme: &Theme, glyphs: Glyphs) -> Result<()> {
+    let (row, col) = blunder.mv.coord.into();
+    execute!(std::io::stdout(), Clear(ClearType::All))?;
+    Cursor::new(blunder.mv.mark, row, col).draw(TOP, LEFT, theme, glyphs)?;
+    blunder.position.draw(TOP, LEFT, theme, glyphs)?;
+    draw_hint(&describe::blunder(blunder), theme, glyphs)?;
+    let help = format!("Mistake {} of {}. Left and right for the others, Enter or Escape to finish.", idx + 1, count);
+    execute!(std::io::stdout(), MoveTo(LEFT + 104, TOP + 19), SetForegroundColor(theme.label), Print(help), ResetColor)
+}
+
+/// The game loop, from the first move until the game ends or a player quits
+fn run(game: &mut Game, players: &mut Players, theme: &Theme, glyphs: Glyphs) -> Result<()> {
+    let mut stdout = std::io::stdout();
+    let mut solver = Solver::new();
+    let mut analyze = false;
+    players.new_game(game)?;
+    'game: while let GameStatus::Pending(player) = game.status() {
+
+        // Programs move on their own, after a moment
+        if players.is_computer(player) {
+            execute!(stdout, Clear(ClearType::All))?;
+            game.draw(TOP, LEFT, theme, glyphs)?;
+            draw_name(game, players, theme, glyphs)?;
+            std::thread::sleep(COMPUTER_DELAY);
+            players.play_computer_move(game)?;
+            continue 'game;
+        }
+
+        // While the game status is pending, there must be at least one available space
+        let mut gc = Cursor::first_available(game).unwrap();
+        let mut hint = None;
+        'control: loop {
+            execute!(stdout, Clear(ClearType::All))?;
+            gc.draw(TOP, LEFT, theme, glyphs)?;
+            game.draw(TOP, LEFT, theme, glyphs)?;
+            draw_name(game, players, theme, glyphs)?;
+            if let Some((mv, outcome)) = hint {
+                draw_hint(&describe::hint(player, mv, outcome), theme, glyphs)?;
+            }
+            if let Some(line) = describe::numbers(game) {
+                execute!(stdout, MoveTo(LEFT + 104, TOP + 18), SetForegroundColor(theme.label), Print(line), ResetColor)?;
+            }
+            if analyze {
+                Analysis::new(game, &mut solver).draw(TOP, LEFT, theme, glyphs)?;
+            }
+            match read_action()? {
+                Some(Action::Quit) => break 'game,
+                Some(Action::Shift(direction)) => gc.shift(direction),
+                Some(Action::Analyze) => analyze = !analyze,
+                Some(Action::Hint) => {
+                    // Asking again moves on to the next equally good move
+                    hint = solver.hint(game, hint.map(|(mv, _)| mv));
+                    if let Some((mv, _)) = hint {
+                        gc.move_to(mv.coord);
+                        gc.set_mark(mv.mark);
+                        gc.select(mv.from);
+                    }
+                },
+                Some(Action::ToggleMark) => gc.toggle_mark(game),
+                Some(Action::ChooseMark(mark)) if game.marks().contains(&mark) => gc.set_mark(mark),
+                Some(Action::Place) => {
+                    // Under morris rules, the first press may only pick up a mark
+                    let mv = match gc.choose(game) {
+                        Some(mv) => mv,
+                        None => continue 'control,
+                    };
+                    if let Err(e) = game.play(mv) {
+                        println!("{}, please try again!", e);
+                    }
+                    continue 'game;
+                },
+                _ => continue 'control,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Play a variant with rules of its own in the alternate screen, leaving the final
+/// board on screen afterwards. Returns the game as it stood when it ended or the
+/// players quit.
+pub(crate) fn play_variant<V: Variant>(mut game: V, players: &mut Players, theme: &Theme, glyphs: Glyphs) -> Result<V> {
+    // Setup
+    let mut stdout = std::io::stdout();
+    enable_raw_mode()?;
+
+    // Draw the game in an alternate screen
+    execute!(stdout, EnterAlternateScreen)?;
+    let result = run_variant(&mut game, players, theme, glyphs);
+
+    // Cleanup, even if the game was cut short by an error
+    disable_raw_mode()?;
+    execute!(stdout, LeaveAlternateScreen)?;
+    execute!(stdout, Clear(ClearType::All))?;
+    game.draw(None, TOP, LEFT, theme, glyphs)?;
+    draw_variant_name(&game, players, theme, glyphs)?;
+    if !matches!(game.status(), GameStatus::Pending(_)) {
+        draw_hint(&game.score().unwrap_or_else(|| game.describe_status()), theme, glyphs)?;
+    }
+    execute!(stdout, MoveDown(40), Print("\n"))?;
+    result.map(|_| game)
+}
+
+/// As `draw_name`, for a variant
+fn draw_variant_name<V: Variant>(game: &V, players: &Players, theme: &Theme, glyphs: Glyphs) -> Result<()> {
+    let seat = |side: V::Side| {
+        let side = side.into();
+        (players.side(side).shown_name(), theme.side(side))
+    };
+    draw_named_status(&game.status(), game.rules(), seat, theme, glyphs)
+}
+
+/// The game loop for a variant, from the first move until the game ends or a player
+/// quits
+fn run_variant<V: Variant>(game: &mut V, players: &mut Players, theme: &Theme, glyphs: Glyphs) -> Result<()> {
+    let mut stdout = std::io::stdout();
+    let mut search = V::Search::default();
+    let mut cursor = None;
+    players.new_variant_game()?;
+    'game: while let GameStatus::Pending(side) = game.status() {
+
+        // Programs move on their own, after a moment
+        if players.side(side.into()).is_computer() {
+            execute!(stdout, Clear(ClearType::All))?;
+            game.draw(None, TOP, LEFT, theme, glyphs)?;
+            draw_variant_name(game, players, theme, glyphs)?;
+            std::thread::sleep(COMPUTER_DELAY);
+            let before = game.clone();
+            let mv = players.play_computer_variant_move(game)?;
+            animate(&before, mv, players, theme, glyphs)?;
+            continue 'game;
+        }
+
+        let mut gc = game.cursor(cursor.take());
+        let mut hint: Option<(V::Move, String)> = None;
+        loop {
+            execute!(stdout, Clear(ClearType::All))?;
+            game.draw(Some(&gc), TOP, LEFT, theme, glyphs)?;
+            draw_variant_name(game, players, theme, glyphs)?;
+            let line = match &hint {
+                Some((_, line)) => Some(line.clone()),
+                None => game.prompt(&gc),
+            };
+            if let Some(line) = line { draw_hint(&line, theme, glyphs)?; }
+            if let Some(help) = game.help() {
+                execute!(stdout, MoveTo(LEFT + 104, TOP + 19), SetForegroundColor(theme.label), Print(help), ResetColor)?;
+            }
+            match read_action()? {
+                Some(Action::Quit) => break 'game,
+                Some(Action::Hint) => {
+                    // Asking again moves on to the next equally good move
+                    hint = game.hint(&mut search, hint.map(|(mv, _)| mv));
+                    if let Some((mv, _)) = hint { game.point(&mut gc, mv); }
+                },
+                Some(action) => {
+                    let mv = match game.act(&mut gc, action) {
+                        Some(mv) => mv,
+                        None => continue,
+                    };
+                    let before = game.clone();
+                    match game.play(mv) {
+                        Ok(()) => animate(&before, mv, players, theme, glyphs)?,
+                        Err(e) => { println!("{}, please try again!", e); continue; },
+                    }
+                    cursor = Some(gc);
+                    continue 'game;
+                },
+                None => {},
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Show a move that was just made, in the game as it was before, if the variant
+/// animates its moves
+fn animate<V: Variant>(before: &V, mv: V::Move, players: &Players, theme: &Theme, glyphs: Glyphs) -> Result<()> {
+    before.animate(mv, &mut |view| {
+        execute!(std::io::stdout(), Clear(ClearType::All))?;
+        view.draw(TOP, LEFT, theme, glyphs)?;
+        draw_variant_name(before, players, theme, glyphs)?;
+        std::thread::sleep(FALL_DELAY);
+        Ok(())
+    })
+}
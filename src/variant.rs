@@ -0,0 +1,137 @@
+//! The games played on boards of their own rather than the 3x3 board of `Game`:
+//! notakto on more than one board, quantum tic-tac-toe, Order and Chaos, gravity rules,
+//! 3D and three players. Each way of showing the game plays every one of them with a
+//! single game loop, through the `Variant` trait.
+
+use crate::ai::Ai;
+use crate::cursor::Cursor;
+use crate::describe;
+use crate::display::{Draw, Glyphs};
+use crate::error::Result;
+use crate::game::{GameStatus, Rules};
+use crate::input::Action;
+use crate::theme::Theme;
+use crate::three::Side;
+
+/// A game with rules of its own, as the game loops see it
+pub(crate) trait Variant: Clone {
+    /// Who takes turns: X and O, or X, O and Y in three-player games
+    type Side: Copy + std::fmt::Display + Into<Side>;
+
+    /// Everything that can be done in one turn
+    type Move: Copy;
+
+    /// Whatever suggesting a move remembers from one hint to the next
+    type Search: Default;
+
+    /// What a move is called when one can't be read from a line of text
+    const MOVE: &'static str = "move";
+
+    fn rules(&self) -> Rules;
+
+    fn status(&self) -> GameStatus<Self::Side>;
+
+    /// The player to move. The game must still be pending.
+    fn turn(&self) -> Self::Side;
+
+    /// How many moves have been made so far
+    fn moves(&self) -> usize;
+
+    /// Make a move for the player to move
+    fn play(&mut self, mv: Self::Move) -> Result<()>;
+
+    /// Ask the built-in computer player for a move. The game must still be pending.
+    fn choose(&self, ai: &mut Ai) -> Self::Move;
+
+    /// Suggest a move for the player to move, and describe it. Asking again moves on
+    /// from `previous` to the next equally good move. Most variants have no hints.
+    fn hint(&self, _search: &mut Self::Search, _previous: Option<Self::Move>) -> Option<(Self::Move, String)> {
+        None
+    }
+
+    /// The score of a finished game in half points, as (X, O), for variants where a
+    /// loser can score too. Most variants are scored by `status` alone.
+    fn half_points(&self) -> Option<(u8, u8)> {
+        None
+    }
+
+    //-- Words
+
+    /// The name a player goes by in this variant, e.g. "Chaos"
+    fn name(&self, side: Self::Side) -> String {
+        side.to_string()
+    }
+
+    /// Describe a move that was just made, e.g. "X marks row 1, column 1."
+    fn describe_move(&self, side: Self::Side, mv: Self::Move) -> String;
+
+    /// Describe the game status, e.g. "O to play."
+    fn describe_status(&self) -> String {
+        describe::status(&self.status())
+    }
+
+    /// Anything to know about this game beyond the rules, e.g. the size of the board
+    fn details(&self) -> Option<String> {
+        None
+    }
+
+    /// Anything the player to move needs to know before they do, e.g. that they must
+    /// collapse a cycle
+    fn note(&self) -> Option<String> {
+        None
+    }
+
+    /// How the points were split at the end, when the winner doesn't take them all
+    fn score(&self) -> Option<String> {
+        None
+    }
+
+    //-- Plain text
+
+    /// How to enter moves as lines of text
+    fn instructions(&self) -> &'static str;
+
+    /// Draw the board as lines of text
+    fn grid(&self) -> Vec<String>;
+
+    /// Read a move from a line of text
+    fn parse(&self, line: &str) -> Option<Self::Move>;
+
+    //-- Full screen
+
+    /// Draw the board, with the cursor on it if a person is choosing a move
+    fn draw(&self, cursor: Option<&Cursor>, term_row: u16, term_col: u16, theme: &Theme, glyphs: Glyphs) -> crossterm::Result<()>;
+
+    /// Where the cursor starts the turn. Some variants leave it where it was at the end
+    /// of the last turn, if there was one.
+    fn cursor(&self, previous: Option<Cursor>) -> Cursor;
+
+    /// Move the cursor to a suggested move. Only variants with hints need to.
+    fn point(&self, _cursor: &mut Cursor, _mv: Self::Move) {}
+
+    /// Act on a key press, returning the move to make if the player has chosen one.
+    /// Quitting and asking for hints are left to the game loop.
+    fn act(&self, cursor: &mut Cursor, action: Action) -> Option<Self::Move>;
+
+    /// A line about the cursor or the choice to be made, e.g. "layer 2, row 1, column 3"
+    fn prompt(&self, cursor: &Cursor) -> Option<String>;
+
+    /// The keys that are special to this variant
+    fn help(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Show a move before it's made, one frame at a time. Only marks dropped under
+    /// gravity rules are animated, falling down their column.
+    fn animate(&self, _mv: Self::Move, _frame: &mut dyn FnMut(&dyn Draw) -> crossterm::Result<()>) -> crossterm::Result<()> {
+        Ok(())
+    }
+}
+
+/// Make each move in turn, to set up a game for a test
+#[cfg(test)]
+pub(crate) fn play<V: Variant>(game: &mut V, moves: &[V::Move]) {
+    for mv in moves {
+        game.play(*mv).expect("Failed to add move.");
+    }
+}